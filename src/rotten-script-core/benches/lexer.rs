@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+use rotten_script_core::lexer::Lexer;
+
+/// Generates a source file similar to the generated code, which has the given number of lines.
+fn generate_source(lines: usize) -> String {
+    let mut source = String::new();
+    let mut ln = 0;
+    let mut i = 0;
+    while ln < lines {
+        source += &format!(
+            "// generated function {i}
+const f{i} = (a: number, b: string): number => {{
+    let x = a * {i} + 0.5;
+    if x >= 10 && b != \"value{i}\" {{
+        x += 1;
+    }}
+    match x {{ 0 => 1, _ => x ** 2 }}
+}};
+",
+            i = i
+        );
+        ln += 8;
+        i += 1;
+    }
+    source
+}
+
+fn bench(lines: usize, iterations: u32) {
+    let source = generate_source(lines);
+    let mut total = Duration::new(0, 0);
+    let mut token_count = 0;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let mut lexer = Lexer::new(&source, "bench.rots");
+        lexer.lex().unwrap();
+        total += start.elapsed();
+        token_count = lexer.tokens.len();
+    }
+    let average = total / iterations;
+    println!(
+        "lex {:>6} lines ({:>8} bytes, {:>7} tokens): {:>10.3} ms/iter, {:>7.2} MB/s",
+        lines,
+        source.len(),
+        token_count,
+        average.as_secs_f64() * 1000.0,
+        source.len() as f64 / average.as_secs_f64() / 1_000_000.0
+    );
+}
+
+fn main() {
+    for (lines, iterations) in [(1_000, 20), (10_000, 5), (50_000, 2)] {
+        bench(lines, iterations);
+    }
+}
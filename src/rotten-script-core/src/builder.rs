@@ -1,15 +1,71 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
 use crate::{
-    lexer::{reserved_word::ReservedWord, token::TokenBase},
+    lexer::{
+        reserved_word::ReservedWord,
+        string_literal,
+        token::{Token, TokenBase},
+    },
+    manifest::ModuleFormat,
     parser::{ast::Ast, ast_type::AstType, non_terminal::NonTerminal},
     semantic_analyzer::project::Project,
+    source_map::{Mapping, SourceMap},
 };
 
+/// How the trailing expression of a block is emitted.
+#[derive(Clone, Copy)]
+enum BlockTail {
+    /// `return expr;` (e.g. function bodies and the branches of an if expression lowered to an IIFE)
+    Return,
+    /// `expr;` (the value of the block is not used)
+    Discard,
+}
+
+/// The outputs built from a source file.
+#[derive(Debug)]
+pub struct BuildOutput {
+    /// JavaScript
+    pub code: String,
+    /// TypeScript declarations (`.d.ts`)
+    pub declaration: String,
+    /// The source map of `code` if it is written to a separate file
+    pub source_map: Option<SourceMap>,
+}
+
 pub struct Builder<'a> {
     semantic_tree: &'a Project<'a>,
     result: String,
+    // positions of the emitted terminals in `result`
+    mappings: Vec<Mapping>,
     debug_mode: bool,
+    module_format: ModuleFormat,
+    // (exported name, local name) emitted at the end of the file,
+    // i.e. `exports.a = a;` in CommonJS and `export default a;` in ES modules
+    exports: Vec<(String, String)>,
+}
+
+/// Converts the doc comment to JSDoc, which is shown by editors, e.g. `/// Adds` is emitted as
+/// ```text
+/// /**
+///  * Adds
+///  */
+/// ```
+pub fn to_jsdoc(doc_comment: &str) -> String {
+    let mut result = String::from("/**\n");
+    for line in doc_comment.lines() {
+        // `*/` would end the comment
+        let line = line.replace("*/", "*\\/");
+        if line.is_empty() {
+            result.push_str(" *\n");
+        } else {
+            result.push_str(&format!(" * {}\n", line));
+        }
+    }
+    result.push_str(" */\n");
+    result
 }
 
 // TODO: unparse using semantic-analyzed tree
@@ -19,6 +75,9 @@ impl Builder<'_> {
             semantic_tree: tree,
             debug_mode: false,
             result: String::new(),
+            mappings: Vec::new(),
+            module_format: ModuleFormat::default(),
+            exports: Vec::new(),
         }
     }
 
@@ -26,31 +85,60 @@ impl Builder<'_> {
         self.debug_mode = true;
     }
 
-    pub fn unparse(&mut self) -> HashMap<String, String> {
+    pub fn set_module_format(&mut self, module_format: ModuleFormat) {
+        self.module_format = module_format;
+    }
+
+    /// Returns the code of each file and the positions of the source tokens in it.
+    pub fn unparse(&mut self) -> HashMap<String, (String, Vec<Mapping>)> {
+        let paths = self.semantic_tree.file_maps.keys().cloned().collect();
+        self.unparse_files(&paths)
+    }
+
+    /// Same as `unparse` but builds only the files of `paths`.
+    pub fn unparse_files(
+        &mut self,
+        paths: &HashSet<String>,
+    ) -> HashMap<String, (String, Vec<Mapping>)> {
         let mut built_map = HashMap::new();
         if self.debug_mode {
             self.semantic_tree.print_project_tree();
         }
 
-        for (file_name, map) in &self.semantic_tree.file_maps {
+        let file_maps = self.semantic_tree.file_maps.iter();
+        for (file_name, map) in file_maps.filter(|(x, _)| paths.contains(*x)) {
             let ast = map.ast;
-            if ast.children.is_some() {
-                ast.children.as_ref().unwrap().iter().for_each(|x| {
+            if let Some(children) = &ast.children {
+                children.iter().for_each(|x| {
                     self.unparse_rec(x, 0);
                 });
             }
-            let entry = self.semantic_tree.get_entrypoint_func();
+            for (exported, local) in std::mem::take(&mut self.exports) {
+                match self.module_format {
+                    ModuleFormat::Esm => {
+                        self.result
+                            .push_str(&format!("export default {};\n", local));
+                    }
+                    ModuleFormat::CommonJs => {
+                        self.result
+                            .push_str(&format!("exports.{} = {};\n", exported, local));
+                    }
+                }
+            }
 
-            if entry.is_some()
-                && &self.semantic_tree.member_map[&(entry.as_ref().unwrap().full_path)].file_name
-                    == file_name
-            {
-                self.result
-                    .push_str(&format!("\n{}();\n", entry.unwrap().name));
+            if let Some(entry) = self.semantic_tree.get_entrypoint_func() {
+                if &self.semantic_tree.member_map[&entry.full_path].file_name == file_name {
+                    self.result.push_str(&format!("\n{}();\n", entry.name));
+                }
             }
 
-            built_map.insert(file_name.clone(), self.result.clone());
-            self.result.clear();
+            built_map.insert(
+                file_name.clone(),
+                (
+                    std::mem::take(&mut self.result),
+                    std::mem::take(&mut self.mappings),
+                ),
+            );
         }
         built_map
     }
@@ -106,11 +194,25 @@ impl Builder<'_> {
                         self.unparse_rec(item, depth);
                     }
                 }
+                NonTerminal::UnaryExpression => {
+                    let children = ast.children.as_ref().unwrap();
+                    // avoid `a--b` or `a++b`
+                    if self.result.ends_with(['-', '+']) {
+                        self.result.push(' ');
+                    }
+                    self.unparse_rec(&children[0], depth);
+                    self.unparse_rec(&children[1], depth);
+                }
                 NonTerminal::ParenthesizedExpression => {
                     self.result.push('(');
                     self.unparse_rec(&ast.children.as_ref().unwrap()[0], depth);
                     self.result.push(')');
                 }
+                NonTerminal::TemplateLiteral => {
+                    for child in ast.children.as_ref().unwrap() {
+                        self.unparse_rec(child, depth);
+                    }
+                }
                 NonTerminal::ExponentiationExpression => {
                     let children = ast.children.as_ref().unwrap();
                     if children.len() == 1 {
@@ -122,26 +224,34 @@ impl Builder<'_> {
                     }
                 }
                 NonTerminal::FunctionExpression => {
-                    self.result.push_str("() => ");
                     let children = ast.children.as_ref().unwrap();
-                    self.unparse_rec(&children[if children.len() == 1 { 0 } else { 1 }], depth);
+                    self.unparse_rec(&children[0], depth);
+                    self.result.push_str(" => ");
+                    self.unparse_block(children.last().unwrap(), depth, BlockTail::Return);
                 }
-                NonTerminal::CompoundExpression => {
-                    self.result.push('{');
-                    self.add_lf_with_depth_space(depth + 1);
+                NonTerminal::ParameterList => {
+                    self.result.push('(');
                     let children = ast.children.as_ref().unwrap();
-                    let last_is_statement = children.last().unwrap().ast_type
-                        != AstType::NonTerminal(NonTerminal::Expression);
-                    for item in children {
-                        self.unparse_rec(item, depth + 1);
+                    for (ind, item) in children.iter().enumerate() {
+                        if ind != 0 {
+                            self.result.push_str(", ");
+                        }
+                        self.unparse_rec(&item.children.as_ref().unwrap()[0], depth);
                     }
-
-                    if last_is_statement {
-                        self.result = self.result[0..self.result.len() - 4].to_string();
+                    self.result.push(')');
+                }
+                // the value of the block is used (e.g. `const x = { let y = 1; y + 1 };`)
+                NonTerminal::CompoundExpression => {
+                    let children = ast.children.as_ref().unwrap();
+                    if children.len() <= 1 && children.iter().all(|x| !x.is_statement()) {
+                        self.result.push('(');
+                        self.unparse_block_value(ast, depth);
+                        self.result.push(')');
                     } else {
-                        self.add_lf_with_depth_space(depth);
+                        self.result.push_str("(() => ");
+                        self.unparse_block(ast, depth, BlockTail::Return);
+                        self.result.push_str(")()");
                     }
-                    self.result.push('}');
                 }
                 NonTerminal::Args => {
                     self.result.push('(');
@@ -155,26 +265,193 @@ impl Builder<'_> {
                     self.result.push(')');
                 }
                 NonTerminal::ExpressionStatement => {
-                    self.unparse_rec(&ast.children.as_ref().unwrap()[0], depth);
-                    self.result.push(';');
-                    self.add_lf_with_depth_space(depth);
+                    self.unparse_statement_expression(&ast.children.as_ref().unwrap()[0], depth);
                 }
-                NonTerminal::ExportableConstDeclaration => {
-                    let ast_len = ast.children.as_ref().unwrap().len();
-                    match ast_len {
-                        1 => {
-                            self.unparse_rec(&ast.children.as_ref().unwrap()[0], depth);
-                        }
-                        2 => {
-                            self.result.push_str("export ");
-                            self.unparse_rec(&ast.children.as_ref().unwrap()[1], depth);
+                NonTerminal::IfExpression => {
+                    if Self::is_simple_if_expression(ast) {
+                        self.unparse_conditional_operator(ast, depth);
+                    } else {
+                        self.result.push_str("(() => {");
+                        self.add_lf_with_depth_space(depth + 1);
+                        self.unparse_if_statement(ast, depth + 1, BlockTail::Return);
+                        self.add_lf_with_depth_space(depth);
+                        self.result.push_str("})()");
+                    }
+                }
+                NonTerminal::MatchExpression => {
+                    let children = ast.children.as_ref().unwrap();
+                    if Self::is_simple_match_expression(ast) {
+                        let target = self.unparse_to_string(&children[0], depth);
+                        self.unparse_match_conditional_operator(&target, &children[1..], depth);
+                    } else {
+                        self.result.push_str("(($match) => {");
+                        self.add_lf_with_depth_space(depth + 1);
+                        for arm in &children[1..] {
+                            if self.unparse_match_arm(arm, depth + 1) {
+                                break;
+                            }
                         }
-                        3 => {
-                            self.result.push_str("export ");
-                            self.unparse_rec(&ast.children.as_ref().unwrap()[2], depth);
+                        self.result.truncate(self.result.len() - 4);
+                        self.result.push_str("})(");
+                        self.unparse_rec(&children[0], depth);
+                        self.result.push(')');
+                    }
+                }
+                NonTerminal::ForStatement => {
+                    // for (let i = start, $end = end; i < $end; i++)
+                    let children = ast.children.as_ref().unwrap();
+                    let range = children[1].children.as_ref().unwrap();
+                    let counter = self.unparse_to_string(&children[0], depth);
+                    let operator = match range[1].token.as_ref().unwrap().get_token() {
+                        Some(TokenBase::Reserved(ReservedWord::RangeInclusive)) => "<=",
+                        _ => "<",
+                    };
+
+                    self.result.push_str(&format!("for (let {} = ", counter));
+                    self.unparse_rec(&range[0], depth);
+                    if Self::is_constant_expression(&range[2]) {
+                        self.result
+                            .push_str(&format!("; {} {} ", counter, operator));
+                        self.unparse_rec(&range[2], depth);
+                    } else {
+                        // the end of the range is evaluated only once
+                        self.result.push_str(", $end = ");
+                        self.unparse_rec(&range[2], depth);
+                        self.result
+                            .push_str(&format!("; {} {} $end", counter, operator));
+                    }
+                    self.result.push_str(&format!("; {}++) ", counter));
+                    self.unparse_block(&children[2], depth, BlockTail::Discard);
+                    self.add_lf_with_depth_space(depth);
+                }
+                NonTerminal::WhileStatement => {
+                    let children = ast.children.as_ref().unwrap();
+                    self.result.push_str("while (");
+                    self.unparse_rec(&children[0], depth);
+                    self.result.push_str(") ");
+                    self.unparse_block(&children[1], depth, BlockTail::Discard);
+                    self.add_lf_with_depth_space(depth);
+                }
+                NonTerminal::ExportableConstDeclaration
+                | NonTerminal::ExportableTypeDeclaration => {
+                    if let Some(doc_comment) = &ast.doc_comment {
+                        self.result.push_str(&to_jsdoc(doc_comment));
+                    }
+                    self.unparse_export(ast);
+                    self.unparse_rec(ast.children.as_ref().unwrap().last().unwrap(), depth);
+                }
+                // class Point {
+                //     constructor({ x, y }) {
+                //         this.x = x;
+                //         this.y = y;
+                //     }
+                // }
+                NonTerminal::StructDeclaration => {
+                    let children = ast.children.as_ref().unwrap();
+                    self.result.push_str("class ");
+                    self.unparse_rec(&children[0], depth);
+                    self.result.push_str(" {");
+                    let fields = children[1..]
+                        .iter()
+                        .map(|x| x.children.as_ref().unwrap()[0].token.as_ref().unwrap())
+                        .map(|x| x.to_string())
+                        .collect::<Vec<_>>();
+                    if !fields.is_empty() {
+                        self.add_lf_with_depth_space(depth + 1);
+                        self.result
+                            .push_str(&format!("constructor({{ {} }}) {{", fields.join(", ")));
+                        for field in &fields {
+                            self.add_lf_with_depth_space(depth + 2);
+                            self.result
+                                .push_str(&format!("this.{} = {};", field, field));
                         }
-                        _ => panic!(),
+                        self.add_lf_with_depth_space(depth + 1);
+                        self.result.push('}');
+                        self.add_lf_with_depth_space(depth);
                     }
+                    self.result.push('}');
+                    self.add_lf_with_depth_space(depth);
+                }
+                // const Shape = Object.freeze({
+                //     Circle: (_0) => Object.freeze({ kind: "Circle", _0 }),
+                //     Rect: ({ w, h }) => Object.freeze({ kind: "Rect", w, h }),
+                //     Empty: Object.freeze({ kind: "Empty" }),
+                // });
+                NonTerminal::EnumDeclaration => {
+                    let children = ast.children.as_ref().unwrap();
+                    self.result.push_str("const ");
+                    self.unparse_rec(&children[0], depth);
+                    self.result.push_str(" = Object.freeze({");
+                    for variant in &children[1..] {
+                        let variant = variant.children.as_ref().unwrap();
+                        let name = variant[0].token.as_ref().unwrap().to_string();
+                        self.add_lf_with_depth_space(depth + 1);
+                        self.result.push_str(&format!("{}: ", name));
+                        let object = match variant.get(1) {
+                            Some(fields)
+                                if fields.ast_type
+                                    == AstType::NonTerminal(NonTerminal::TupleFields) =>
+                            {
+                                let fields = (0..fields.children.as_ref().unwrap().len())
+                                    .map(|x| format!("_{}", x))
+                                    .collect::<Vec<_>>();
+                                self.result
+                                    .push_str(&format!("({}) => ", fields.join(", ")));
+                                fields
+                            }
+                            Some(fields) => {
+                                let fields = fields
+                                    .children
+                                    .as_ref()
+                                    .unwrap()
+                                    .iter()
+                                    .map(|x| {
+                                        x.children.as_ref().unwrap()[0].token.as_ref().unwrap()
+                                    })
+                                    .map(|x| x.to_string())
+                                    .collect::<Vec<_>>();
+                                self.result
+                                    .push_str(&format!("({{ {} }}) => ", fields.join(", ")));
+                                fields
+                            }
+                            None => vec![],
+                        };
+                        let members = std::iter::once(format!("kind: \"{}\"", name))
+                            .chain(object)
+                            .collect::<Vec<_>>();
+                        self.result
+                            .push_str(&format!("Object.freeze({{ {} }}),", members.join(", ")));
+                    }
+                    if children.len() > 1 {
+                        self.add_lf_with_depth_space(depth);
+                    }
+                    self.result.push_str("});");
+                    self.add_lf_with_depth_space(depth);
+                }
+                // Shape.Circle
+                NonTerminal::PathExpression => {
+                    let children = ast.children.as_ref().unwrap();
+                    self.unparse_rec(&children[0], depth);
+                    self.result.push('.');
+                    self.unparse_rec(&children[1], depth);
+                }
+                // new Point({ x: 1, y: 2 }), Shape.Rect({ w: 1, h: 2 })
+                NonTerminal::StructExpression => {
+                    let children = ast.children.as_ref().unwrap();
+                    if children[0].ast_type == AstType::Terminal {
+                        self.result.push_str("new ");
+                    }
+                    self.unparse_rec(&children[0], depth);
+                    self.result.push_str("({");
+                    for (ind, item) in children[1..].iter().enumerate() {
+                        let initializer = item.children.as_ref().unwrap();
+                        self.result.push_str(if ind == 0 { " " } else { ", " });
+                        self.unparse_rec(&initializer[0], depth);
+                        self.result.push_str(": ");
+                        self.unparse_rec(&initializer[1], depth);
+                    }
+                    self.result
+                        .push_str(if children.len() > 1 { " })" } else { "})" });
                 }
                 NonTerminal::ImportDeclaration => {
                     self.unparse_rec(&ast.children.as_ref().unwrap()[0], depth);
@@ -182,21 +459,135 @@ impl Builder<'_> {
                     self.add_lf_with_depth_space(depth);
                 }
                 NonTerminal::DefaultImportDeclaration => {
-                    self.result.push_str("import ");
-                    self.unparse_rec(&ast.children.as_ref().unwrap()[0], depth);
-                    self.result.push_str(" from ");
-                    self.unparse_rec(&ast.children.as_ref().unwrap()[1], depth);
+                    let children = ast.children.as_ref().unwrap();
+                    match self.module_format {
+                        ModuleFormat::Esm => {
+                            self.result.push_str("import ");
+                            self.unparse_rec(&children[0], depth);
+                            self.result.push_str(" from ");
+                            self.unparse_module_specifier(&children[1], depth);
+                        }
+                        // const a = require("./a").default
+                        ModuleFormat::CommonJs => {
+                            self.result.push_str("const ");
+                            self.unparse_rec(&children[0], depth);
+                            self.result.push_str(" = require(");
+                            self.unparse_module_specifier(&children[1], depth);
+                            self.result.push_str(").default");
+                        }
+                    }
                 }
                 NonTerminal::NamedImportDeclaration => {
-                    self.result.push_str("import {");
-                    let len = ast.children.as_ref().unwrap().len();
-                    for i in 0..len - 1 {
-                        self.result.push(' ');
-                        self.unparse_rec(&ast.children.as_ref().unwrap()[i], depth);
-                        self.result.push(',');
+                    let children = ast.children.as_ref().unwrap();
+                    let len = children.len();
+                    match self.module_format {
+                        ModuleFormat::Esm => {
+                            self.result.push_str("import {");
+                            for item in &children[..len - 1] {
+                                self.result.push(' ');
+                                self.unparse_rec(item, depth);
+                                self.result.push(',');
+                            }
+                            self.result.push_str(" } from ");
+                        }
+                        // const { a, b } = require("./a")
+                        ModuleFormat::CommonJs => {
+                            self.result.push_str("const {");
+                            for (ind, item) in children[..len - 1].iter().enumerate() {
+                                self.result.push_str(if ind == 0 { " " } else { ", " });
+                                self.unparse_rec(item, depth);
+                            }
+                            self.result.push_str(" } = require(");
+                        }
+                    }
+                    self.unparse_module_specifier(&children[len - 1], depth);
+                    if self.module_format == ModuleFormat::CommonJs {
+                        self.result.push(')');
                     }
-                    self.result.push_str(" } from ");
-                    self.unparse_rec(&ast.children.as_ref().unwrap()[len - 1], depth);
+                }
+                // `a as b`
+                NonTerminal::ImportSpecifier => {
+                    let children = ast.children.as_ref().unwrap();
+                    self.unparse_rec(&children[0], depth);
+                    self.result.push_str(match self.module_format {
+                        ModuleFormat::Esm => " as ",
+                        // const { a: b } = require("./a")
+                        ModuleFormat::CommonJs => ": ",
+                    });
+                    self.unparse_rec(&children[1], depth);
+                }
+                NonTerminal::NamespaceImportDeclaration => {
+                    let children = ast.children.as_ref().unwrap();
+                    match self.module_format {
+                        ModuleFormat::Esm => {
+                            self.result.push_str("import * as ");
+                            self.unparse_rec(&children[0], depth);
+                            self.result.push_str(" from ");
+                            self.unparse_module_specifier(&children[1], depth);
+                        }
+                        // const ns = require("./a")
+                        ModuleFormat::CommonJs => {
+                            self.result.push_str("const ");
+                            self.unparse_rec(&children[0], depth);
+                            self.result.push_str(" = require(");
+                            self.unparse_module_specifier(&children[1], depth);
+                            self.result.push(')');
+                        }
+                    }
+                }
+                NonTerminal::ReExportDeclaration => {
+                    let children = ast.children.as_ref().unwrap();
+                    let len = children.len();
+                    match self.module_format {
+                        ModuleFormat::Esm => {
+                            self.result.push_str("export {");
+                            for item in &children[..len - 1] {
+                                self.result.push(' ');
+                                self.unparse_rec(item, depth);
+                                self.result.push(',');
+                            }
+                            self.result.push_str(" } from ");
+                            self.unparse_module_specifier(&children[len - 1], depth);
+                            self.result.push(';');
+                            self.add_lf_with_depth_space(depth);
+                        }
+                        // exports.b = require("./a").a;
+                        ModuleFormat::CommonJs => {
+                            for item in &children[..len - 1] {
+                                let (name, alias) = match item.children.as_deref() {
+                                    Some([name, alias]) => (name, alias),
+                                    _ => (item, item),
+                                };
+                                self.result.push_str("exports.");
+                                self.unparse_rec(alias, depth);
+                                self.result.push_str(" = require(");
+                                self.unparse_module_specifier(&children[len - 1], depth);
+                                self.result.push_str(").");
+                                self.unparse_rec(name, depth);
+                                self.result.push(';');
+                                self.add_lf_with_depth_space(depth);
+                            }
+                        }
+                    }
+                }
+                NonTerminal::ReExportAllDeclaration => {
+                    let specifier = &ast.children.as_ref().unwrap()[0];
+                    match self.module_format {
+                        ModuleFormat::Esm => {
+                            self.result.push_str("export * from ");
+                            self.unparse_module_specifier(specifier, depth);
+                        }
+                        // `export *` does not re-export the default export
+                        ModuleFormat::CommonJs => {
+                            self.result
+                                .push_str("for (const [key, value] of Object.entries(require(");
+                            self.unparse_module_specifier(specifier, depth);
+                            self.result
+                                .push_str("))) if (key !== \"default\") exports[key] = value");
+                        }
+                    }
+                    self.result.push(';');
+                    self.add_lf_with_depth_space(depth);
                 }
                 NonTerminal::AssignmentStatement => {
                     let children = ast.children.as_ref().unwrap();
@@ -214,9 +605,11 @@ impl Builder<'_> {
                 _ => {}
             }
         } else {
-            match ast.token.as_ref().unwrap().get_token().as_ref().unwrap() {
-                TokenBase::String(s) => {
-                    self.result.push_str(&format!("\"{}\"", s));
+            let token = ast.token.as_ref().unwrap();
+            self.add_mapping(token);
+            match token.get_token().as_ref().unwrap() {
+                token_base @ TokenBase::String(_) | token_base @ TokenBase::Template(..) => {
+                    self.result.push_str(&token_base.to_string());
                 }
                 TokenBase::Number(n) | TokenBase::Identifier(n) => self.result.push_str(n),
                 TokenBase::Reserved(r) => {
@@ -229,6 +622,505 @@ impl Builder<'_> {
         }
     }
 
+    /// Emits `export` of ExportableConstDeclaration or ExportableTypeDeclaration.
+    /// `export default` is emitted as `export default name;` at the end of the file since
+    /// `export default const` is not valid, and in CommonJS every member is assigned to `exports`
+    /// at the end of the file instead.
+    fn unparse_export(&mut self, ast: &Ast) {
+        let children = ast.children.as_ref().unwrap();
+        let len = children.len();
+        if len == 1 {
+            return;
+        }
+        if len == 2 && self.module_format == ModuleFormat::Esm {
+            self.result.push_str("export ");
+            return;
+        }
+        let declaration = children.last().unwrap();
+        let mut name = &declaration.children.as_ref().unwrap()[0];
+        if name.ast_type == AstType::NonTerminal(NonTerminal::DeclarationBody) {
+            name = &name.children.as_ref().unwrap()[0];
+        }
+        let name = name.token.as_ref().unwrap().to_string();
+        // `export default const a = ...;`
+        let exported = if len == 3 {
+            "default".to_string()
+        } else {
+            name.clone()
+        };
+        self.exports.push((exported, name));
+    }
+
+    /// Emits the module specifier of an import. `.rots` is replaced with `.js`, and in ES modules
+    /// the relative specifiers without an extension get `.js` since they are resolved by the exact path.
+    fn unparse_module_specifier(&mut self, ast: &Ast, depth: u32) {
+        let token = ast.token.as_ref().unwrap();
+        let specifier = match token.get_token() {
+            Some(TokenBase::String(specifier)) => specifier,
+            _ => return self.unparse_rec(ast, depth),
+        };
+        let is_relative = specifier.starts_with("./") || specifier.starts_with("../");
+        let extension = Path::new(specifier).extension();
+        let specifier = if is_relative && extension.is_some_and(|x| x == "rots") {
+            format!("{}js", &specifier[..specifier.len() - "rots".len()])
+        } else if self.module_format == ModuleFormat::Esm && is_relative && extension.is_none() {
+            format!("{}.js", specifier)
+        } else {
+            specifier.clone()
+        };
+        self.add_mapping(token);
+        self.result.push_str(&string_literal::quote(&specifier));
+    }
+
+    /// Maps the current position of `result` to `token`.
+    fn add_mapping(&mut self, token: &Token) {
+        let position = token.get_token_position();
+        self.mappings.push(Mapping {
+            generated: self.result.len(),
+            source_line: position.ln - 1,
+            source_column: position.utf16_col - 1,
+        });
+    }
+
+    /// Emits an expression whose value is not used, as a statement.
+    fn unparse_statement_expression(&mut self, ast: &Ast, depth: u32) {
+        match Self::get_block_like(ast) {
+            Some(block) if block.ast_type == AstType::NonTerminal(NonTerminal::IfExpression) => {
+                self.unparse_if_statement(block, depth, BlockTail::Discard);
+                self.add_lf_with_depth_space(depth);
+            }
+            Some(block) => {
+                self.unparse_block(block, depth, BlockTail::Discard);
+                self.add_lf_with_depth_space(depth);
+            }
+            None => {
+                self.unparse_rec(ast, depth);
+                self.result.push(';');
+                self.add_lf_with_depth_space(depth);
+            }
+        }
+    }
+
+    /// Emits CompoundExpression as a JS block statement.
+    fn unparse_block(&mut self, ast: &Ast, depth: u32, tail: BlockTail) {
+        self.result.push('{');
+        let children = ast.children.as_ref().unwrap();
+        if children.is_empty() {
+            self.result.push('}');
+            return;
+        }
+        self.add_lf_with_depth_space(depth + 1);
+        for item in children {
+            if item.is_statement() {
+                self.unparse_rec(item, depth + 1);
+                continue;
+            }
+            match tail {
+                BlockTail::Return => self.unparse_return_statement(item, depth + 1),
+                BlockTail::Discard => self.unparse_statement_expression(item, depth + 1),
+            }
+        }
+        self.result.truncate(self.result.len() - 4);
+        self.result.push('}');
+    }
+
+    /// Emits `return expr;`. A block-like expression which always produces a value is
+    /// emitted as statements returning in each branch instead.
+    fn unparse_return_statement(&mut self, ast: &Ast, depth: u32) {
+        match Self::get_block_like(ast) {
+            Some(block)
+                if block.ast_type == AstType::NonTerminal(NonTerminal::IfExpression)
+                    && Self::has_else_branch(block) =>
+            {
+                self.unparse_if_statement(block, depth, BlockTail::Return);
+            }
+            Some(block)
+                if block.ast_type == AstType::NonTerminal(NonTerminal::CompoundExpression)
+                    && block
+                        .children
+                        .as_ref()
+                        .unwrap()
+                        .last()
+                        .is_some_and(|x| !x.is_statement()) =>
+            {
+                self.unparse_block(block, depth, BlockTail::Return);
+            }
+            _ => {
+                self.result.push_str("return ");
+                self.unparse_rec(ast, depth);
+                self.result.push(';');
+            }
+        }
+        self.add_lf_with_depth_space(depth);
+    }
+
+    /// Returns true if every branch of the if expression chain ends with `else { ... }`.
+    fn has_else_branch(ast: &Ast) -> bool {
+        match ast.children.as_ref().unwrap().get(2) {
+            Some(else_ast)
+                if else_ast.ast_type == AstType::NonTerminal(NonTerminal::IfExpression) =>
+            {
+                Self::has_else_branch(else_ast)
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn unparse_if_statement(&mut self, ast: &Ast, depth: u32, tail: BlockTail) {
+        let children = ast.children.as_ref().unwrap();
+        self.result.push_str("if (");
+        self.unparse_rec(&children[0], depth);
+        self.result.push_str(") ");
+        self.unparse_block(&children[1], depth, tail);
+        if let Some(else_ast) = children.get(2) {
+            self.result.push_str(" else ");
+            if else_ast.ast_type == AstType::NonTerminal(NonTerminal::IfExpression) {
+                self.unparse_if_statement(else_ast, depth, tail);
+            } else {
+                self.unparse_block(else_ast, depth, tail);
+            }
+        }
+    }
+
+    /// Emits IfExpression as `(cond ? a : b)`.
+    fn unparse_conditional_operator(&mut self, ast: &Ast, depth: u32) {
+        let children = ast.children.as_ref().unwrap();
+        self.result.push('(');
+        self.unparse_rec(&children[0], depth);
+        self.result.push_str(" ? ");
+        self.unparse_block_value(&children[1], depth);
+        self.result.push_str(" : ");
+        match children.get(2) {
+            Some(else_ast)
+                if else_ast.ast_type == AstType::NonTerminal(NonTerminal::IfExpression) =>
+            {
+                self.unparse_conditional_operator(else_ast, depth);
+            }
+            Some(else_ast) => self.unparse_block_value(else_ast, depth),
+            None => self.result.push_str("undefined"),
+        }
+        self.result.push(')');
+    }
+
+    /// Emits the value of a CompoundExpression which has no statements.
+    fn unparse_block_value(&mut self, ast: &Ast, depth: u32) {
+        match ast.children.as_ref().unwrap().first() {
+            Some(value) => self.unparse_rec(value, depth),
+            None => self.result.push_str("undefined"),
+        }
+    }
+
+    /// Emits MatchExpression as `(cond ? a : (cond ? b : c))`.
+    fn unparse_match_conditional_operator(&mut self, target: &str, arms: &[Ast], depth: u32) {
+        let (pattern, guard, body) = match arms.first() {
+            Some(arm) => Self::get_arm_parts(arm),
+            None => {
+                self.result.push_str("undefined");
+                return;
+            }
+        };
+        let mut conditions = Vec::new();
+        self.lower_pattern(target, pattern, depth, &mut conditions, &mut Vec::new());
+        if conditions.is_empty() && guard.is_none() {
+            self.unparse_arm_value(body, depth);
+            return;
+        }
+        self.result.push('(');
+        self.unparse_match_condition(&conditions, guard, depth);
+        self.result.push_str(" ? ");
+        self.unparse_arm_value(body, depth);
+        self.result.push_str(" : ");
+        self.unparse_match_conditional_operator(target, &arms[1..], depth);
+        self.result.push(')');
+    }
+
+    /// Emits an arm of MatchExpression lowered to a function and returns true if
+    /// the arm matches any value (the following arms are unreachable).
+    fn unparse_match_arm(&mut self, arm: &Ast, depth: u32) -> bool {
+        let (pattern, guard, body) = Self::get_arm_parts(arm);
+        let (mut conditions, mut bindings) = (Vec::new(), Vec::new());
+        self.lower_pattern("$match", pattern, depth, &mut conditions, &mut bindings);
+        if bindings.is_empty() {
+            if conditions.is_empty() && guard.is_none() {
+                // `_ => body`
+                self.unparse_arm_statements(body, depth);
+                return true;
+            }
+            self.result.push_str("if (");
+            self.unparse_match_condition(&conditions, guard, depth);
+            self.result.push_str(") ");
+            self.unparse_arm_block(body, depth);
+            self.add_lf_with_depth_space(depth);
+            return false;
+        }
+
+        // the bindings are scoped to the arm
+        if conditions.is_empty() {
+            self.result.push('{');
+        } else {
+            self.result
+                .push_str(&format!("if ({}) {{", conditions.join(" && ")));
+        }
+        self.add_lf_with_depth_space(depth + 1);
+        for (name, value) in &bindings {
+            self.result
+                .push_str(&format!("const {} = {};", name, value));
+            self.add_lf_with_depth_space(depth + 1);
+        }
+        match guard {
+            Some(guard) => {
+                self.result.push_str("if (");
+                self.unparse_rec(guard, depth + 1);
+                self.result.push_str(") ");
+                self.unparse_arm_block(body, depth + 1);
+                self.add_lf_with_depth_space(depth + 1);
+            }
+            None => self.unparse_arm_statements(body, depth + 1),
+        }
+        self.result.truncate(self.result.len() - 4);
+        self.result.push('}');
+        self.add_lf_with_depth_space(depth);
+        conditions.is_empty() && guard.is_none()
+    }
+
+    /// Emits `{ ...; return value; }`.
+    fn unparse_arm_block(&mut self, body: &Ast, depth: u32) {
+        self.result.push('{');
+        self.add_lf_with_depth_space(depth + 1);
+        self.unparse_arm_statements(body, depth + 1);
+        self.result.truncate(self.result.len() - 4);
+        self.result.push('}');
+    }
+
+    /// Emits the body of an arm as statements which return its value.
+    fn unparse_arm_statements(&mut self, body: &Ast, depth: u32) {
+        let block = match Self::get_block_like(body) {
+            Some(block)
+                if block.ast_type == AstType::NonTerminal(NonTerminal::CompoundExpression) =>
+            {
+                block
+            }
+            _ => {
+                self.unparse_return_statement(body, depth);
+                return;
+            }
+        };
+        let children = block.children.as_ref().unwrap();
+        for item in children {
+            if item.is_statement() {
+                self.unparse_rec(item, depth);
+            } else {
+                self.unparse_return_statement(item, depth);
+            }
+        }
+        if children.last().is_none_or(|x| x.is_statement()) {
+            // prevent falling through to the following arms
+            self.result.push_str("return;");
+            self.add_lf_with_depth_space(depth);
+        }
+    }
+
+    /// Emits the condition of the arm, e.g. `target === 1 && (guard)`.
+    fn unparse_match_condition(&mut self, conditions: &[String], guard: Option<&Ast>, depth: u32) {
+        if conditions.is_empty() {
+            if let Some(guard) = guard {
+                self.unparse_rec(guard, depth);
+            }
+            return;
+        }
+        self.result.push_str(&conditions.join(" && "));
+        if let Some(guard) = guard {
+            self.result.push_str(" && (");
+            self.unparse_rec(guard, depth);
+            self.result.push(')');
+        }
+    }
+
+    /// Lowers the pattern matched against `target` to conditions and bindings,
+    /// e.g. `Shape::Circle(r)` to `target.kind === "Circle"` and `const r = target._0;`.
+    fn lower_pattern(
+        &mut self,
+        target: &str,
+        pattern: &Ast,
+        depth: u32,
+        conditions: &mut Vec<String>,
+        bindings: &mut Vec<(String, String)>,
+    ) {
+        let children = pattern.children.as_ref().unwrap();
+        if children[0].ast_type == AstType::NonTerminal(NonTerminal::PathExpression) {
+            let variant = children[0].children.as_ref().unwrap()[1]
+                .token
+                .as_ref()
+                .unwrap();
+            conditions.push(format!("{}.kind === \"{}\"", target, variant));
+            let payload = match children.get(1) {
+                Some(payload) => payload,
+                None => return,
+            };
+            for (ind, item) in payload.children.as_ref().unwrap().iter().enumerate() {
+                if payload.ast_type == AstType::NonTerminal(NonTerminal::TuplePattern) {
+                    let target = format!("{}._{}", target, ind);
+                    self.lower_pattern(&target, item, depth, conditions, bindings);
+                    continue;
+                }
+                // FieldPattern = Identifier , [ ":" , Pattern ];
+                let field_pattern = item.children.as_ref().unwrap();
+                let name = field_pattern[0].token.as_ref().unwrap().to_string();
+                let target = format!("{}.{}", target, name);
+                match field_pattern.get(1) {
+                    Some(sub_pattern) => {
+                        self.lower_pattern(&target, sub_pattern, depth, conditions, bindings)
+                    }
+                    None => bindings.push((name, target)),
+                }
+            }
+            return;
+        }
+        match children[0].token.as_ref().unwrap().get_token() {
+            Some(TokenBase::Reserved(ReservedWord::Underscore)) => {}
+            Some(TokenBase::Identifier(name)) => bindings.push((name.clone(), target.to_string())),
+            _ => {
+                let literal = children
+                    .iter()
+                    .map(|x| self.unparse_to_string(x, depth))
+                    .collect::<String>();
+                conditions.push(format!("{} === {}", target, literal));
+            }
+        }
+    }
+
+    /// Emits the value of the arm body used as an operand of the conditional operator.
+    fn unparse_arm_value(&mut self, body: &Ast, depth: u32) {
+        match Self::get_block_like(body) {
+            Some(block)
+                if block.ast_type == AstType::NonTerminal(NonTerminal::CompoundExpression) =>
+            {
+                self.unparse_block_value(block, depth)
+            }
+            _ => self.unparse_rec(body, depth),
+        }
+    }
+
+    // MatchArm = Pattern , [ MatchGuard ] , "=>" , Expression;
+    fn get_arm_parts(arm: &Ast) -> (&Ast, Option<&Ast>, &Ast) {
+        let children = arm.children.as_ref().unwrap();
+        let guard = if children.len() == 3 {
+            Some(&children[1].children.as_ref().unwrap()[0])
+        } else {
+            None
+        };
+        (&children[0], guard, children.last().unwrap())
+    }
+
+    /// Returns true if the pattern binds a name (e.g. `n`, `Shape::Circle(r)`).
+    fn has_binding(pattern: &Ast) -> bool {
+        pattern
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|item| match &item.ast_type {
+                AstType::Terminal => matches!(
+                    item.token.as_ref().unwrap().get_token(),
+                    Some(TokenBase::Identifier(_))
+                ),
+                AstType::NonTerminal(NonTerminal::TuplePattern) => item
+                    .children
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .any(Self::has_binding),
+                AstType::NonTerminal(NonTerminal::StructPattern) => {
+                    item.children.as_ref().unwrap().iter().any(|field| {
+                        match field.children.as_ref().unwrap().get(1) {
+                            Some(sub_pattern) => Self::has_binding(sub_pattern),
+                            None => true,
+                        }
+                    })
+                }
+                AstType::NonTerminal(_) => false,
+            })
+    }
+
+    /// Returns true if the match expression can be emitted with conditional operators,
+    /// i.e. the scrutinee is a single identifier or literal, no arm binds a name and
+    /// no arm body contains statements.
+    fn is_simple_match_expression(ast: &Ast) -> bool {
+        let children = ast.children.as_ref().unwrap();
+        let is_simple_target = matches!(
+            children[0].children.as_deref(),
+            Some([leaf]) if leaf.ast_type == AstType::Terminal
+        );
+        is_simple_target
+            && children[1..].iter().all(|arm| {
+                let (pattern, _, body) = Self::get_arm_parts(arm);
+                !Self::has_binding(pattern)
+                    && match Self::get_block_like(body) {
+                        Some(block)
+                            if block.ast_type
+                                == AstType::NonTerminal(NonTerminal::CompoundExpression) =>
+                        {
+                            let children = block.children.as_ref().unwrap();
+                            children.len() <= 1 && children.iter().all(|x| !x.is_statement())
+                        }
+                        Some(block) => Self::is_simple_if_expression(block),
+                        None => true,
+                    }
+            })
+    }
+
+    /// Returns true if the expression is a literal, e.g. `10` or `-1`.
+    fn is_constant_expression(ast: &Ast) -> bool {
+        match (&ast.ast_type, ast.children.as_deref()) {
+            (AstType::NonTerminal(NonTerminal::PrimaryExpression), Some([leaf])) => matches!(
+                leaf.token.as_ref().unwrap().get_token(),
+                Some(TokenBase::Number(_))
+            ),
+            (AstType::NonTerminal(NonTerminal::UnaryExpression), Some([_, operand])) => {
+                Self::is_constant_expression(operand)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true if no branch of the if expression contains statements.
+    fn is_simple_if_expression(ast: &Ast) -> bool {
+        ast.children.as_ref().unwrap()[1..].iter().all(|branch| {
+            if branch.ast_type == AstType::NonTerminal(NonTerminal::IfExpression) {
+                Self::is_simple_if_expression(branch)
+            } else {
+                let children = branch.children.as_ref().unwrap();
+                children.len() <= 1 && children.iter().all(|x| !x.is_statement())
+            }
+        })
+    }
+
+    /// Returns the inner IfExpression or CompoundExpression if the expression is block-like.
+    fn get_block_like(ast: &Ast) -> Option<&Ast> {
+        match ast.children.as_deref() {
+            Some([child])
+                if ast.ast_type == AstType::NonTerminal(NonTerminal::PrimaryExpression) =>
+            {
+                match child.ast_type {
+                    AstType::NonTerminal(NonTerminal::IfExpression)
+                    | AstType::NonTerminal(NonTerminal::CompoundExpression) => Some(child),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Emits the AST to a string which is not mapped to the source
+    /// (e.g. the scrutinee repeated in each condition).
+    fn unparse_to_string(&mut self, ast: &Ast, depth: u32) -> String {
+        let start = self.result.len();
+        self.unparse_rec(ast, depth);
+        self.mappings.retain(|x| x.generated < start);
+        self.result.split_off(start)
+    }
+
     pub fn get_result(&self) -> String {
         self.result.clone()
     }
@@ -242,3 +1134,466 @@ impl Builder<'_> {
         self.result.push_str(&space);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        manifest::ModuleFormat,
+        semantic_analyzer::BuildOptions,
+        source_map::SourceMapKind,
+        test_support::{analyze, parse},
+    };
+
+    fn build_source(source: &str) -> String {
+        let asts = parse(&[("./test.rots", source)]);
+        let project = analyze(&asts);
+        assert!(!project.has_error());
+        project
+            .call_builder(false)
+            .remove("./test.rots")
+            .unwrap()
+            .code
+    }
+
+    #[test]
+    fn build_simple_if_expression_test() {
+        let result = build_source("const a = if true { 1 } else if false { 2 } else { 3 };");
+        assert_eq!("const a = (true ? 1 : (false ? 2 : 3));\n", result);
+    }
+
+    #[test]
+    fn build_if_expression_with_statements_test() {
+        let result = build_source(
+            "const a = (n: number) => { let x = if n > 1 { let y = n; y } else { 0 }; };",
+        );
+        assert_eq!(
+            "const a = (n) => {
+    let x = (() => {
+        if (n>1) {
+            let y = n;
+            return y;
+        } else {
+            return 0;
+        }
+    })();
+};
+",
+            result
+        );
+    }
+
+    #[test]
+    fn build_if_statement_test() {
+        let result =
+            build_source("const a = (n: number) => { if n > 1 { f(); } else { g(); } h(); };");
+        assert_eq!(
+            "const a = (n) => {
+    if (n>1) {
+        f();
+    } else {
+        g();
+    }
+    h();
+};
+",
+            result
+        );
+    }
+
+    #[test]
+    fn build_simple_match_expression_test() {
+        let result = build_source(
+            r#"const a = (n: number) => { let s = match n { 0 => "zero", -1 => { "minus" } _ if n > 0 => "positive", _ => "negative" }; };"#,
+        );
+        assert_eq!(
+            r#"const a = (n) => {
+    let s = (n === 0 ? "zero" : (n === -1 ? "minus" : (n>0 ? "positive" : "negative")));
+};
+"#,
+            result
+        );
+    }
+
+    #[test]
+    fn build_match_expression_with_bindings_test() {
+        let result = build_source(
+            r#"const a = (n: number) => { let s = match n + 1 { 1 => { f(); "one" } m if m > 9 => "big", m => g(m), }; };"#,
+        );
+        assert_eq!(
+            r#"const a = (n) => {
+    let s = (($match) => {
+        if ($match === 1) {
+            f();
+            return "one";
+        }
+        {
+            const m = $match;
+            if (m>9) {
+                return "big";
+            }
+        }
+        {
+            const m = $match;
+            return g(m);
+        }
+    })(n+1);
+};
+"#,
+            result
+        );
+    }
+
+    #[test]
+    fn build_loop_statements_test() {
+        let result = build_source(
+            "const a = (n: number) => { for i in 0..10 { for j in i..=n * 2 { f(i, j); } } while n > 0 { n -= 1; } };",
+        );
+        assert_eq!(
+            "const a = (n) => {
+    for (let i = 0; i < 10; i++) {
+        for (let j = i, $end = n*2; j <= $end; j++) {
+            f(i,j);
+        }
+    }
+    while (n>0) {
+        n-=1;
+    }
+};
+",
+            result
+        );
+    }
+
+    #[test]
+    fn build_implicit_return_test() {
+        let result = build_source(
+            "const add = (a: number, b: number): number => { a + b }; const abs = (n: number): number => { if n < 0 { -n } else { n } };",
+        );
+        assert_eq!(
+            "const add = (a, b) => {
+    return a+b;
+};
+const abs = (n) => {
+    if (n<0) {
+        return -n;
+    } else {
+        return n;
+    }
+};
+",
+            result
+        );
+    }
+
+    #[test]
+    fn build_block_expression_test() {
+        let result = build_source(
+            "const a = () => { const x = { let y = 1; y + 1 }; const z = { x }; x + z };",
+        );
+        assert_eq!(
+            "const a = () => {
+    const x = (() => {
+        let y = 1;
+        return y+1;
+    })();
+    const z = (x);
+    return x+z;
+};
+",
+            result
+        );
+    }
+
+    #[test]
+    fn build_struct_test() {
+        let result = build_source(
+            "export struct Point { x: number, y: number } struct Unit {} const a = () => { Point { x: 1, y: 2 }.x };",
+        );
+        assert_eq!(
+            "export class Point {
+    constructor({ x, y }) {
+        this.x = x;
+        this.y = y;
+    }
+}
+class Unit {}
+const a = () => {
+    return new Point({ x: 1, y: 2 }).x;
+};
+",
+            result
+        );
+    }
+
+    #[test]
+    fn build_enum_test() {
+        let result = build_source(
+            r#"export enum Shape { Circle(number), Rect { w: number, h: number }, Empty } enum Never {}
+const area = (s: Shape): number => { match s { Shape::Circle(r) => r * r, Shape::Rect { w, h: 0 } => w, Shape::Rect { w, h } if w > h => w * h, _ => 0 } };
+const a = () => { area(Shape::Circle(1)) + area(Shape::Rect { w: 1, h: 2 }) + area(Shape::Empty) };"#,
+        );
+        assert_eq!(
+            r#"export const Shape = Object.freeze({
+    Circle: (_0) => Object.freeze({ kind: "Circle", _0 }),
+    Rect: ({ w, h }) => Object.freeze({ kind: "Rect", w, h }),
+    Empty: Object.freeze({ kind: "Empty" }),
+});
+const Never = Object.freeze({});
+const area = (s) => {
+    return (($match) => {
+        if ($match.kind === "Circle") {
+            const r = $match._0;
+            return r*r;
+        }
+        if ($match.kind === "Rect" && $match.h === 0) {
+            const w = $match.w;
+            return w;
+        }
+        if ($match.kind === "Rect") {
+            const w = $match.w;
+            const h = $match.h;
+            if (w>h) {
+                return w*h;
+            }
+        }
+        return 0;
+    })(s);
+};
+const a = () => {
+    return area(Shape.Circle(1))+area(Shape.Rect({ w: 1, h: 2 }))+area(Shape.Empty);
+};
+"#,
+            result
+        );
+    }
+
+    #[test]
+    fn build_module_format_test() {
+        let asts = parse(&[
+            (
+                "./main.rots",
+                r#"import { add, Point } from "./math.rots";
+import times from "./math";
+import { b } from "./lib/b.mjs";
+export const main = () => { add(1, 2) };"#,
+            ),
+            (
+                "./math.rots",
+                "export const add = (a: number, b: number) => { a + b };
+const sub = (a: number, b: number) => { a - b };
+export default const mul = (a: number, b: number) => { a * b };
+export struct Point { x: number }",
+            ),
+        ]);
+        let project = analyze(&asts);
+        assert!(project.get_semantic_errors().is_empty());
+        let build = |module_format| {
+            let options = BuildOptions {
+                module_format,
+                ..BuildOptions::default()
+            };
+            let mut outputs = project.build(&options);
+            let main = outputs.remove("./main.rots").unwrap().code;
+            let math = outputs.remove("./math.rots").unwrap().code;
+            (main, math)
+        };
+
+        let (main, math) = build(ModuleFormat::Esm);
+        assert_eq!(
+            r#"import { add, Point, } from "./math.js";
+import times from "./math.js";
+import { b, } from "./lib/b.mjs";
+export const main = () => {
+    return add(1,2);
+};
+"#,
+            main
+        );
+        assert!(math.starts_with("export const add = "));
+        assert!(math.contains("\nconst mul = "));
+        assert!(math.ends_with("export class Point {\n    constructor({ x }) {\n        this.x = x;\n    }\n}\nexport default mul;\n"));
+
+        let (main, math) = build(ModuleFormat::CommonJs);
+        assert_eq!(
+            r#"const { add, Point } = require("./math.js");
+const times = require("./math").default;
+const { b } = require("./lib/b.mjs");
+const main = () => {
+    return add(1,2);
+};
+exports.main = main;
+"#,
+            main
+        );
+        assert_eq!(
+            "const add = (a, b) => {
+    return a+b;
+};
+const sub = (a, b) => {
+    return a-b;
+};
+const mul = (a, b) => {
+    return a*b;
+};
+class Point {
+    constructor({ x }) {
+        this.x = x;
+    }
+}
+exports.add = add;
+exports.default = mul;
+exports.Point = Point;
+",
+            math
+        );
+    }
+
+    #[test]
+    fn build_string_literal_test() {
+        let result = build_source(
+            r#"const a = 'it\'s "x"\u{1F600}\n';
+const b = `${a}\`${1 + 2}\${c}
+`;"#,
+        );
+        assert_eq!(
+            "const a = \"it's \\\"x\\\"😀\\n\";\nconst b = `${a}\\`${1+2}\\${c}\n`;\n",
+            result
+        );
+    }
+
+    #[test]
+    fn build_numeric_literal_test() {
+        let result = build_source(
+            "const a = () => { console.log(0x1F, 0o17, 0b1010, 1e9, 1.5E-3, 1_000_000, -10n) };",
+        );
+        assert_eq!(
+            "const a = () => {\n    return console.log(0x1F,0o17,0b1010,1e9,1.5E-3,1_000_000,-10n);\n};\n",
+            result
+        );
+    }
+
+    #[test]
+    fn build_doc_comment_test() {
+        let result = build_source(
+            "/* not emitted */\n/// Adds\n///\n/// `*/` is escaped.\nexport const add = (a: number, b: number) => { a + b };",
+        );
+        assert_eq!(
+            "/**\n * Adds\n *\n * `*\\/` is escaped.\n */\nexport const add = (a, b) => {\n    return a+b;\n};\n",
+            result
+        );
+    }
+
+    #[test]
+    fn build_source_map_test() {
+        let source = "const a = 1;\n\nconst b = (x: number) => {\n    x + a\n};";
+        let asts = parse(&[("./src/test.rots", source)]);
+        let project = analyze(&asts);
+        let mut options = BuildOptions {
+            source_map: SourceMapKind::File,
+            ..BuildOptions::default()
+        };
+        options
+            .sources_content
+            .insert("./src/test.rots".to_string(), source.to_string());
+
+        let output = project.build(&options).remove("./src/test.rots").unwrap();
+        assert_eq!(
+            "const a = 1;
+const b = (x) => {
+    return x+a;
+};
+//# sourceMappingURL=test.js.map
+",
+            output.code
+        );
+        let source_map = output.source_map.unwrap();
+        assert_eq!("test.js", source_map.file);
+        assert_eq!(vec!["./src/test.rots"], source_map.sources);
+        assert_eq!(Some(vec![source.to_string()]), source_map.sources_content);
+        assert_eq!("MAAM,IAAI;MAEJ,KAAK;WACP,CAAE,CAAE;;", source_map.mappings);
+
+        options.source_map = SourceMapKind::Inline;
+        let output = project.build(&options).remove("./src/test.rots").unwrap();
+        assert!(output.source_map.is_none());
+        let (code, comment) = output.code.split_at(output.code.find("//#").unwrap());
+        assert!(code.ends_with("};\n"));
+        assert!(comment.starts_with("//# sourceMappingURL=data:application/json;base64,"));
+    }
+
+    #[test]
+    fn build_re_export_test() {
+        let asts = parse(&[
+            (
+                "./main.rots",
+                r#"import { plus as add, Point } from "./index";
+import * as lib from "./index";
+export const main = (p: Point) => { add(1, 2) + lib.plus(p.x, 2) };"#,
+            ),
+            (
+                "./index.rots",
+                r#"export { add as plus } from "./math";
+export * from "./math";"#,
+            ),
+            (
+                "./math.rots",
+                "export const add = (a: number, b: number) => { a + b };
+export struct Point { x: number }",
+            ),
+        ]);
+        let project = analyze(&asts);
+        assert!(project.get_semantic_errors().is_empty());
+        let build = |module_format| {
+            let options = BuildOptions {
+                module_format,
+                ..BuildOptions::default()
+            };
+            let mut outputs = project.build(&options);
+            let main = outputs.remove("./main.rots").unwrap();
+            let index = outputs.remove("./index.rots").unwrap();
+            (main, index)
+        };
+
+        let (main, index) = build(ModuleFormat::Esm);
+        assert_eq!(
+            r#"import { plus as add, Point, } from "./index.js";
+import * as lib from "./index.js";
+export const main = (p) => {
+    return add(1,2)+lib.plus(p.x,2);
+};
+"#,
+            main.code
+        );
+        assert_eq!(
+            r#"export { add as plus, } from "./math.js";
+export * from "./math.js";
+"#,
+            index.code
+        );
+        assert_eq!(
+            r#"import { plus as add, Point } from "./index.js";
+import * as lib from "./index.js";
+export declare const main: (p: Point) => number;
+"#,
+            main.declaration
+        );
+        assert_eq!(
+            r#"export { add as plus } from "./math.js";
+export * from "./math.js";
+"#,
+            index.declaration
+        );
+
+        let (main, index) = build(ModuleFormat::CommonJs);
+        assert!(main.code.starts_with(
+            r#"const { plus: add, Point } = require("./index");
+const lib = require("./index");
+"#
+        ));
+        assert_eq!(
+            r#"exports.plus = require("./math").add;
+for (const [key, value] of Object.entries(require("./math"))) if (key !== "default") exports[key] = value;
+"#,
+            index.code
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    builder::BuildOutput,
+    diagnostic::Diagnostics,
+    lexer::Lexer,
+    manifest::Manifest,
+    parser::{ast::Ast, token_stack::TokenStack, Parser},
+    semantic_analyzer::{analyze_project, BuildOptions},
+};
+
+/// The result of `compile`.
+pub struct CompileOutput {
+    /// The outputs of the files (key: path). Empty if any error is found.
+    pub outputs: HashMap<String, BuildOutput>,
+    pub diagnostics: Diagnostics,
+}
+
+/// Lexes and parses the file. Returns `None` if it has an error, which is reported to `diagnostics`.
+/// The parse errors at the tokens with lex errors (e.g. `found [unknown token: $]`) are not
+/// reported since the lex errors already explain them.
+pub fn parse_source(path: &str, content: &str, diagnostics: &mut Diagnostics) -> Option<Ast> {
+    let mut has_error = false;
+    let mut lexer = Lexer::new(content, path);
+    let mut lex_error_offsets = HashSet::new();
+    if let Err(e) = lexer.lex() {
+        let lex_diagnostics = e.to_diagnostics();
+        lex_error_offsets.extend(
+            lex_diagnostics
+                .iter()
+                .filter_map(|x| Some(x.span.as_ref()?.ind)),
+        );
+        diagnostics.extend(lex_diagnostics);
+        has_error = true;
+    }
+    let token_stack = &mut TokenStack::new(&lexer.tokens);
+    let mut parser = Parser::new(token_stack);
+    if let Err(e) = parser.parse() {
+        diagnostics.extend(e.to_diagnostics().into_iter().filter(|x| {
+            !x.span
+                .as_ref()
+                .is_some_and(|span| lex_error_offsets.contains(&span.ind))
+        }));
+        has_error = true;
+    }
+    if has_error {
+        None
+    } else {
+        Some(parser.ast)
+    }
+}
+
+/// Compiles the files (path, content) of the project declared by the manifest.
+/// The semantic analysis is skipped if any file has a lex or parse error.
+pub fn compile(
+    manifest: &Manifest,
+    files: &[(String, String)],
+    options: &BuildOptions,
+) -> CompileOutput {
+    let mut diagnostics = Diagnostics::new();
+    let asts = parse_sources(files, &mut diagnostics);
+    if diagnostics.has_errors() {
+        return CompileOutput {
+            outputs: HashMap::new(),
+            diagnostics,
+        };
+    }
+
+    let project = analyze_project(manifest, asts.iter().map(|x| (x.0.clone(), &x.1)).collect());
+    project.report_errors(&mut diagnostics);
+    let outputs = if diagnostics.has_errors() {
+        HashMap::new()
+    } else {
+        project.build(options)
+    };
+    CompileOutput {
+        outputs,
+        diagnostics,
+    }
+}
+
+/// Same as `compile` but only reports the diagnostics without building the files.
+pub fn check(manifest: &Manifest, files: &[(String, String)]) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    let asts = parse_sources(files, &mut diagnostics);
+    if !diagnostics.has_errors() {
+        let project = analyze_project(manifest, asts.iter().map(|x| (x.0.clone(), &x.1)).collect());
+        project.report_errors(&mut diagnostics);
+    }
+    diagnostics
+}
+
+fn parse_sources(files: &[(String, String)], diagnostics: &mut Diagnostics) -> Vec<(String, Ast)> {
+    files
+        .iter()
+        .filter_map(|(path, content)| {
+            Some((path.clone(), parse_source(path, content, diagnostics)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manifest::Manifest;
+
+    use super::*;
+
+    fn compile_files(files: &[(&str, &str)]) -> CompileOutput {
+        let files = files
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect::<Vec<_>>();
+        compile(
+            &Manifest::new("test".to_string()),
+            &files,
+            &BuildOptions::default(),
+        )
+    }
+
+    fn codes(output: &CompileOutput) -> Vec<&str> {
+        output.diagnostics.iter().map(|x| x.code).collect()
+    }
+
+    #[test]
+    fn compile_test() {
+        let output = compile_files(&[(
+            "./main.rots",
+            "[EntryPoint]\nconst main = () => {\n    console.log(1);\n};",
+        )]);
+        assert!(output.diagnostics.is_empty());
+        assert!(output.outputs["./main.rots"]
+            .code
+            .contains("console.log(1)"));
+    }
+
+    #[test]
+    fn compile_lex_and_parse_error_test() {
+        let output = compile_files(&[("./a.rots", "const a = 1 $ 2;"), ("./b.rots", "foo")]);
+        assert!(output.outputs.is_empty());
+        assert_eq!(codes(&output)[0], "E0001");
+        let span = output
+            .diagnostics
+            .iter()
+            .last()
+            .unwrap()
+            .span
+            .clone()
+            .unwrap();
+        assert_eq!((span.path.as_str(), span.ln, span.col), ("./b.rots", 1, 1));
+
+        let output = compile_files(&[("./a.rots", "const a = (1 + ")]);
+        assert_eq!(codes(&output), vec!["E0102"]);
+
+        let files = vec![("./a.rots".to_string(), "const a = (1 + ".to_string())];
+        let diagnostics = check(&Manifest::new("test".to_string()), &files);
+        assert_eq!(
+            diagnostics.iter().map(|x| x.code).collect::<Vec<_>>(),
+            vec!["E0102"]
+        );
+    }
+
+    #[test]
+    fn compile_lex_error_at_parse_error_test() {
+        let output = compile_files(&[(
+            "./a.rots",
+            "const a = 1 $ 2;\nconst b = 08;\nconst c = 1 1;",
+        )]);
+        let errors = output
+            .diagnostics
+            .iter()
+            .map(|x| (x.code, x.span.as_ref().unwrap().ln))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![("E0001", 1), ("E0004", 2), ("E0101", 3)]);
+    }
+
+    #[test]
+    fn compile_diagnostic_order_test() {
+        let output = compile_files(&[
+            ("./b.rots", "const a = 1 1;\nconst b = 08;"),
+            ("./a.rots", "const a = $;"),
+        ]);
+        let errors = output
+            .diagnostics
+            .iter()
+            .map(|x| {
+                let span = x.span.as_ref().unwrap();
+                (x.code, span.path.as_str(), span.ln)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                ("E0001", "./a.rots", 1),
+                ("E0101", "./b.rots", 1),
+                ("E0004", "./b.rots", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_semantic_error_test() {
+        let output = compile_files(&[(
+            "./main.rots",
+            "const a: number = \"x\";\n[EntryPoint]\nconst main = () => {};\n[EntryPoint]\nconst other = () => {};",
+        )]);
+        assert!(output.outputs.is_empty());
+        assert_eq!(codes(&output), vec!["E0201", "E0219"]);
+        let entry_point = output.diagnostics.iter().last().unwrap();
+        let span = entry_point.span.as_ref().unwrap();
+        assert_eq!((span.ln, span.col), (5, 7));
+        assert_eq!(entry_point.labels[0].span.ln, 3);
+    }
+}
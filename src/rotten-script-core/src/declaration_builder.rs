@@ -0,0 +1,557 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    builder::to_jsdoc,
+    parser::{ast::Ast, ast_type::AstType, non_terminal::NonTerminal},
+    semantic_analyzer::{
+        file_map::{resolve_export, ExportedMember, FileMap, ImportClause, ImportMember},
+        func_info::{ExportedType, FuncInfo, MemberKind, Type, VariantFields},
+        project::Project,
+    },
+};
+
+/// Emits the TypeScript declaration file (`.d.ts`) of each file from the analyzed members.
+pub struct DeclarationBuilder<'a> {
+    semantic_tree: &'a Project<'a>,
+    result: String,
+    // the names by which the structs and the enums are referred in the current file
+    // (key: the full path e.g. `./a.rots#Point`, value: e.g. `Point`, `P` or `ns.Point`)
+    type_names: HashMap<String, String>,
+    // the path of the current file
+    current_file: String,
+}
+
+impl DeclarationBuilder<'_> {
+    pub fn new<'a>(tree: &'a Project<'a>) -> DeclarationBuilder<'a> {
+        DeclarationBuilder {
+            semantic_tree: tree,
+            result: String::new(),
+            type_names: HashMap::new(),
+            current_file: String::new(),
+        }
+    }
+
+    pub fn unparse(&mut self) -> HashMap<String, String> {
+        let paths = self.semantic_tree.file_maps.keys().cloned().collect();
+        self.unparse_files(&paths)
+    }
+
+    /// Same as `unparse` but emits only the files of `paths`.
+    pub fn unparse_files(&mut self, paths: &HashSet<String>) -> HashMap<String, String> {
+        let mut built_map = HashMap::new();
+        let file_maps = self.semantic_tree.file_maps.iter();
+        for (file_name, map) in file_maps.filter(|(x, _)| paths.contains(*x)) {
+            self.current_file = file_name.clone();
+            self.type_names = self.collect_type_names(map);
+            let mut has_export = self.unparse_imports(map);
+            for ast in map.ast.children.iter().flatten() {
+                if let Some(member) = Self::get_member(map, ast) {
+                    has_export |= self.unparse_member(map, member, ast.doc_comment.as_deref());
+                }
+            }
+            if !has_export {
+                // keep the file a module even if it exports nothing
+                self.result.push_str("export {};\n");
+            }
+            built_map.insert(file_name.clone(), std::mem::take(&mut self.result));
+        }
+        built_map
+    }
+
+    /// Returns the names bound to the types in the file, i.e. the types declared in the file and
+    /// the imported ones. Other types are referred by `import("...")`.
+    fn collect_type_names(&self, map: &FileMap) -> HashMap<String, String> {
+        let file_maps = &self.semantic_tree.file_maps;
+        let mut type_names = HashMap::new();
+        for import in &map.imports {
+            let resolved_path = match &import.resolved_path {
+                Some(resolved_path) => resolved_path,
+                None => continue,
+            };
+            let imported = match file_maps.get(resolved_path) {
+                Some(imported) => imported,
+                None => continue,
+            };
+            match &import.clause {
+                ImportClause::Named(members) => {
+                    for member in members {
+                        let name = member.name.to_string();
+                        if let Some(ExportedMember::Member { path, name }) =
+                            resolve_export(file_maps, resolved_path, &name)
+                        {
+                            type_names
+                                .entry(format!("{}#{}", path, name))
+                                .or_insert_with(|| member.get_local_name());
+                        }
+                    }
+                }
+                ImportClause::Default(member) => {
+                    if let [name] = imported.default_exports.as_slice() {
+                        type_names
+                            .entry(format!("{}#{}", resolved_path, name))
+                            .or_insert_with(|| member.to_string());
+                    }
+                }
+                ImportClause::Namespace(namespace) => {
+                    for name in imported.members.keys() {
+                        if imported.is_exported(name) {
+                            type_names
+                                .entry(format!("{}#{}", resolved_path, name))
+                                .or_insert_with(|| format!("{}.{}", namespace, name));
+                        }
+                    }
+                }
+                ImportClause::ReExport(_) | ImportClause::ReExportAll => {}
+            }
+        }
+        for name in map.members.keys() {
+            type_names.insert(format!("{}#{}", map.path, name), name.clone());
+        }
+        type_names
+    }
+
+    /// Emits the imports and the re-exports of RottenScript files with the same bindings as the
+    /// source, and returns true if the file re-exports any member.
+    /// The members of JavaScript modules are `any` and not imported.
+    fn unparse_imports(&mut self, map: &FileMap) -> bool {
+        let mut has_export = false;
+        for import in &map.imports {
+            if import.resolved_path.is_none() {
+                continue;
+            }
+            // `./math` and `./math.rots` -> `./math.js`, which is resolved to `./math.d.ts`
+            let specifier = format!("\"{}.js\"", import.import_path.trim_end_matches(".rots"));
+            let to_specifiers = |members: &[ImportMember]| {
+                members
+                    .iter()
+                    .map(|member| match &member.alias {
+                        Some(alias) => format!("{} as {}", member.name, alias),
+                        None => member.name.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let declaration = match &import.clause {
+                ImportClause::Named(members) => {
+                    format!(
+                        "import {{ {} }} from {};\n",
+                        to_specifiers(members),
+                        specifier
+                    )
+                }
+                ImportClause::Default(member) => {
+                    format!("import {} from {};\n", member, specifier)
+                }
+                ImportClause::Namespace(namespace) => {
+                    format!("import * as {} from {};\n", namespace, specifier)
+                }
+                ImportClause::ReExport(members) => {
+                    has_export = true;
+                    format!(
+                        "export {{ {} }} from {};\n",
+                        to_specifiers(members),
+                        specifier
+                    )
+                }
+                ImportClause::ReExportAll => {
+                    has_export = true;
+                    format!("export * from {};\n", specifier)
+                }
+            };
+            self.result.push_str(&declaration);
+        }
+        has_export
+    }
+
+    /// Returns the member declared by the top-level declaration.
+    fn get_member<'b>(map: &'b FileMap, ast: &Ast) -> Option<&'b FuncInfo> {
+        match ast.ast_type {
+            AstType::NonTerminal(NonTerminal::ExportableConstDeclaration)
+            | AstType::NonTerminal(NonTerminal::ExportableTypeDeclaration) => {}
+            _ => return None,
+        }
+        // (ConstDeclaration | StructDeclaration | EnumDeclaration) is the last child
+        let declaration = ast.children.as_ref()?.last()?;
+        let mut name = declaration.children.as_ref()?.first()?;
+        if name.ast_type == AstType::NonTerminal(NonTerminal::DeclarationBody) {
+            name = name.children.as_ref()?.first()?;
+        }
+        let name = name.token.as_ref()?.to_string();
+        map.members.get(&name).map(|x| x.as_ref())
+    }
+
+    /// Emits the declaration of the member and returns true if it is exported.
+    /// Types are emitted even if they are not exported since exported members may refer them.
+    /// `export default` members are declared without `export` and exported by `export default name;`.
+    /// The doc comment is emitted as JSDoc before each declaration.
+    fn unparse_member(
+        &mut self,
+        map: &FileMap,
+        member: &FuncInfo,
+        doc_comment: Option<&str>,
+    ) -> bool {
+        let is_exported = !matches!(member.get_exported_type(), ExportedType::None);
+        let is_default = matches!(member.get_exported_type(), ExportedType::DefaultExport);
+        match member.get_kind() {
+            MemberKind::Function { args, .. } => {
+                if !is_exported {
+                    return false;
+                }
+                let ty = map.functions[&member.name]
+                    .get_tree()
+                    .map(|x| x.ty.clone())
+                    .unwrap_or(Type::Any);
+                let ty = match ty {
+                    Type::Function(params, ret) if params.len() == args.arguments.len() => {
+                        let names = args.arguments.iter().map(|(name, _)| name.as_str());
+                        self.to_function_type(names.zip(params.iter()), &ret)
+                    }
+                    ty => self.to_ts_type(&ty),
+                };
+                self.push_export(doc_comment, is_exported && !is_default);
+                self.result
+                    .push_str(&format!("declare const {}: {};\n", member.name, ty));
+            }
+            // declare class Point {
+            //     constructor(fields: { x: number; y: number });
+            //     x: number;
+            //     y: number;
+            // }
+            MemberKind::Struct { fields } => {
+                self.push_export(doc_comment, is_exported && !is_default);
+                self.result
+                    .push_str(&format!("declare class {} {{\n", member.name));
+                if !fields.arguments.is_empty() {
+                    self.result.push_str(&format!(
+                        "    constructor(fields: {});\n",
+                        self.to_object_type(&fields.arguments)
+                    ));
+                }
+                for (name, ty) in &fields.arguments {
+                    self.result
+                        .push_str(&format!("    {}: {};\n", name, self.to_ts_type(ty)));
+                }
+                self.result.push_str("}\n");
+            }
+            // type Shape =
+            //     | { readonly kind: "Circle"; readonly _0: number }
+            //     | { readonly kind: "Empty" };
+            // declare const Shape: {
+            //     readonly Circle: (_0: number) => Shape;
+            //     readonly Empty: Shape;
+            // };
+            MemberKind::Enum { variants } => {
+                self.push_export(doc_comment, is_exported && !is_default);
+                self.result.push_str(&format!("type {} =", member.name));
+                if variants.is_empty() {
+                    self.result.push_str(" never");
+                }
+                for variant in variants {
+                    let mut members = vec![format!("readonly kind: \"{}\"", variant.name)];
+                    match &variant.fields {
+                        VariantFields::Unit => {}
+                        VariantFields::Tuple(types) => {
+                            members.extend(types.iter().enumerate().map(|(ind, ty)| {
+                                format!("readonly _{}: {}", ind, self.to_ts_type(ty))
+                            }));
+                        }
+                        VariantFields::Struct(fields) => {
+                            members.extend(fields.arguments.iter().map(|(name, ty)| {
+                                format!("readonly {}: {}", name, self.to_ts_type(ty))
+                            }));
+                        }
+                    }
+                    self.result
+                        .push_str(&format!("\n    | {{ {} }}", members.join("; ")));
+                }
+                self.result.push_str(";\n");
+
+                self.push_export(doc_comment, is_exported && !is_default);
+                self.result
+                    .push_str(&format!("declare const {}: {{\n", member.name));
+                for variant in variants {
+                    let ty = match &variant.fields {
+                        VariantFields::Unit => member.name.clone(),
+                        VariantFields::Tuple(types) => {
+                            let names = (0..types.len()).map(|x| format!("_{}", x));
+                            let params = names
+                                .zip(types)
+                                .map(|(name, ty)| format!("{}: {}", name, self.to_ts_type(ty)))
+                                .collect::<Vec<_>>();
+                            format!("({}) => {}", params.join(", "), member.name)
+                        }
+                        VariantFields::Struct(fields) => format!(
+                            "(fields: {}) => {}",
+                            self.to_object_type(&fields.arguments),
+                            member.name
+                        ),
+                    };
+                    self.result
+                        .push_str(&format!("    readonly {}: {};\n", variant.name, ty));
+                }
+                self.result.push_str("};\n");
+            }
+        }
+        if is_default {
+            self.result
+                .push_str(&format!("export default {};\n", member.name));
+        }
+        is_exported
+    }
+
+    fn push_export(&mut self, doc_comment: Option<&str>, is_exported: bool) {
+        if let Some(doc_comment) = doc_comment {
+            self.result.push_str(&to_jsdoc(doc_comment));
+        }
+        if is_exported {
+            self.result.push_str("export ");
+        }
+    }
+
+    /// Converts the type to TypeScript, e.g. `(arg0: number) => string`.
+    fn to_ts_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::Primitive(primitive) => primitive.to_string(),
+            Type::Function(params, ret) => {
+                let names = (0..params.len()).map(|x| format!("arg{}", x));
+                self.to_function_type(names.zip(params.iter()), ret)
+            }
+            Type::Struct(full_path) | Type::Enum(full_path) => {
+                match self.type_names.get(full_path) {
+                    Some(name) => name.clone(),
+                    // e.g. `import("./shape.js").Shape`
+                    None => {
+                        let (path, name) = full_path.rsplit_once('#').unwrap();
+                        let specifier = relative_specifier(&self.current_file, path);
+                        format!("import(\"{}\").{}", specifier, name)
+                    }
+                }
+            }
+            Type::Any => "any".to_string(),
+        }
+    }
+
+    fn to_function_type<'b, T: std::fmt::Display>(
+        &self,
+        params: impl Iterator<Item = (T, &'b Type)>,
+        ret: &Type,
+    ) -> String {
+        let params = params
+            .map(|(name, ty)| format!("{}: {}", name, self.to_ts_type(ty)))
+            .collect::<Vec<_>>();
+        format!("({}) => {}", params.join(", "), self.to_ts_type(ret))
+    }
+
+    /// e.g. `{ x: number; y: number }`
+    fn to_object_type(&self, fields: &[(String, Type)]) -> String {
+        let fields = fields
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, self.to_ts_type(ty)))
+            .collect::<Vec<_>>();
+        format!("{{ {} }}", fields.join("; "))
+    }
+}
+
+/// Returns the specifier of the output of `to` imported from the output of `from`,
+/// e.g. `../b.js` for `./src/sub/a.rots` and `./src/b.rots`.
+fn relative_specifier(from: &str, to: &str) -> String {
+    let from_dir = from.split('/').collect::<Vec<_>>();
+    let from_dir = &from_dir[..from_dir.len() - 1];
+    let to = to.split('/').collect::<Vec<_>>();
+    let common = from_dir.iter().zip(&to).take_while(|(x, y)| x == y).count();
+    let mut segments = vec![".."; from_dir.len() - common];
+    if segments.is_empty() {
+        segments.push(".");
+    }
+    segments.extend(&to[common..]);
+    format!("{}.js", segments.join("/").trim_end_matches(".rots"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::test_support::{analyze, parse};
+
+    fn build_declaration(source: &str) -> String {
+        build_declarations(&[("./test.rots", source)])
+            .remove("./test.rots")
+            .unwrap()
+    }
+
+    /// Builds the files of (path, source) and returns the declarations (key: path).
+    fn build_declarations(files: &[(&str, &str)]) -> HashMap<String, String> {
+        let asts = parse(files);
+        let project = analyze(&asts);
+        assert!(!project.has_error());
+        project
+            .call_builder(false)
+            .into_iter()
+            .map(|(path, output)| (path, output.declaration))
+            .collect()
+    }
+
+    #[test]
+    fn build_function_declaration_test() {
+        let result = build_declaration(
+            r#"export const add = (a: number, b: number): number => { a + b };
+export const greet = (name: string) => { "Hello, " + name };
+export const twice = (f: number) => { (x: number) => { x * f } };
+export const answer = 42;
+const hidden = () => { 1 };"#,
+        );
+        assert_eq!(
+            "export declare const add: (a: number, b: number) => number;
+export declare const greet: (name: string) => string;
+export declare const twice: (f: number) => (arg0: number) => number;
+export declare const answer: number;
+",
+            result
+        );
+    }
+
+    #[test]
+    fn build_type_declaration_test() {
+        let result = build_declaration(
+            r#"struct Point { x: number, y: number }
+export enum Shape { Circle(number), Rect { w: number, h: number }, Empty }
+export const origin = (): Point => { Point { x: 0, y: 0 } };"#,
+        );
+        assert_eq!(
+            r#"declare class Point {
+    constructor(fields: { x: number; y: number });
+    x: number;
+    y: number;
+}
+export type Shape =
+    | { readonly kind: "Circle"; readonly _0: number }
+    | { readonly kind: "Rect"; readonly w: number; readonly h: number }
+    | { readonly kind: "Empty" };
+export declare const Shape: {
+    readonly Circle: (_0: number) => Shape;
+    readonly Rect: (fields: { w: number; h: number }) => Shape;
+    readonly Empty: Shape;
+};
+export declare const origin: () => Point;
+"#,
+            result
+        );
+    }
+
+    #[test]
+    fn build_empty_declaration_test() {
+        let result = build_declaration("const main = () => { };");
+        assert_eq!("export {};\n", result);
+    }
+
+    #[test]
+    fn build_doc_comment_declaration_test() {
+        let result = build_declaration(
+            r#"/// Adds two numbers.
+export const add = (a: number, b: number): number => { a + b };
+/// Not exported.
+const hidden = () => { 1 };
+/// A shape.
+export enum Shape { Empty }"#,
+        );
+        assert_eq!(
+            r#"/**
+ * Adds two numbers.
+ */
+export declare const add: (a: number, b: number) => number;
+/**
+ * A shape.
+ */
+export type Shape =
+    | { readonly kind: "Empty" };
+/**
+ * A shape.
+ */
+export declare const Shape: {
+    readonly Empty: Shape;
+};
+"#,
+            result
+        );
+    }
+
+    #[test]
+    fn build_import_declaration_test() {
+        let mut declarations = build_declarations(&[
+            (
+                "./src/main.rots",
+                r#"import { plus, Point as P } from "./index";
+import { add as adder } from "./lib/math";
+import Origin from "./lib/origin";
+import * as math from "./lib/math";
+export const a = (p: P, o: Origin) => { plus(1, 2) + adder(p.x, o.x) };
+export const b = () => { math.origin() };
+export const c = () => { math.vector() };"#,
+            ),
+            (
+                "./src/index.rots",
+                r#"export { add as plus } from "./lib/math";
+export { Point } from "./lib/math";"#,
+            ),
+            (
+                "./src/lib/math.rots",
+                r#"import Origin from "./origin";
+export const add = (a: number, b: number) => { a + b };
+export struct Point { x: number }
+export struct Vector { x: number }
+export const origin = () => { Origin { x: 0 } };
+export const vector = () => { Vector { x: 0 } };"#,
+            ),
+            (
+                "./src/lib/origin.rots",
+                "export default struct Origin { x: number }",
+            ),
+        ]);
+        assert_eq!(
+            r#"import { plus, Point as P } from "./index.js";
+import { add as adder } from "./lib/math.js";
+import Origin from "./lib/origin.js";
+import * as math from "./lib/math.js";
+export declare const a: (p: P, o: Origin) => number;
+export declare const b: () => Origin;
+export declare const c: () => math.Vector;
+"#,
+            declarations.remove("./src/main.rots").unwrap()
+        );
+        // the types which are not imported are referred by `import("...")`
+        let declarations = build_declarations(&[
+            (
+                "./src/main.rots",
+                r#"import { origin } from "./lib/math";
+export const b = () => { origin() };"#,
+            ),
+            (
+                "./src/lib/math.rots",
+                r#"export struct Point { x: number }
+export const origin = () => { Point { x: 0 } };"#,
+            ),
+        ]);
+        assert_eq!(
+            r#"import { origin } from "./lib/math.js";
+export declare const b: () => import("./lib/math.js").Point;
+"#,
+            declarations["./src/main.rots"]
+        );
+    }
+
+    #[test]
+    fn build_default_declaration_test() {
+        let result =
+            build_declaration("export default struct Point { x: number }\nexport const zero = 0;");
+        assert_eq!(
+            r#"declare class Point {
+    constructor(fields: { x: number });
+    x: number;
+}
+export default Point;
+export declare const zero: number;
+"#,
+            result
+        );
+    }
+}
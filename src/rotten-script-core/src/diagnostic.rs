@@ -0,0 +1,585 @@
+use std::{fmt::Display, rc::Rc};
+
+use colored::{ColoredString, Colorize};
+
+use crate::{lexer::token::TokenPosition, source_map::to_json_string};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A range of a source file, e.g. the token which caused an error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub path: Rc<String>,
+    /// 1-based line
+    pub ln: u32,
+    /// 1-based column
+    pub col: u32,
+    /// 0-based byte offset
+    pub ind: u64,
+    pub len: usize,
+}
+
+impl From<TokenPosition> for Span {
+    fn from(position: TokenPosition) -> Self {
+        Self {
+            path: position.path,
+            ln: position.ln,
+            col: position.col,
+            ind: position.ind,
+            len: position.len,
+        }
+    }
+}
+
+impl From<&TokenPosition> for Span {
+    fn from(position: &TokenPosition) -> Self {
+        Self {
+            path: position.path.clone(),
+            ln: position.ln,
+            col: position.col,
+            ind: position.ind,
+            len: position.len,
+        }
+    }
+}
+
+/// A secondary span of a diagnostic with its message, e.g. "first defined here".
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// An error or a warning found by the lexer, the parser or the semantic analyzer.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// e.g. `E0201`
+    pub code: &'static str,
+    pub message: String,
+    /// `None` if the diagnostic is not related to a source position
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    /// suggestions to fix the error
+    pub helps: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            code,
+            message: message.into(),
+            span: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            helps: Vec::new(),
+        }
+    }
+
+    pub fn with_span(mut self, span: impl Into<Span>) -> Self {
+        self.span = Some(span.into());
+        self
+    }
+
+    pub fn with_label(mut self, span: impl Into<Span>, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span: span.into(),
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.helps.push(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Returns the diagnostic as a JSON object for editors and CI, e.g.
+    /// `{"code":"E0201","severity":"error","message":"...","file":"./main.rots","line":1,"column":19,"offset":18,"length":3,"related":[],"notes":[],"helps":[]}`.
+    /// The position fields are `null` if the diagnostic has no span.
+    pub fn to_json(&self) -> String {
+        let related = self
+            .labels
+            .iter()
+            .map(|x| {
+                format!(
+                    "{{{},\"message\":{}}}",
+                    span_to_json(Some(&x.span)),
+                    to_json_string(&x.message)
+                )
+            })
+            .collect::<Vec<_>>();
+        let strings = |x: &[String]| {
+            x.iter()
+                .map(|x| to_json_string(x))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!(
+            r#"{{"code":{},"severity":{},"message":{},{},"related":[{}],"notes":[{}],"helps":[{}]}}"#,
+            to_json_string(self.code),
+            to_json_string(&self.severity.to_string()),
+            to_json_string(&self.message),
+            span_to_json(self.span.as_ref()),
+            related.join(","),
+            strings(&self.notes),
+            strings(&self.helps)
+        )
+    }
+
+    fn colorize_header(&self, text: &str) -> ColoredString {
+        match self.severity {
+            Severity::Error => text.red().bold(),
+            Severity::Warning => text.yellow().bold(),
+        }
+    }
+
+    /// Renders the diagnostic in the style of rustc with the source lines of the spans.
+    /// `get_source` returns the content of the file at the path.
+    ///
+    /// ```text
+    /// error[E0201]: mismatched types: expected `number`, found `string`
+    ///  --> ./main.rots:1:19
+    ///   |
+    /// 1 | const a: number = "x";
+    ///   |                   ^^^
+    /// ```
+    pub fn render<'s>(&self, get_source: impl Fn(&str) -> Option<&'s str>) -> String {
+        let mut result = format!(
+            "{}: {}",
+            self.colorize_header(&format!("{}[{}]", self.severity, self.code)),
+            self.message.bold()
+        );
+        let width = self
+            .span
+            .iter()
+            .chain(self.labels.iter().map(|x| &x.span))
+            .map(|x| x.ln.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat(width);
+        let pipe = format!("{} {}", gutter, "|".blue().bold());
+
+        // the marks are grouped by the file, the file of the primary span comes first
+        let mut groups: Vec<(&Span, Vec<Mark>)> = Vec::new();
+        let primary = self.span.iter().map(|span| Mark {
+            span,
+            message: "",
+            is_primary: true,
+        });
+        let labels = self.labels.iter().map(|label| Mark {
+            span: &label.span,
+            message: &label.message,
+            is_primary: false,
+        });
+        for mark in primary.chain(labels) {
+            match groups.iter_mut().find(|(x, _)| x.path == mark.span.path) {
+                Some((_, marks)) => marks.push(mark),
+                None => groups.push((mark.span, vec![mark])),
+            }
+        }
+        for (i, (first, mut marks)) in groups.into_iter().enumerate() {
+            let arrow = if i == 0 && self.span.is_some() {
+                "-->"
+            } else {
+                ":::"
+            };
+            result.push_str(&format!(
+                "\n{}{} {}:{}:{}",
+                gutter,
+                arrow.blue().bold(),
+                first.path,
+                first.ln,
+                first.col
+            ));
+            let source = match get_source(&first.path) {
+                Some(source) => source,
+                None => {
+                    for mark in marks.iter().filter(|x| !x.message.is_empty()) {
+                        result.push_str(&format!(
+                            "\n{}{} {}:{}:{}: {}",
+                            gutter,
+                            ":::".blue().bold(),
+                            mark.span.path,
+                            mark.span.ln,
+                            mark.span.col,
+                            mark.message
+                        ));
+                    }
+                    continue;
+                }
+            };
+            let lines = source.lines().collect::<Vec<_>>();
+            marks.sort_by_key(|x| (x.span.ln, x.span.col));
+            result.push_str(&format!("\n{}", pipe));
+            let mut last_ln = None;
+            for mark in &marks {
+                let line = match lines.get(mark.span.ln as usize - 1) {
+                    Some(line) => *line,
+                    None => continue,
+                };
+                if last_ln != Some(mark.span.ln) {
+                    if last_ln.is_some_and(|x| x + 1 < mark.span.ln) {
+                        result.push_str(&format!("\n{}", "...".blue().bold()));
+                    }
+                    result.push_str(&format!(
+                        "\n{} {} {}",
+                        format!("{:>width$}", mark.span.ln, width = width)
+                            .blue()
+                            .bold(),
+                        "|".blue().bold(),
+                        expand_tabs(line)
+                    ));
+                    last_ln = Some(mark.span.ln);
+                }
+                let (offset, len) = get_underline(line, mark.span);
+                let mut underline = if mark.is_primary { "^" } else { "-" }.repeat(len);
+                if !mark.message.is_empty() {
+                    underline = format!("{} {}", underline, mark.message);
+                }
+                let underline = if mark.is_primary {
+                    self.colorize_header(&underline)
+                } else {
+                    underline.blue().bold()
+                };
+                result.push_str(&format!("\n{} {}{}", pipe, " ".repeat(offset), underline));
+            }
+        }
+        if !self.notes.is_empty() || !self.helps.is_empty() {
+            if self.span.is_some() || !self.labels.is_empty() {
+                result.push_str(&format!("\n{}", pipe));
+            }
+            for note in &self.notes {
+                result.push_str(&format!(
+                    "\n{} {} {}: {}",
+                    gutter,
+                    "=".blue().bold(),
+                    "note".bold(),
+                    note
+                ));
+            }
+            for help in &self.helps {
+                result.push_str(&format!(
+                    "\n{} {} {}: {}",
+                    gutter,
+                    "=".blue().bold(),
+                    "help".bold(),
+                    help
+                ));
+            }
+        }
+        result
+    }
+}
+
+fn span_to_json(span: Option<&Span>) -> String {
+    match span {
+        Some(span) => format!(
+            r#""file":{},"line":{},"column":{},"offset":{},"length":{}"#,
+            to_json_string(&span.path),
+            span.ln,
+            span.col,
+            span.ind,
+            span.len
+        ),
+        None => r#""file":null,"line":null,"column":null,"offset":null,"length":null"#.to_string(),
+    }
+}
+
+struct Mark<'a> {
+    span: &'a Span,
+    message: &'a str,
+    is_primary: bool,
+}
+
+/// Tabs are shown as 4 spaces so that the underlines are aligned with the line.
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', "    ")
+}
+
+/// Returns the display offset and the width of the underline of `span` in `line`.
+/// The column of the span is in characters and the length is in bytes.
+fn get_underline(line: &str, span: &Span) -> (usize, usize) {
+    let start = line
+        .char_indices()
+        .nth(span.col as usize - 1)
+        .map_or(line.len(), |(ind, _)| ind);
+    let end = (start + span.len).min(line.len());
+    let width = |text: Option<&str>| {
+        text.unwrap_or_default()
+            .chars()
+            .map(|x| if x == '\t' { 4 } else { 1 })
+            .sum::<usize>()
+    };
+    let offset = width(line.get(..start));
+    // an empty span (e.g. EOF) is still marked with a caret
+    let len = width(line.get(start..end)).max(1);
+    (offset, len)
+}
+
+/// error[E0201]: mismatched types: expected `number`, found `string`
+///     --> ./main.rots:1:19
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header = format!("{}[{}]", self.severity, self.code);
+        let header = match self.severity {
+            Severity::Error => header.red().bold(),
+            Severity::Warning => header.yellow().bold(),
+        };
+        write!(f, "{}: {}", header, self.message)?;
+        if let Some(span) = &self.span {
+            write!(f, "\n\t --> {}:{}:{}", span.path, span.ln, span.col)?;
+        }
+        for label in &self.labels {
+            write!(
+                f,
+                "\n\t --> {}:{}:{}: {}",
+                label.span.path, label.span.ln, label.span.col, label.message
+            )?;
+        }
+        for note in &self.notes {
+            write!(f, "\n\t = note: {}", note)?;
+        }
+        for help in &self.helps {
+            write!(f, "\n\t = help: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+/// The sink of the diagnostics reported by every phase of the compilation.
+/// The diagnostics are kept sorted by the file and the position, whatever phase reports them.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+        self.sort();
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|x| x.is_error())
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.diagnostics.iter().filter(|x| x.is_error()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    /// Sorts the diagnostics by the path, the line and the column. The ones without a span come
+    /// first and the ones at the same position are kept in the reported order.
+    fn sort(&mut self) {
+        self.diagnostics
+            .sort_by_key(|x| x.span.as_ref().map(|x| (x.path.clone(), x.ln, x.col)));
+    }
+
+    /// Renders the diagnostics in the style of rustc. See `Diagnostic::render`.
+    pub fn render<'s>(&self, get_source: impl Fn(&str) -> Option<&'s str>) -> String {
+        self.diagnostics
+            .iter()
+            .map(|x| format!("{}\n", x.render(&get_source)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the diagnostics as a JSON array. See `Diagnostic::to_json`.
+    pub fn to_json(&self) -> String {
+        let diagnostics = self
+            .diagnostics
+            .iter()
+            .map(|x| x.to_json())
+            .collect::<Vec<_>>();
+        format!("[{}]", diagnostics.join(","))
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
+
+impl Extend<Diagnostic> for Diagnostics {
+    fn extend<T: IntoIterator<Item = Diagnostic>>(&mut self, iter: T) {
+        self.diagnostics.extend(iter);
+        self.sort();
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(ln: u32, col: u32, ind: u64, len: usize) -> Span {
+        Span {
+            path: Rc::new("./main.rots".to_string()),
+            ln,
+            col,
+            ind,
+            len,
+        }
+    }
+
+    fn render(diagnostic: &Diagnostic, source: Option<&str>) -> String {
+        colored::control::set_override(false);
+        diagnostic.render(|_| source)
+    }
+
+    #[test]
+    fn render_test() {
+        let source = "const a = (): number => {\n\tlet b: number = \"x\";\n};";
+        let diagnostic = Diagnostic::error("E0201", "mismatched types")
+            .with_span(span(2, 18, 43, 3))
+            .with_help("remove the quotes");
+        assert_eq!(
+            render(&diagnostic, Some(source)),
+            r#"error[E0201]: mismatched types
+ --> ./main.rots:2:18
+  |
+2 |     let b: number = "x";
+  |                     ^^^
+  |
+  = help: remove the quotes"#
+        );
+    }
+
+    #[test]
+    fn render_non_ascii_test() {
+        let source = "const message = \"日本語\"; const text = 1 1;";
+        let diagnostic =
+            Diagnostic::error("E0101", "expected `;`, found `1`").with_span(span(1, 39, 44, 1));
+        assert_eq!(
+            render(&diagnostic, Some(source)),
+            r#"error[E0101]: expected `;`, found `1`
+ --> ./main.rots:1:39
+  |
+1 | const message = "日本語"; const text = 1 1;
+  |                                       ^"#
+        );
+    }
+
+    #[test]
+    fn to_json_test() {
+        let diagnostic = Diagnostic::error("E0219", "two \"entry\" points")
+            .with_span(span(4, 7, 21, 1))
+            .with_label(span(1, 7, 6, 1), "first");
+        assert_eq!(
+            diagnostic.to_json(),
+            r#"{"code":"E0219","severity":"error","message":"two \"entry\" points","file":"./main.rots","line":4,"column":7,"offset":21,"length":1,"related":[{"file":"./main.rots","line":1,"column":7,"offset":6,"length":1,"message":"first"}],"notes":[],"helps":[]}"#
+        );
+        let diagnostic = Diagnostic::error("E0000", "no span").with_help("a\nb");
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push(diagnostic);
+        assert_eq!(
+            diagnostics.to_json(),
+            r#"[{"code":"E0000","severity":"error","message":"no span","file":null,"line":null,"column":null,"offset":null,"length":null,"related":[],"notes":[],"helps":["a\nb"]}]"#
+        );
+    }
+
+    #[test]
+    fn sort_test() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.extend([
+            Diagnostic::error("E0201", "b").with_span(span(2, 1, 10, 1)),
+            Diagnostic::error("E0101", "a").with_span(span(1, 9, 8, 1)),
+            Diagnostic::error("E0202", "c").with_span(span(1, 9, 8, 1)),
+        ]);
+        diagnostics.push(Diagnostic::error("E0001", "d").with_span(span(1, 2, 1, 1)));
+        diagnostics.push(Diagnostic::error("E0000", "no span"));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|x| x.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["no span", "d", "a", "c", "b"]
+        );
+    }
+
+    #[test]
+    fn render_label_test() {
+        let source = "const a = 1;\n\n\nconst a = 2;";
+        let diagnostic = Diagnostic::error("E0000", "duplicated")
+            .with_span(span(4, 7, 21, 1))
+            .with_label(span(1, 7, 6, 1), "first defined here")
+            .with_note("names must be unique");
+        assert_eq!(
+            render(&diagnostic, Some(source)),
+            "error[E0000]: duplicated
+ --> ./main.rots:4:7
+  |
+1 | const a = 1;
+  |       - first defined here
+...
+4 | const a = 2;
+  |       ^
+  |
+  = note: names must be unique"
+        );
+        assert_eq!(
+            render(&diagnostic, None),
+            "error[E0000]: duplicated
+ --> ./main.rots:4:7
+ ::: ./main.rots:1:7: first defined here
+  |
+  = note: names must be unique"
+        );
+    }
+}
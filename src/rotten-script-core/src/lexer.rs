@@ -1,21 +1,91 @@
 mod lex_error;
+pub mod number_literal;
 pub mod reserved_word;
+pub mod string_literal;
 pub mod token;
 
 use std::rc::Rc;
 
-use lex_error::LexError;
-use regex::Regex;
+use lex_error::{LexError, LexErrorType};
 use reserved_word::ReservedWord;
-use token::{Token, TokenBase};
+use token::{TemplatePart, Token, TokenBase};
+
+/// How a string literal or a part of a template literal ends.
+enum LiteralEnd {
+    /// the closing quote
+    Quote,
+    /// `${`
+    Substitution,
+    /// EOF, or a line break in a string literal
+    Unterminated,
+}
+
+/// The punctuators sorted so that the longest match is found first.
+const PUNCTUATORS: [(&str, ReservedWord); 49] = [
+    (">>>=", ReservedWord::UnsignedRightShiftAssign),
+    (">>>", ReservedWord::UnsignedRightShift),
+    ("<<=", ReservedWord::LeftShiftAssign),
+    (">>=", ReservedWord::RightShiftAssign),
+    ("**=", ReservedWord::ExponentialAssign),
+    ("..=", ReservedWord::RangeInclusive),
+    ("=>", ReservedWord::Arrow),
+    ("==", ReservedWord::Equal),
+    ("!=", ReservedWord::NotEqual),
+    ("<=", ReservedWord::LessOrEq),
+    (">=", ReservedWord::GreaterOrEq),
+    ("<<", ReservedWord::LeftShift),
+    (">>", ReservedWord::RightShift),
+    ("**", ReservedWord::Exponential),
+    ("&&", ReservedWord::LogicalAnd),
+    ("||", ReservedWord::LogicalOr),
+    ("+=", ReservedWord::AdditiveAssign),
+    ("-=", ReservedWord::SubtractiveAssign),
+    ("*=", ReservedWord::MultiplicativeAssign),
+    ("/=", ReservedWord::DivisiveAssign),
+    ("%=", ReservedWord::ModuloAssign),
+    ("&=", ReservedWord::AndAssign),
+    ("^=", ReservedWord::XorAssign),
+    ("|=", ReservedWord::OrAssign),
+    ("..", ReservedWord::Range),
+    ("::", ReservedWord::DoubleColon),
+    ("=", ReservedWord::Assign),
+    ("(", ReservedWord::LeftParenthesis),
+    (")", ReservedWord::RightParenthesis),
+    ("[", ReservedWord::LeftSquareBracket),
+    ("]", ReservedWord::RightSquareBracket),
+    ("{", ReservedWord::LeftCurly),
+    ("}", ReservedWord::RightCurly),
+    (".", ReservedWord::Dot),
+    (",", ReservedWord::Comma),
+    (";", ReservedWord::SemiColon),
+    (":", ReservedWord::Colon),
+    ("+", ReservedWord::Add),
+    ("*", ReservedWord::Mult),
+    ("/", ReservedWord::Div),
+    ("-", ReservedWord::Sub),
+    ("%", ReservedWord::Mod),
+    ("<", ReservedWord::Less),
+    (">", ReservedWord::Greater),
+    ("&", ReservedWord::And),
+    ("|", ReservedWord::Or),
+    ("^", ReservedWord::Xor),
+    ("~", ReservedWord::Not),
+    ("!", ReservedWord::LogicalNot),
+];
 
 pub struct Lexer<'a> {
     source: &'a str,
     pub tokens: Vec<Token>,
     ind: u64,
     col: u32,
+    utf16_col: u32,
     ln: u32,
     file_path: Rc<String>,
+    errors: Vec<(Token, LexErrorType)>,
+    // the depths of `{` in the substitutions of template literals being lexed
+    template_depths: Vec<u32>,
+    // the lines of the doc comments which will be attached to the next token
+    doc_comment: Vec<&'a str>,
 }
 
 impl<'a> Lexer<'a> {
@@ -25,183 +95,332 @@ impl<'a> Lexer<'a> {
             tokens: Vec::new(),
             ind: 0,
             col: 1,
+            utf16_col: 1,
             ln: 1,
             file_path: Rc::new(path.to_string()),
+            errors: Vec::new(),
+            template_depths: Vec::new(),
+            doc_comment: Vec::new(),
         }
     }
 
     fn push_token(&mut self, token_base: TokenBase) {
-        self.tokens.push(Token::new(
-            Ok(token_base),
-            self.ln,
-            self.col,
-            self.ind,
-            self.file_path.clone(),
-        ));
+        self.tokens.push(
+            Token::new(
+                Ok(token_base),
+                self.ln,
+                self.col,
+                self.ind,
+                self.file_path.clone(),
+            )
+            .with_utf16_col(self.utf16_col),
+        );
     }
 
-    fn push_invalid_token(&mut self, base_str: String) -> Token {
+    fn push_invalid_token(&mut self, base_str: String) {
         let tk = Token::new(
             Err(base_str),
             self.ln,
             self.col,
             self.ind,
             self.file_path.clone(),
-        );
+        )
+        .with_utf16_col(self.utf16_col);
         self.tokens.push(tk.clone());
-        tk
+        self.errors.push((tk, LexErrorType::InvalidToken));
+    }
+
+    /// Returns (ln, col, UTF-16 col, ind) of the cursor.
+    fn position(&self) -> (u32, u32, u32, u64) {
+        (self.ln, self.col, self.utf16_col, self.ind)
+    }
+
+    /// Creates the token from `start` to the cursor.
+    fn new_token_from(
+        &self,
+        start: (u32, u32, u32, u64),
+        token_base: Result<TokenBase, String>,
+    ) -> Token {
+        let (ln, col, utf16_col, ind) = start;
+        Token::new(token_base, ln, col, ind, self.file_path.clone())
+            .with_len((self.ind - ind) as usize)
+            .with_utf16_col(utf16_col)
+    }
+
+    /// Returns the source which is not lexed yet.
+    fn rest(&self) -> &'a str {
+        &self.source[self.ind as usize..]
+    }
+
+    /// Advances the cursor by `len` bytes in the current line.
+    /// The column is counted in characters and in UTF-16 code units.
+    fn advance(&mut self, len: usize) {
+        let text = &self.rest()[..len];
+        self.col += text.chars().count() as u32;
+        self.utf16_col += text.encode_utf16().count() as u32;
+        self.ind += len as u64;
+    }
+
+    fn advance_line(&mut self) {
+        self.ind += 1;
+        self.ln += 1;
+        self.col = 1;
+        self.utf16_col = 1;
     }
 
     pub fn lex(&mut self) -> Result<(), LexError> {
-        let reserved_regex = Regex::new(
-            r"^(={1,2}[>]?|\(|\)|\{|\}|\[|\]|\.|,|:|;|\+=?|\*{1,2}=?|/=?|-=?|%=?|<<?=?|>{1,3}=?|&&|&=?|\|\||\|=?|\^=?|\~|!=?|const|let|import|export|from|default|true|false)",
-        )
-        .unwrap();
-        let identifier_regex = Regex::new(r"^([_a-zA-Z][_a-zA-Z0-9]*)").unwrap();
-        let dq_str_literal_regex = Regex::new(r#"^"(.*?)""#).unwrap();
-        let sq_str_literal_regex = Regex::new(r"^'(.*?)'").unwrap();
-        let number_literal_regex = Regex::new(r"^(\.\d+|[1-9]\d*\.\d+|[1-9]\d*|0\.\d*|0)").unwrap();
-        let mut line_com_mode = false;
-        let mut code = String::from(self.source);
-        let mut invalid_tokens = Vec::new();
-
-        while !code.is_empty() {
-            let replace_length;
-            if line_com_mode {
-                if let Some(last_ind) = code.find(|x| x == '\r' || x == '\n' || x == '\0') {
-                    replace_length = last_ind + 1;
-                    line_com_mode = false;
-                    self.ln += 1;
-                    self.col = 1;
-                } else {
-                    break;
+        while let Some(c) = self.rest().chars().next() {
+            let token_count = self.tokens.len();
+            match c {
+                '\n' => self.advance_line(),
+                '\r' | ' ' | '\t' => self.advance(1),
+                // `////` is not a doc comment
+                '/' if self.rest().starts_with("///") && !self.rest().starts_with("////") => {
+                    self.lex_doc_comment()
                 }
-            } else if code.starts_with(&['\n', '\r', ' ', '\t'][..]) {
-                if code.starts_with('\n') {
-                    self.ln += 1;
-                    self.col = 1;
-                } else {
-                    self.col += 1;
+                '/' if self.rest().starts_with("//") => self.skip_line_comment(),
+                '/' if self.rest().starts_with("/*") => self.skip_block_comment(),
+                '"' | '\'' => self.lex_string(c),
+                '`' => self.lex_template(TemplatePart::Head),
+                // the end of a substitution of a template literal
+                '}' if self.template_depths.last() == Some(&0) => {
+                    self.template_depths.pop();
+                    self.lex_template(TemplatePart::Tail);
                 }
+                _ if self.lex_number() || self.lex_punctuator() || self.lex_identifier() => {}
+                _ => {
+                    let rest = self.rest();
+                    let len = rest
+                        .find(&['\r', '\n', '\t', ' ', '\0'][..])
+                        .unwrap_or(rest.len())
+                        .max(c.len_utf8());
+                    self.push_invalid_token(rest[..len].to_string());
+                    self.advance(len);
+                }
+            }
+            if self.tokens.len() > token_count && !self.doc_comment.is_empty() {
+                let doc_comment = std::mem::take(&mut self.doc_comment).join("\n");
+                self.tokens[token_count].set_doc_comment(doc_comment);
+            }
+        }
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(LexError::new(std::mem::take(&mut self.errors)))
+        }
+    }
 
-                replace_length = 1;
-            } else if code.starts_with("//") {
-                line_com_mode = true;
-                replace_length = 2;
-                self.col += replace_length as u32;
-            } else if let Some(number) = number_literal_regex.find(&code) {
-                let mat = number.as_str();
-
-                self.push_token(TokenBase::Number(mat.to_string()));
-                replace_length = mat.len();
-                self.col += replace_length as u32;
-            } else if let Some(reserveds) = reserved_regex.find(&code) {
-                let mat = reserveds.as_str();
-
-                let word = match mat {
-                    "=>" => ReservedWord::Arrow,
-                    "=" => ReservedWord::Assign,
-                    "(" => ReservedWord::LeftParenthesis,
-                    ")" => ReservedWord::RightParenthesis,
-                    "[" => ReservedWord::LeftSquareBracket,
-                    "]" => ReservedWord::RightSquareBracket,
-                    "{" => ReservedWord::LeftCurly,
-                    "}" => ReservedWord::RightCurly,
-                    "." => ReservedWord::Dot,
-                    "," => ReservedWord::Comma,
-                    ";" => ReservedWord::SemiColon,
-                    "+" => ReservedWord::Add,
-                    "*" => ReservedWord::Mult,
-                    "/" => ReservedWord::Div,
-                    "-" => ReservedWord::Sub,
-                    "%" => ReservedWord::Mod,
-                    "<" => ReservedWord::Less,
-                    ">" => ReservedWord::Greater,
-                    "&" => ReservedWord::And,
-                    "|" => ReservedWord::Or,
-                    "^" => ReservedWord::Xor,
-                    "~" => ReservedWord::Not,
-                    "!" => ReservedWord::LogicalNot,
-                    ":" => ReservedWord::Colon,
-                    "const" => ReservedWord::Const,
-                    "let" => ReservedWord::Let,
-                    "import" => ReservedWord::Import,
-                    "export" => ReservedWord::Export,
-                    "from" => ReservedWord::From,
-                    "default" => ReservedWord::Default,
-                    "true" => ReservedWord::True,
-                    "false" => ReservedWord::False,
-                    "<<" => ReservedWord::LeftShift,
-                    ">>" => ReservedWord::RightShift,
-                    ">>>" => ReservedWord::UnsignedRightShift,
-                    "<=" => ReservedWord::LessOrEq,
-                    ">=" => ReservedWord::GreaterOrEq,
-                    "==" => ReservedWord::Equal,
-                    "!=" => ReservedWord::NotEqual,
-                    "**" => ReservedWord::Exponential,
-                    "&&" => ReservedWord::LogicalAnd,
-                    "||" => ReservedWord::LogicalOr,
-                    "+=" => ReservedWord::AdditiveAssign,
-                    "-=" => ReservedWord::SubtractiveAssign,
-                    "*=" => ReservedWord::MultiplicativeAssign,
-                    "/=" => ReservedWord::DivisiveAssign,
-                    "%=" => ReservedWord::ModuloAssign,
-                    "<<=" => ReservedWord::LeftShiftAssign,
-                    ">>=" => ReservedWord::RightShiftAssign,
-                    ">>>=" => ReservedWord::UnsignedRightShiftAssign,
-                    "&=" => ReservedWord::AndAssign,
-                    "^=" => ReservedWord::XorAssign,
-                    "|=" => ReservedWord::OrAssign,
-                    "**=" => ReservedWord::ExponentialAssign,
-                    _ => panic!(),
-                };
-                // self.tokens.push(TokenBase::Reserved(word));
-
-                self.push_token(TokenBase::Reserved(word));
-
-                replace_length = mat.len();
-                self.col += replace_length as u32;
-            } else if let Some(ident) = identifier_regex.find(&code) {
-                let mat = ident.as_str();
-                // self.tokens.push(TokenBase::Identifier(mat.to_string()));
-                self.push_token(TokenBase::Identifier(mat.to_string()));
-
-                replace_length = mat.len();
-                self.col += replace_length as u32;
-            } else if let Some(dq_str) = dq_str_literal_regex.find(&code) {
-                let mat = dq_str.as_str().trim_matches('"');
-                // self.tokens.push(TokenBase::String(mat.to_string()));
-                self.push_token(TokenBase::String(mat.to_string()));
-
-                replace_length = mat.len() + 2;
-                self.col += replace_length as u32;
-            } else if let Some(sq_str) = sq_str_literal_regex.find(&code) {
-                let mat = sq_str.as_str().trim_matches('\'');
-                // self.tokens.push(TokenBase::String(mat.to_string()));
-                self.push_token(TokenBase::String(mat.to_string()));
-                replace_length = mat.len() + 2;
-                self.col += replace_length as u32;
+    /// Skips the comment until the end of the line. The line break is not consumed.
+    fn skip_line_comment(&mut self) {
+        let rest = self.rest();
+        let len = rest.find('\n').unwrap_or(rest.len());
+        self.advance(len);
+    }
+
+    /// Reads the line of a doc comment `/// ...`, whose leading space is removed.
+    fn lex_doc_comment(&mut self) {
+        let rest = self.rest();
+        let len = rest.find('\n').unwrap_or(rest.len());
+        let line = rest[3..len].trim_end_matches('\r');
+        self.doc_comment
+            .push(line.strip_prefix(' ').unwrap_or(line));
+        self.advance(len);
+    }
+
+    /// Skips the block comment, which may be nested e.g. `/* a /* b */ c */`.
+    fn skip_block_comment(&mut self) {
+        let start = self.position();
+        let mut depth = 0;
+        loop {
+            let rest = self.rest();
+            let len = match rest.find(&['/', '*', '\n'][..]) {
+                Some(len) => len,
+                None => break,
+            };
+            self.advance(len);
+            let rest = &rest[len..];
+            if rest.starts_with("/*") {
+                depth += 1;
+                self.advance(2);
+            } else if rest.starts_with("*/") {
+                depth -= 1;
+                self.advance(2);
+                if depth == 0 {
+                    return;
+                }
+            } else if rest.starts_with('\n') {
+                self.advance_line();
             } else {
-                let next = code.find(&['\r', '\n', '\t', ' ', '\0'][..]);
-                match next {
-                    Some(ind) if ind > 0 => {
-                        invalid_tokens.push(self.push_invalid_token(code[..ind].to_string()));
-                        replace_length = ind;
-                    }
-                    None => {
-                        invalid_tokens.push(self.push_invalid_token(code[..].to_string()));
-                        replace_length = code.len();
+                self.advance(1);
+            }
+        }
+        self.advance(self.rest().len());
+        let (ln, col, utf16_col, ind) = start;
+        let token = Token::new(Err("/*".to_string()), ln, col, ind, self.file_path.clone())
+            .with_utf16_col(utf16_col);
+        self.errors.push((token, LexErrorType::UnterminatedComment));
+    }
+
+    fn lex_number(&mut self) -> bool {
+        let rest = self.rest();
+        let scanned = number_literal::scan(rest);
+        let len = match scanned {
+            Ok(0) => return false,
+            Ok(len) | Err(len) => len,
+        };
+        let start = self.position();
+        self.push_token(TokenBase::Number(rest[..len].to_string()));
+        self.advance(len);
+        // e.g. `007`. The token is still pushed not to report the parse errors caused by it.
+        if scanned.is_err() {
+            let token = self.new_token_from(start, Err(rest[..len].to_string()));
+            self.errors
+                .push((token, LexErrorType::InvalidNumericLiteral));
+        }
+        true
+    }
+
+    fn lex_punctuator(&mut self) -> bool {
+        let rest = self.rest();
+        let first = rest.as_bytes()[0];
+        match PUNCTUATORS
+            .iter()
+            .find(|(text, _)| text.as_bytes()[0] == first && rest.starts_with(text))
+        {
+            Some((text, word)) => {
+                if let Some(depth) = self.template_depths.last_mut() {
+                    match word {
+                        ReservedWord::LeftCurly => *depth += 1,
+                        ReservedWord::RightCurly => *depth -= 1,
+                        _ => {}
                     }
-                    Some(_) => panic!("out of bound!"),
                 }
+                self.push_token(TokenBase::Reserved(*word));
+                self.advance(text.len());
+                true
             }
-            self.ind += replace_length as u64;
-            code.replace_range(0..replace_length, "");
+            None => false,
         }
-        if invalid_tokens.is_empty() {
-            Ok(())
-        } else {
-            Err(LexError::new(invalid_tokens))
+    }
+
+    // Identifier = ( "_" | alphabet ) , { "_" | alphabet | digit };
+    fn lex_identifier(&mut self) -> bool {
+        let bytes = self.rest().as_bytes();
+        if !(bytes[0] == b'_' || bytes[0].is_ascii_alphabetic()) {
+            return false;
+        }
+        let len = bytes
+            .iter()
+            .take_while(|x| **x == b'_' || x.is_ascii_alphanumeric())
+            .count();
+        let ident = &self.rest()[..len];
+        let token_base = match ReservedWord::from_keyword(ident) {
+            Some(word) => TokenBase::Reserved(word),
+            None => TokenBase::Identifier(ident.to_string()),
+        };
+        self.push_token(token_base);
+        self.advance(len);
+        true
+    }
+
+    /// Lexes the string literal quoted by `quote`.
+    fn lex_string(&mut self, quote: char) {
+        let start = self.position();
+        self.advance(1);
+        let (value, end) = self.read_literal(quote);
+        self.push_literal(start, TokenBase::String(value), end);
+    }
+
+    /// Lexes a part of a template literal, which starts with "`" (`first` is Head) or
+    /// `}` closing a substitution (`first` is Tail).
+    fn lex_template(&mut self, first: TemplatePart) {
+        let start = self.position();
+        self.advance(1);
+        let (value, end) = self.read_literal('`');
+        let part = match (first, &end) {
+            (TemplatePart::Head, LiteralEnd::Substitution) => TemplatePart::Head,
+            (TemplatePart::Head, _) => TemplatePart::NoSubstitution,
+            (_, LiteralEnd::Substitution) => TemplatePart::Middle,
+            (_, _) => TemplatePart::Tail,
+        };
+        if let LiteralEnd::Substitution = end {
+            self.template_depths.push(0);
+        }
+        self.push_literal(start, TokenBase::Template(part, value), end);
+    }
+
+    fn push_literal(
+        &mut self,
+        start: (u32, u32, u32, u64),
+        token_base: TokenBase,
+        end: LiteralEnd,
+    ) {
+        match end {
+            LiteralEnd::Unterminated => {
+                let text = self.source[start.3 as usize..self.ind as usize].to_string();
+                let token = self.new_token_from(start, Err(text));
+                self.tokens.push(token.clone());
+                self.errors.push((token, LexErrorType::UnterminatedLiteral));
+            }
+            _ => {
+                let token = self.new_token_from(start, Ok(token_base));
+                self.tokens.push(token);
+            }
+        }
+    }
+
+    /// Reads the characters until `quote` (or `${` in template literals) and decodes the escape
+    /// sequences. Line breaks are allowed only in template literals.
+    fn read_literal(&mut self, quote: char) -> (String, LiteralEnd) {
+        let is_template = quote == '`';
+        let mut value = String::new();
+        while let Some(c) = self.rest().chars().next() {
+            match c {
+                _ if c == quote => {
+                    self.advance(1);
+                    return (value, LiteralEnd::Quote);
+                }
+                '$' if is_template && self.rest().starts_with("${") => {
+                    self.advance(2);
+                    return (value, LiteralEnd::Substitution);
+                }
+                '\\' => self.read_escape(&mut value),
+                '\n' if is_template => {
+                    value.push('\n');
+                    self.advance_line();
+                }
+                // CRLF in template literals is normalized to LF
+                '\r' if is_template => self.advance(1),
+                '\n' => break,
+                _ => {
+                    value.push(c);
+                    self.advance(c.len_utf8());
+                }
+            }
+        }
+        (value, LiteralEnd::Unterminated)
+    }
+
+    fn read_escape(&mut self, value: &mut String) {
+        let rest = self.rest();
+        // line continuation
+        if rest[1..].starts_with("\r\n") || rest[1..].starts_with('\n') {
+            self.advance(rest.find('\n').unwrap());
+            self.advance_line();
+            return;
+        }
+        match string_literal::unescape(&rest[1..]) {
+            Ok((c, len)) => {
+                value.push(c);
+                self.advance(len + 1);
+            }
+            Err(len) => {
+                let start = self.position();
+                self.advance(len + 1);
+                let token = self.new_token_from(start, Err(rest[..len + 1].to_string()));
+                self.errors
+                    .push((token, LexErrorType::InvalidEscapeSequence));
+            }
         }
     }
 }
@@ -233,6 +452,47 @@ mod tests {
             assert_eq!(0, lexer.tokens.len());
         }
     }
+    #[test]
+    fn test_block_comment() {
+        let source = "/* a /* b */ c\n */ x /**/ y /*\r\n*/ z";
+        let mut lexer = Lexer::new(source, "");
+        lexer.lex().unwrap();
+        let actual = lexer
+            .tokens
+            .iter()
+            .map(|x| {
+                let position = x.get_token_position();
+                (x.to_string(), position.ln, position.col)
+            })
+            .collect::<Vec<_>>();
+        let expected = [("x", 2, 5), ("y", 2, 12), ("z", 3, 4)];
+        assert_eq!(
+            expected
+                .iter()
+                .map(|(x, ln, col)| (x.to_string(), *ln, *col))
+                .collect::<Vec<_>>(),
+            actual
+        );
+
+        let mut lexer = Lexer::new("a /* b /* c */", "");
+        let diagnostics = lexer.lex().unwrap_err().to_diagnostics();
+        assert_eq!("E0005", diagnostics[0].code);
+        assert_eq!(3, diagnostics[0].span.as_ref().unwrap().col);
+        assert_eq!(1, lexer.tokens.len());
+    }
+
+    #[test]
+    fn test_doc_comment() {
+        let source = "/// first\r\n///second\n//// not a doc\n\n///\nconst a = 1; /// last\n";
+        let mut lexer = Lexer::new(source, "");
+        lexer.lex().unwrap();
+        assert_eq!(5, lexer.tokens.len());
+        assert_eq!(Some("first\nsecond\n"), lexer.tokens[0].get_doc_comment());
+        assert!(lexer.tokens[1..]
+            .iter()
+            .all(|x| x.get_doc_comment().is_none()));
+    }
+
     #[test]
     fn test_identifier() {
         let cases = vec!["ident", "ident ident", "ide\nnt", "ode \t den"];
@@ -391,13 +651,64 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_numeric_literal() {
+        let cases = [
+            "0x1F",
+            "0o17",
+            "0b1010",
+            "1e9",
+            "1.5E-3",
+            "1_000_000",
+            "10n",
+            "0xFFn",
+        ];
+        for item in cases.iter() {
+            let mut lexer = Lexer::new(item, "");
+            lexer.lex().unwrap();
+            assert_eq!(1, lexer.tokens.len());
+            assert_eq_token_and_token_base(TokenBase::Number(item.to_string()), &lexer.tokens[0]);
+        }
+
+        let mut lexer = Lexer::new("[007, 1__0, 0x, 1.5n]", "");
+        let diagnostics = lexer.lex().unwrap_err().to_diagnostics();
+        let errors = diagnostics
+            .iter()
+            .map(|x| (x.code, x.message.as_str(), x.span.as_ref().unwrap().col))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("E0004", "invalid numeric literal `007`", 2),
+                ("E0004", "invalid numeric literal `1__0`", 7),
+                ("E0004", "invalid numeric literal `0x`", 13),
+                ("E0004", "invalid numeric literal `1.5n`", 17),
+            ],
+            errors
+        );
+        // each invalid literal is still a single token
+        assert_eq!(9, lexer.tokens.len());
+    }
+
+    #[test]
+    fn test_range() {
+        let cases = [("0..10", ".."), ("1..=n", "..="), ("10..20", "..")];
+        for (item, range) in cases.iter() {
+            let mut lexer = Lexer::new(item, "");
+            lexer.lex().unwrap();
+            assert_eq!(3, lexer.tokens.len());
+            assert_eq!(*range, lexer.tokens[1].to_string());
+        }
+    }
+
     #[test]
     fn test_reserved() {
         let cases = vec![
             "=", "(", ")", "{", "}", "[", "]", ".", ",", ";", "=>", "const", "let", "import",
             "export", "default", "from", "true", "false", "+", "*", "/", "-", "%", "<", ">", "&",
             "|", "^", "~", "!", "<<", ">>", ">>>", "<=", ">=", "==", "!=", "**", "&&", "||", "+=",
-            "-=", "*=", "/=", "%=", "<<=", ">>=", ">>>=", "&=", "^=", "|=", "**=", ":",
+            "-=", "*=", "/=", "%=", "<<=", ">>=", ">>>=", "&=", "^=", "|=", "**=", ":", "if",
+            "else", "match", "_", "..", "..=", "for", "in", "while", "struct", "enum", "::",
         ];
         use super::ReservedWord::*;
         use super::TokenBase::Reserved;
@@ -463,9 +774,232 @@ mod tests {
                 51 => assert_eq!(Reserved(OrAssign), first),
                 52 => assert_eq!(Reserved(ExponentialAssign), first),
                 53 => assert_eq!(Reserved(Colon), first),
-                // 54 => assert_eq!(Reserved(LeftShiftAssign), first),
+                54 => assert_eq!(Reserved(If), first),
+                55 => assert_eq!(Reserved(Else), first),
+                56 => assert_eq!(Reserved(Match), first),
+                57 => assert_eq!(Reserved(Underscore), first),
+                58 => assert_eq!(Reserved(Range), first),
+                59 => assert_eq!(Reserved(RangeInclusive), first),
+                60 => assert_eq!(Reserved(For), first),
+                61 => assert_eq!(Reserved(In), first),
+                62 => assert_eq!(Reserved(While), first),
+                63 => assert_eq!(Reserved(Struct), first),
+                64 => assert_eq!(Reserved(Enum), first),
+                65 => assert_eq!(Reserved(DoubleColon), first),
                 _ => panic!(),
             }
         }
     }
+
+    #[test]
+    fn test_keyword_prefix() {
+        let cases = [
+            "constant",
+            "letters",
+            "importer",
+            "exports",
+            "defaults",
+            "fromage",
+            "trueish",
+            "falsey",
+            "iff",
+            "elsewhere",
+            "matches",
+            "format",
+            "inner",
+            "whiled",
+            "structure",
+            "enums",
+            "ask",
+            "_if",
+            "const_",
+            "let2",
+            "__",
+        ];
+        for item in cases.iter() {
+            let mut lexer = Lexer::new(item, "");
+            lexer.lex().unwrap();
+            assert_eq!(1, lexer.tokens.len());
+            assert_eq_token_and_token_base(
+                TokenBase::Identifier(item.to_string()),
+                &lexer.tokens[0],
+            );
+        }
+
+        let mut lexer = Lexer::new("const constant=let letters;", "");
+        lexer.lex().unwrap();
+        let actual = lexer
+            .tokens
+            .iter()
+            .map(|x| x.get_token().clone().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                TokenBase::Reserved(ReservedWord::Const),
+                TokenBase::Identifier("constant".to_string()),
+                TokenBase::Reserved(ReservedWord::Assign),
+                TokenBase::Reserved(ReservedWord::Let),
+                TokenBase::Identifier("letters".to_string()),
+                TokenBase::Reserved(ReservedWord::SemiColon),
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn test_string_escape() {
+        let cases = [
+            (r#""a\"b""#, "a\"b"),
+            (r"'it\'s'", "it's"),
+            (r"'\n\t\\\0'", "\n\t\\\0"),
+            (r"'\x41é\u{1F600}😀'", "Aé😀😀"),
+            ("'a\\\nb'", "ab"),
+            ("'é😀'", "é😀"),
+        ];
+        for (item, expected) in cases.iter() {
+            let mut lexer = Lexer::new(item, "");
+            lexer.lex().unwrap();
+            assert_eq!(1, lexer.tokens.len());
+            assert_eq_token_and_token_base(
+                TokenBase::String(expected.to_string()),
+                &lexer.tokens[0],
+            );
+            assert_eq!(item.len(), lexer.tokens[0].get_token_position().len);
+        }
+
+        let mut lexer = Lexer::new(r#"a("\x4\u{110000}\7", "b"#, "");
+        let diagnostics = lexer.lex().unwrap_err().to_diagnostics();
+        let errors = diagnostics
+            .iter()
+            .map(|x| (x.code, x.message.as_str(), x.span.as_ref().unwrap().col))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("E0003", r"invalid escape sequence `\x4`", 4),
+                ("E0003", r"invalid escape sequence `\u{110000}`", 7),
+                ("E0003", r"invalid escape sequence `\7`", 17),
+                ("E0002", r#"unterminated literal `"b`"#, 22),
+            ],
+            errors
+        );
+        // the string with invalid escape sequences is still a string
+        assert_eq_token_and_token_base(TokenBase::String(String::new()), &lexer.tokens[2]);
+    }
+
+    #[test]
+    fn test_template() {
+        let source = "`a${x + `b${y}`}\n${ {c: 1} }\\`\\${`";
+        let mut lexer = Lexer::new(source, "");
+        lexer.lex().unwrap();
+        let actual = lexer
+            .tokens
+            .iter()
+            .map(|x| {
+                let position = x.get_token_position();
+                (
+                    format!("{:?}", x.get_token().as_ref().unwrap()),
+                    position.ln,
+                    position.col,
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected = [
+            ("template(Head, a)", 1, 1),
+            ("identifier(x)", 1, 5),
+            ("Add", 1, 7),
+            ("template(Head, b)", 1, 9),
+            ("identifier(y)", 1, 13),
+            ("template(Tail, )", 1, 14),
+            ("template(Middle, \n)", 1, 16),
+            ("LeftCurly", 2, 4),
+            ("identifier(c)", 2, 5),
+            ("Colon", 2, 6),
+            ("number(1)", 2, 8),
+            ("RightCurly", 2, 9),
+            ("template(Tail, `${)", 2, 11),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(token, ln, col)| (token.to_string(), *ln, *col))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+
+        let mut lexer = Lexer::new("`a${b}", "");
+        assert!(lexer.lex().is_err());
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let cases = [
+            ("==>", vec!["==", ">"]),
+            ("a>>>=b", vec!["a", ">>>=", "b"]),
+            ("1...2", vec!["1", "..", ".2"]),
+            ("x**=-1", vec!["x", "**=", "-", "1"]),
+            ("a::b:c", vec!["a", "::", "b", ":", "c"]),
+        ];
+        for (item, expected) in cases.iter() {
+            let mut lexer = Lexer::new(item, "");
+            lexer.lex().unwrap();
+            let actual = lexer
+                .tokens
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(expected, &actual);
+        }
+    }
+
+    #[test]
+    fn test_position() {
+        let source = "const a = 'x';// comment\r\n\tlet é b = 1.5;";
+        let mut lexer = Lexer::new(source, "");
+        assert!(lexer.lex().is_err());
+
+        let positions = lexer
+            .tokens
+            .iter()
+            .map(|x| {
+                let position = x.get_token_position();
+                (position.ln, position.col, position.ind, position.len)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (1, 1, 0, 5),
+                (1, 7, 6, 1),
+                (1, 9, 8, 1),
+                (1, 11, 10, 3),
+                (1, 14, 13, 1),
+                (2, 2, 27, 3),
+                (2, 6, 31, 2),
+                (2, 8, 34, 1),
+                (2, 10, 36, 1),
+                (2, 12, 38, 3),
+                (2, 15, 41, 1),
+            ],
+            positions
+        );
+        for token in &lexer.tokens {
+            let position = token.get_token_position();
+            let ind = position.ind as usize;
+            assert_eq!(
+                token.get_base_text().trim_matches('"'),
+                source[ind..ind + position.len].trim_matches('\'')
+            );
+        }
+    }
+
+    #[test]
+    fn test_column_in_characters() {
+        let mut lexer = Lexer::new("const message = \"日本語\"; const text = 1;", "");
+        lexer.lex().unwrap();
+        let position = lexer.tokens.last().unwrap().get_token_position();
+        assert_eq!((1, 38, 43), (position.ln, position.col, position.ind));
+
+        // the characters outside the BMP are two UTF-16 code units
+        let mut lexer = Lexer::new("const s = \"😀\"; const t = 1;", "");
+        lexer.lex().unwrap();
+        let position = lexer.tokens.last().unwrap().get_token_position();
+        assert_eq!((27, 28), (position.col, position.utf16_col));
+    }
 }
@@ -2,27 +2,76 @@ use std::{error::Error, fmt::Display};
 
 use colored::Colorize;
 
+use crate::diagnostic::Diagnostic;
+
 use super::token::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LexErrorType {
+    InvalidToken,
+    UnterminatedLiteral,
+    InvalidEscapeSequence,
+    InvalidNumericLiteral,
+    UnterminatedComment,
+}
+
+impl LexErrorType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexErrorType::InvalidToken => "E0001",
+            LexErrorType::UnterminatedLiteral => "E0002",
+            LexErrorType::InvalidEscapeSequence => "E0003",
+            LexErrorType::InvalidNumericLiteral => "E0004",
+            LexErrorType::UnterminatedComment => "E0005",
+        }
+    }
+
+    fn message(&self, base_text: &str) -> String {
+        match self {
+            LexErrorType::InvalidToken => format!("invalid token `{}`", base_text),
+            LexErrorType::UnterminatedLiteral => {
+                format!("unterminated literal `{}`", base_text)
+            }
+            LexErrorType::InvalidEscapeSequence => {
+                format!("invalid escape sequence `{}`", base_text)
+            }
+            LexErrorType::InvalidNumericLiteral => {
+                format!("invalid numeric literal `{}`", base_text)
+            }
+            LexErrorType::UnterminatedComment => "unterminated block comment".to_string(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct LexError {
-    invalid_tokens: Vec<Token>,
+    errors: Vec<(Token, LexErrorType)>,
 }
 
 impl LexError {
-    pub fn new(invalid_tokens: Vec<Token>) -> Self {
-        Self { invalid_tokens }
+    pub fn new(errors: Vec<(Token, LexErrorType)>) -> Self {
+        Self { errors }
+    }
+
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        self.errors
+            .iter()
+            .map(|(token, error_type)| {
+                Diagnostic::error(error_type.code(), error_type.message(token.get_base_text()))
+                    .with_span(token.get_token_position())
+            })
+            .collect()
     }
 }
 
 impl Display for LexError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for token in &self.invalid_tokens {
-            let base_text = token.get_base_text();
+        for (token, error_type) in &self.errors {
             let position = token.get_token_position();
             f.write_str(&format!(
-                "{}: invalid token `{}` \n\t --> {}:{}:{}\n",
+                "{}: {} \n\t --> {}:{}:{}\n",
                 "error".red().bold(),
-                base_text,
+                error_type.message(token.get_base_text()),
                 position.path,
                 position.ln,
                 position.col
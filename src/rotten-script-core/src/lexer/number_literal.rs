@@ -0,0 +1,174 @@
+/// Returns the length of the numeric literal at the start of `source`, or the length of the invalid
+/// literal as the error, e.g. `0x1F`, `1_000.5e-3`, `10n`, `.5` and `5.` are valid but `007` and
+/// `1__0` are not. Returns `Ok(0)` if `source` does not start with a number.
+/// The point followed by another point is not a part of the literal, e.g. `0..10`.
+// NumericLiteral = ( DecimalLiteral | DecimalIntegerLiteral , "n" | NonDecimalIntegerLiteral , [ "n" ] );
+// DecimalLiteral = ( DecimalIntegerLiteral , [ "." , [ Digits ] ] | "." , Digits ) , [ ExponentPart ];
+// DecimalIntegerLiteral = "0" | NonZeroDigit , [ [ "_" ] , Digits ];
+// NonDecimalIntegerLiteral = ( "0x" | "0X" ) , HexDigits | ( "0o" | "0O" ) , OctalDigits | ( "0b" | "0B" ) , BinaryDigits;
+pub fn scan(source: &str) -> Result<usize, usize> {
+    let bytes = source.as_bytes();
+    let is_fraction = bytes.first() == Some(&b'.') && bytes.get(1).is_some_and(u8::is_ascii_digit);
+    if !(is_fraction || bytes.first().is_some_and(u8::is_ascii_digit)) {
+        return Ok(0);
+    }
+    scan_number(bytes).ok_or_else(|| invalid_len(bytes))
+}
+
+/// Returns true if the numeric literal is a bigint, e.g. `10n`.
+pub fn is_bigint(literal: &str) -> bool {
+    literal.ends_with('n')
+}
+
+fn scan_number(bytes: &[u8]) -> Option<usize> {
+    let radix_digit: Option<fn(&u8) -> bool> = match bytes {
+        [b'0', b'x' | b'X', ..] => Some(u8::is_ascii_hexdigit),
+        [b'0', b'o' | b'O', ..] => Some(|x| (b'0'..=b'7').contains(x)),
+        [b'0', b'b' | b'B', ..] => Some(|x| *x == b'0' || *x == b'1'),
+        _ => None,
+    };
+    let (mut len, is_integer) = match radix_digit {
+        Some(is_digit) => (scan_digits(bytes, 2, is_digit)?, true),
+        None => scan_decimal(bytes)?,
+    };
+    if bytes.get(len) == Some(&b'n') {
+        if !is_integer {
+            return None;
+        }
+        len += 1;
+    }
+    // e.g. `3in` and `1_`
+    match bytes.get(len) {
+        Some(x) if x.is_ascii_alphanumeric() || *x == b'_' => None,
+        _ => Some(len),
+    }
+}
+
+/// Returns the length and true if it is an integer without the fraction and the exponent.
+fn scan_decimal(bytes: &[u8]) -> Option<(usize, bool)> {
+    let mut is_integer = true;
+    let mut len = if bytes[0] == b'.' {
+        0
+    } else if bytes[0] == b'0' {
+        // legacy octal literals such as `007` are not allowed in strict mode
+        1
+    } else {
+        scan_digits(bytes, 0, u8::is_ascii_digit)?
+    };
+    if bytes.get(len) == Some(&b'.') && bytes.get(len + 1) != Some(&b'.') {
+        // the digits after the point may be omitted, e.g. `5.` and `1.e3`
+        len = scan_digits(bytes, len + 1, u8::is_ascii_digit).unwrap_or(len + 1);
+        is_integer = false;
+    }
+    if let Some(b'e' | b'E') = bytes.get(len) {
+        let sign = matches!(bytes.get(len + 1), Some(b'+' | b'-')) as usize;
+        len = scan_digits(bytes, len + 1 + sign, u8::is_ascii_digit)?;
+        is_integer = false;
+    }
+    Some((len, is_integer))
+}
+
+/// Returns the end of the digits from `start`, which may be separated by `_` e.g. `1_000`.
+fn scan_digits(bytes: &[u8], start: usize, is_digit: fn(&u8) -> bool) -> Option<usize> {
+    let mut end = start;
+    while let Some(x) = bytes.get(end) {
+        if is_digit(x) {
+            end += 1;
+        } else if *x == b'_' && end > start && bytes.get(end + 1).is_some_and(is_digit) {
+            end += 2;
+        } else {
+            break;
+        }
+    }
+    (end > start).then_some(end)
+}
+
+// the length of the word which looks like a number, e.g. `0x` and `1e+5_`
+fn invalid_len(bytes: &[u8]) -> usize {
+    let mut len = 0;
+    while let Some(x) = bytes.get(len) {
+        let is_sign = (*x == b'+' || *x == b'-') && matches!(bytes[len - 1], b'e' | b'E');
+        let is_point = *x == b'.' && bytes.get(len + 1) != Some(&b'.');
+        if x.is_ascii_alphanumeric() || *x == b'_' || is_sign || is_point {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        let valid_cases = [
+            "0",
+            "33",
+            ".435",
+            "3232.042",
+            "0.33",
+            "1_000_000",
+            "1e9",
+            "1.5E-3",
+            ".5e+2",
+            "5.",
+            "0.",
+            "1.e3",
+            "0e1",
+            "0x1F",
+            "0XfF_fF",
+            "0o17",
+            "0b1010_0101",
+            "10n",
+            "0n",
+            "0x1Fn",
+            "0b1n",
+        ];
+        for item in valid_cases.iter() {
+            assert_eq!(Ok(item.len()), scan(item), "{}", item);
+        }
+
+        let cases = [
+            ("1..10", Ok(1)),
+            ("1.5..2", Ok(3)),
+            ("1.", Ok(2)),
+            ("1.)", Ok(2)),
+            ("1..=n", Ok(1)),
+            ("1._5", Err(4)),
+            ("1.foo", Err(5)),
+            ("2+1", Ok(1)),
+            ("1e3-1", Ok(3)),
+            ("a1", Ok(0)),
+            (".a", Ok(0)),
+            ("007", Err(3)),
+            ("08.5", Err(4)),
+            ("1__0", Err(4)),
+            ("1_", Err(2)),
+            ("1_.5", Err(4)),
+            ("0x", Err(2)),
+            ("0x_1", Err(4)),
+            ("0b102", Err(5)),
+            ("0o8", Err(3)),
+            ("1e", Err(2)),
+            ("1e+", Err(3)),
+            ("1.5n", Err(4)),
+            ("1e3n", Err(4)),
+            ("01n", Err(3)),
+            ("3in", Err(3)),
+        ];
+        for (item, expected) in cases.iter() {
+            assert_eq!(*expected, scan(item), "{}", item);
+        }
+    }
+
+    #[test]
+    fn test_is_bigint() {
+        assert!(is_bigint("10n"));
+        assert!(is_bigint("0xFFn"));
+        assert!(!is_bigint("10"));
+        assert!(!is_bigint("0xFF"));
+    }
+}
@@ -25,6 +25,7 @@ pub enum ReservedWord {
     Not = '~' as isize,
     LogicalNot = '!' as isize,
     Colon = ':' as isize,
+    Underscore = '_' as isize,
     Arrow = 1000,
     Const,
     Let,
@@ -56,23 +57,69 @@ pub enum ReservedWord {
     XorAssign,
     OrAssign,
     ExponentialAssign,
+    If,
+    Else,
+    Match,
+    Range,
+    RangeInclusive,
+    For,
+    In,
+    While,
+    Struct,
+    Enum,
+    DoubleColon,
+    As,
+}
+
+/// The keywords. They are recognized only as whole identifiers, e.g. `constant` is an identifier.
+pub const KEYWORDS: [(&str, ReservedWord); 18] = [
+    ("const", ReservedWord::Const),
+    ("let", ReservedWord::Let),
+    ("import", ReservedWord::Import),
+    ("export", ReservedWord::Export),
+    ("default", ReservedWord::Default),
+    ("from", ReservedWord::From),
+    ("true", ReservedWord::True),
+    ("false", ReservedWord::False),
+    ("if", ReservedWord::If),
+    ("else", ReservedWord::Else),
+    ("match", ReservedWord::Match),
+    ("for", ReservedWord::For),
+    ("in", ReservedWord::In),
+    ("while", ReservedWord::While),
+    ("struct", ReservedWord::Struct),
+    ("enum", ReservedWord::Enum),
+    ("as", ReservedWord::As),
+    ("_", ReservedWord::Underscore),
+];
+
+impl ReservedWord {
+    /// Returns the keyword whose text is `ident`.
+    pub fn from_keyword(ident: &str) -> Option<ReservedWord> {
+        KEYWORDS
+            .iter()
+            .find(|(text, _)| *text == ident)
+            .map(|(_, word)| *word)
+    }
+
+    /// Returns the text if this is a keyword.
+    pub fn keyword_text(&self) -> Option<&'static str> {
+        KEYWORDS
+            .iter()
+            .find(|(_, word)| word == self)
+            .map(|(text, _)| *text)
+    }
 }
 
 impl Display for ReservedWord {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let text = if *self < ReservedWord::Arrow {
             (*self as u8 as char).to_string()
+        } else if let Some(text) = self.keyword_text() {
+            String::from(text)
         } else {
             String::from(match *self {
                 ReservedWord::Arrow => "=>",
-                ReservedWord::Const => "const",
-                ReservedWord::Let => "let",
-                ReservedWord::Import => "import",
-                ReservedWord::Export => "export",
-                ReservedWord::Default => "default",
-                ReservedWord::From => "from",
-                ReservedWord::True => "true",
-                ReservedWord::False => "false",
                 ReservedWord::LeftShift => "<<",
                 ReservedWord::RightShift => ">>",
                 ReservedWord::UnsignedRightShift => ">>>",
@@ -95,6 +142,9 @@ impl Display for ReservedWord {
                 ReservedWord::XorAssign => "^=",
                 ReservedWord::OrAssign => "|=",
                 ReservedWord::ExponentialAssign => "**=",
+                ReservedWord::Range => "..",
+                ReservedWord::RangeInclusive => "..=",
+                ReservedWord::DoubleColon => "::",
                 _ => panic!(),
             })
         };
@@ -105,6 +155,17 @@ impl Display for ReservedWord {
 #[cfg(test)]
 mod tests {
     use super::ReservedWord::*;
+    use super::{ReservedWord, KEYWORDS};
+
+    #[test]
+    fn test_keyword() {
+        for (text, word) in KEYWORDS.iter() {
+            assert_eq!(Some(*word), ReservedWord::from_keyword(text));
+            assert_eq!(*text, word.to_string());
+        }
+        assert_eq!(None, ReservedWord::from_keyword("constant"));
+        assert_eq!(None, Arrow.keyword_text());
+    }
 
     #[test]
     fn test_to_string() {
@@ -163,6 +224,19 @@ mod tests {
             True,
             False,
             Colon,
+            If,
+            Else,
+            Match,
+            Underscore,
+            Range,
+            RangeInclusive,
+            For,
+            In,
+            While,
+            Struct,
+            Enum,
+            DoubleColon,
+            As,
         ];
         for item in reserveds {
             match item {
@@ -220,6 +294,19 @@ mod tests {
                 LogicalNot => assert_eq!("!", item.to_string()),
                 LogicalOr => assert_eq!("||", item.to_string()),
                 Colon => assert_eq!(":", item.to_string()),
+                If => assert_eq!("if", item.to_string()),
+                Else => assert_eq!("else", item.to_string()),
+                Match => assert_eq!("match", item.to_string()),
+                Underscore => assert_eq!("_", item.to_string()),
+                Range => assert_eq!("..", item.to_string()),
+                RangeInclusive => assert_eq!("..=", item.to_string()),
+                For => assert_eq!("for", item.to_string()),
+                In => assert_eq!("in", item.to_string()),
+                While => assert_eq!("while", item.to_string()),
+                Struct => assert_eq!("struct", item.to_string()),
+                Enum => assert_eq!("enum", item.to_string()),
+                DoubleColon => assert_eq!("::", item.to_string()),
+                As => assert_eq!("as", item.to_string()),
             }
         }
     }
@@ -0,0 +1,157 @@
+/// Returns the JavaScript string literal of `value` quoted by double quotes.
+/// The characters which cannot appear in a literal as they are, are escaped.
+pub fn quote(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    escape_into(&mut result, value, false);
+    result.push('"');
+    result
+}
+
+/// Escapes `value` to be a part of a template literal, e.g. `${` is escaped as `\${`.
+pub fn escape_template(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    escape_into(&mut result, value, true);
+    result
+}
+
+fn escape_into(result: &mut String, value: &str, is_template: bool) {
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' if !is_template => result.push_str("\\\""),
+            '`' if is_template => result.push_str("\\`"),
+            '$' if is_template && chars.peek() == Some(&'{') => result.push_str("\\$"),
+            '\n' if is_template => result.push('\n'),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\u{8}' => result.push_str("\\b"),
+            '\u{b}' => result.push_str("\\v"),
+            '\u{c}' => result.push_str("\\f"),
+            // `\0` followed by a digit is an octal escape sequence
+            '\0' if !chars.peek().is_some_and(|x| x.is_ascii_digit()) => result.push_str("\\0"),
+            '\u{0}'..='\u{1f}' | '\u{7f}' => result.push_str(&format!("\\x{:02x}", c as u32)),
+            // line terminators in JavaScript
+            '\u{2028}' | '\u{2029}' => result.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => result.push(c),
+        }
+    }
+}
+
+/// Decodes the escape sequence `source` starts with, whose backslash is removed.
+/// Returns the character and the length of the sequence, or the length of the invalid sequence.
+/// Line continuations (a backslash followed by a line break) are handled by the lexer.
+pub fn unescape(source: &str) -> Result<(char, usize), usize> {
+    let c = source.chars().next().ok_or(0_usize)?;
+    let decoded = match c {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'b' => '\u{8}',
+        'v' => '\u{b}',
+        'f' => '\u{c}',
+        '0' if !source[1..].starts_with(|x: char| x.is_ascii_digit()) => '\0',
+        // octal escape sequences are not allowed in strict mode
+        '0'..='9' => return Err(1),
+        'x' => {
+            let value = read_hex(&source[1..], 2).ok_or_else(|| hex_len(&source[1..], 2) + 1)?;
+            return Ok((char::from_u32(value).unwrap(), 3));
+        }
+        'u' => return unescape_unicode(source),
+        _ => c,
+    };
+    Ok((decoded, c.len_utf8()))
+}
+
+// `u{1F600}`, `u00e9`, or a surrogate pair `uD83D\uDE00`
+fn unescape_unicode(source: &str) -> Result<(char, usize), usize> {
+    if let Some(braced) = source[1..].strip_prefix('{') {
+        let len = hex_len(braced, 6);
+        if len == 0 || !braced[len..].starts_with('}') {
+            return Err(len + 2);
+        }
+        let value = u32::from_str_radix(&braced[..len], 16).unwrap();
+        return char::from_u32(value).map(|x| (x, len + 3)).ok_or(len + 3);
+    }
+    let value = read_hex(&source[1..], 4).ok_or_else(|| hex_len(&source[1..], 4) + 1)?;
+    if let Some(c) = char::from_u32(value) {
+        return Ok((c, 5));
+    }
+    let low = source[5..]
+        .strip_prefix("\\u")
+        .and_then(|x| read_hex(x, 4))
+        .filter(|x| (0xdc00..=0xdfff).contains(x));
+    match low {
+        Some(low) if value < 0xdc00 => {
+            let value = 0x10000 + ((value - 0xd800) << 10) + (low - 0xdc00);
+            Ok((char::from_u32(value).unwrap(), 11))
+        }
+        // lone surrogates cannot be represented in UTF-8
+        _ => Err(5),
+    }
+}
+
+fn hex_len(source: &str, max: usize) -> usize {
+    source
+        .bytes()
+        .take(max)
+        .take_while(|x| x.is_ascii_hexdigit())
+        .count()
+}
+
+fn read_hex(source: &str, len: usize) -> Option<u32> {
+    if hex_len(source, len) == len {
+        u32::from_str_radix(&source[..len], 16).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        let cases = [
+            ("abc", r#""abc""#),
+            ("a\"b'c", r#""a\"b'c""#),
+            ("\\n\n\t\r", r#""\\n\n\t\r""#),
+            ("\0\u{0}1\u{1b}", r#""\0\x001\x1b""#),
+            ("é😀\u{2028}", "\"é😀\\u2028\""),
+        ];
+        for (value, expected) in cases.iter() {
+            assert_eq!(*expected, quote(value));
+        }
+        assert_eq!(r"a\`\${b}$c", escape_template("a`${b}$c"));
+        assert_eq!("a\nb\\`", escape_template("a\nb`"));
+    }
+
+    #[test]
+    fn test_unescape() {
+        let cases = [
+            ("n", Ok(('\n', 1))),
+            ("'", Ok(('\'', 1))),
+            ("q", Ok(('q', 1))),
+            ("é", Ok(('é', 2))),
+            ("0", Ok(('\0', 1))),
+            ("01", Err(1)),
+            ("7", Err(1)),
+            ("x41", Ok(('A', 3))),
+            ("x4g", Err(2)),
+            ("u00e9", Ok(('é', 5))),
+            ("u{1F600}!", Ok(('😀', 8))),
+            ("u{}", Err(2)),
+            ("u{110000}", Err(9)),
+            ("uD83D\\uDE00", Ok(('😀', 11))),
+            ("uD83D", Err(5)),
+            ("uDE00\\uD83D", Err(5)),
+            ("", Err(0)),
+        ];
+        for (source, expected) in cases.iter() {
+            assert_eq!(*expected, unescape(source), "{}", source);
+        }
+    }
+}
@@ -2,31 +2,53 @@ use std::{fmt::Display, rc::Rc};
 
 use reserved_word::ReservedWord;
 
-use super::reserved_word;
+use super::{reserved_word, string_literal};
 
 #[derive(Clone, PartialEq)]
 pub enum TokenBase {
+    /// The value of a string literal, whose escape sequences are decoded.
     String(String),
+    /// A part of a template literal split by substitutions and its decoded value.
+    Template(TemplatePart, String),
     Number(String),
     Reserved(ReservedWord),
     Identifier(String),
 }
 
+/// The kinds of the parts of a template literal, e.g. `` `a${x}b${y}c` `` is lexed as
+/// `` `a${ ``(Head), `x`, `}b${`(Middle), `y`, and `` }c` ``(Tail).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemplatePart {
+    /// `` `...` ``
+    NoSubstitution,
+    /// `` `...${ ``
+    Head,
+    /// `}...${`
+    Middle,
+    /// `` }...` ``
+    Tail,
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     token: Option<TokenBase>,
     base_str: String,
     ln: u32,
     col: u32,
+    // the column counted in UTF-16 code units, which is used by the source maps
+    utf16_col: u32,
     ind: u64,
     len: usize,
     file_path: Rc<String>,
+    // the `///` comments just before the token
+    doc_comment: Option<Rc<String>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TokenPosition {
     pub ln: u32,
     pub col: u32,
+    pub utf16_col: u32,
     pub ind: u64,
     pub len: usize,
     pub path: Rc<String>,
@@ -53,9 +75,11 @@ impl Token {
             base_str,
             ln,
             col,
+            utf16_col: col,
             ind,
             len,
             file_path,
+            doc_comment: None,
         }
     }
 
@@ -67,6 +91,7 @@ impl Token {
         TokenPosition {
             ln: self.ln,
             col: self.col,
+            utf16_col: self.utf16_col,
             ind: self.ind,
             len: self.len,
             path: self.file_path.clone(),
@@ -76,6 +101,30 @@ impl Token {
     pub fn get_base_text(&self) -> &str {
         &self.base_str
     }
+
+    /// Sets the length of the token in the source, which differs from the length of the text
+    /// e.g. if a string literal has escape sequences.
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = len;
+        self
+    }
+
+    /// Sets the column counted in UTF-16 code units, which differs from the column counted in
+    /// characters e.g. if the line has an emoji before the token.
+    pub fn with_utf16_col(mut self, utf16_col: u32) -> Self {
+        self.utf16_col = utf16_col;
+        self
+    }
+
+    /// Returns the text of the doc comments (`/// ...`) just before the token, whose lines are
+    /// joined by `\n`.
+    pub fn get_doc_comment(&self) -> Option<&str> {
+        self.doc_comment.as_ref().map(|x| x.as_str())
+    }
+
+    pub fn set_doc_comment(&mut self, doc_comment: String) {
+        self.doc_comment = Some(Rc::new(doc_comment));
+    }
 }
 
 impl Display for Token {
@@ -105,7 +154,16 @@ impl PartialEq for Token {
 impl Display for TokenBase {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TokenBase::String(s) => write!(f, "\"{}\"", s),
+            TokenBase::String(s) => write!(f, "{}", string_literal::quote(s)),
+            TokenBase::Template(part, s) => {
+                let (start, end) = match part {
+                    TemplatePart::NoSubstitution => ("`", "`"),
+                    TemplatePart::Head => ("`", "${"),
+                    TemplatePart::Middle => ("}", "${"),
+                    TemplatePart::Tail => ("}", "`"),
+                };
+                write!(f, "{}{}{}", start, string_literal::escape_template(s), end)
+            }
             TokenBase::Number(n) => write!(f, "{}", n),
             TokenBase::Reserved(r) => write!(f, "{}", r),
             TokenBase::Identifier(i) => write!(f, "{}", i),
@@ -117,6 +175,7 @@ impl std::fmt::Debug for TokenBase {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             TokenBase::String(s) => write!(f, "string({})", s),
+            TokenBase::Template(part, s) => write!(f, "template({:?}, {})", part, s),
             TokenBase::Number(n) => write!(f, "number({})", n),
             TokenBase::Reserved(r) => write!(f, "{:?}", r),
             TokenBase::Identifier(i) => write!(f, "identifier({})", i),
@@ -1,3 +1,21 @@
+// The lints below are tripped by the code and the tests which predate the clippy gate.
+#![allow(
+    clippy::crate_in_macro_def,
+    clippy::derivable_impls,
+    clippy::manual_repeat_n,
+    clippy::needless_borrow,
+    clippy::to_string_in_format_args,
+    clippy::unnecessary_unwrap
+)]
+#![cfg_attr(
+    test,
+    allow(
+        clippy::bool_assert_comparison,
+        clippy::manual_pattern_char_comparison,
+        clippy::useless_vec
+    )
+)]
+
 use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
@@ -11,9 +29,16 @@ macro_rules! log {
 }
 
 pub mod builder;
+pub mod compile;
+pub mod declaration_builder;
+pub mod diagnostic;
 pub mod lexer;
+pub mod manifest;
 pub mod parser;
 pub mod semantic_analyzer;
+pub mod source_map;
+#[cfg(test)]
+mod test_support;
 
 pub struct Logger {
     pub logger: Option<Box<dyn Fn(String) + Sync + Send>>,
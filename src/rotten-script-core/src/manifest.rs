@@ -0,0 +1,342 @@
+use std::fmt::Display;
+
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+
+use crate::{semantic_analyzer::BuildOptions, source_map::SourceMapKind};
+
+/// The name of the manifest file placed at the project root.
+pub const MANIFEST_FILE_NAME: &str = "rotten.toml";
+
+/// The runtime which runs the generated JavaScript.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    #[default]
+    Node,
+    Web,
+    Deno,
+}
+
+/// The module system of the generated JavaScript.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub enum ModuleFormat {
+    /// `import` / `export`
+    #[default]
+    #[serde(rename = "esm")]
+    Esm,
+    /// `require` / `exports`
+    #[serde(rename = "commonjs")]
+    CommonJs,
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Node => write!(f, "node"),
+            Target::Web => write!(f, "web"),
+            Target::Deno => write!(f, "deno"),
+        }
+    }
+}
+
+impl Display for ModuleFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleFormat::Esm => write!(f, "esm"),
+            ModuleFormat::CommonJs => write!(f, "commonjs"),
+        }
+    }
+}
+
+/// The settings of a project declared by `rotten.toml`, e.g.
+///
+/// ```toml
+/// [project]
+/// name = "sample"
+///
+/// [build]
+/// source-roots = ["src"]
+/// include = ["**/*.rots"]
+/// exclude = ["**/*.test.rots"]
+/// out-dir = "dist"
+/// target = "node"
+/// module = "esm"
+/// source-map = "file"
+/// ```
+///
+/// The paths and the globs are relative to the project root.
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub name: String,
+    pub source_roots: Vec<String>,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    pub out_dir: String,
+    pub target: Target,
+    pub module_format: ModuleFormat,
+    /// How `rotc build` and `rotc watch` provide the source maps: `file`, `inline` or `none`
+    pub source_map: SourceMapKind,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    project: RawProject,
+    #[serde(default)]
+    build: RawBuild,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawProject {
+    name: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct RawBuild {
+    source_roots: Option<Vec<String>>,
+    include: Option<Vec<String>>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    out_dir: Option<String>,
+    #[serde(default)]
+    target: Target,
+    module: Option<ModuleFormat>,
+    source_map: Option<SourceMapKind>,
+}
+
+impl Manifest {
+    /// Returns the settings used when the project has no manifest.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            source_roots: vec![".".to_string()],
+            include: vec![Pattern::new("**/*.rots").unwrap()],
+            exclude: Vec::new(),
+            out_dir: "dist".to_string(),
+            target: Target::default(),
+            module_format: ModuleFormat::default(),
+            source_map: SourceMapKind::File,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ManifestError> {
+        let raw = toml::from_str::<RawManifest>(text).map_err(ManifestError::InvalidToml)?;
+        let build = raw.build;
+        let module_format = build.module.unwrap_or_default();
+        if build.target != Target::Node && module_format == ModuleFormat::CommonJs {
+            return Err(ManifestError::UnsupportedModuleFormat {
+                target: build.target,
+                module_format,
+            });
+        }
+
+        let mut manifest = Self::new(raw.project.name);
+        if let Some(source_roots) = build.source_roots {
+            manifest.source_roots = source_roots
+                .iter()
+                .map(|x| normalize_path(x).to_string())
+                .collect();
+        }
+        if let Some(include) = build.include {
+            manifest.include = compile_patterns(&include)?;
+        }
+        manifest.exclude = compile_patterns(&build.exclude)?;
+        if let Some(out_dir) = build.out_dir {
+            manifest.out_dir = out_dir;
+        }
+        manifest.target = build.target;
+        manifest.module_format = module_format;
+        if let Some(source_map) = build.source_map {
+            manifest.source_map = source_map;
+        }
+        Ok(manifest)
+    }
+
+    /// Returns true if the file (relative to the project root) is compiled, i.e. it is a `.rots` file
+    /// in one of the source roots which matches an `include` glob and does not match any `exclude` glob.
+    pub fn is_source_file(&self, relative_path: &str) -> bool {
+        let path = normalize_path(relative_path);
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        path.ends_with(".rots")
+            && self.source_roots.iter().any(|root| {
+                root == "."
+                    || path
+                        .strip_prefix(root.as_str())
+                        .is_some_and(|x| x.starts_with('/'))
+            })
+            && self.include.iter().any(|x| x.matches_with(path, options))
+            && !self.exclude.iter().any(|x| x.matches_with(path, options))
+    }
+
+    /// Returns the path relative to the source root which contains the file,
+    /// i.e. the path of the outputs in the output directory.
+    pub fn strip_source_root<'b>(&self, relative_path: &'b str) -> &'b str {
+        let path = normalize_path(relative_path);
+        self.source_roots
+            .iter()
+            .filter(|x| x.as_str() != ".")
+            .find_map(|root| path.strip_prefix(root.as_str())?.strip_prefix('/'))
+            .unwrap_or(path)
+    }
+
+    /// Returns the build options which reflect the module format.
+    /// The target does not change the generated code but `package.json` written by `rotc build`.
+    pub fn build_options(&self) -> BuildOptions {
+        BuildOptions {
+            module_format: self.module_format,
+            ..BuildOptions::default()
+        }
+    }
+}
+
+/// `./src/` -> `src`
+fn normalize_path(path: &str) -> &str {
+    let mut path = path.trim_end_matches('/');
+    while let Some(stripped) = path.strip_prefix("./") {
+        path = stripped;
+    }
+    if path.is_empty() {
+        "."
+    } else {
+        path
+    }
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>, ManifestError> {
+    patterns
+        .iter()
+        .map(|x| {
+            Pattern::new(normalize_path(x)).map_err(|e| ManifestError::InvalidPattern {
+                pattern: x.clone(),
+                message: e.msg,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    InvalidToml(toml::de::Error),
+    InvalidPattern {
+        pattern: String,
+        message: &'static str,
+    },
+    // e.g. `require` is not available in browsers
+    UnsupportedModuleFormat {
+        target: Target,
+        module_format: ModuleFormat,
+    },
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::InvalidToml(e) => write!(f, "invalid manifest: {}", e),
+            ManifestError::InvalidPattern { pattern, message } => {
+                write!(f, "invalid glob `{}` in manifest: {}", pattern, message)
+            }
+            ManifestError::UnsupportedModuleFormat {
+                target,
+                module_format,
+            } => write!(
+                f,
+                "module format `{}` is not supported by target `{}`",
+                module_format, target
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::source_map::SourceMapKind;
+
+    use super::{Manifest, ManifestError, ModuleFormat, Target};
+
+    #[test]
+    fn parse_manifest_test() {
+        let manifest = Manifest::parse(
+            r#"[project]
+name = "sample"
+
+[build]
+source-roots = ["./src/", "lib"]
+exclude = ["**/*.test.rots", "src/generated/**"]
+out-dir = "out"
+target = "node"
+module = "commonjs"
+source-map = "inline"
+"#,
+        )
+        .unwrap();
+        assert_eq!("sample", manifest.name);
+        assert_eq!(vec!["src", "lib"], manifest.source_roots);
+        assert_eq!("out", manifest.out_dir);
+        assert_eq!(Target::Node, manifest.target);
+        assert_eq!(ModuleFormat::CommonJs, manifest.module_format);
+        assert_eq!(SourceMapKind::Inline, manifest.source_map);
+
+        assert!(manifest.is_source_file("src/main.rots"));
+        assert!(manifest.is_source_file("./lib/util/math.rots"));
+        assert!(!manifest.is_source_file("main.rots"));
+        assert!(!manifest.is_source_file("srcs/main.rots"));
+        assert!(!manifest.is_source_file("src/main.test.rots"));
+        assert!(!manifest.is_source_file("src/generated/a.rots"));
+        assert!(!manifest.is_source_file("src/main.js"));
+        assert_eq!(
+            "util/math.rots",
+            manifest.strip_source_root("./lib/util/math.rots")
+        );
+        assert_eq!("main.rots", manifest.strip_source_root("main.rots"));
+    }
+
+    #[test]
+    fn parse_default_manifest_test() {
+        let manifest = Manifest::parse("[project]\nname = \"sample\"").unwrap();
+        assert_eq!(vec!["."], manifest.source_roots);
+        assert_eq!("dist", manifest.out_dir);
+        assert_eq!(Target::Node, manifest.target);
+        assert_eq!(ModuleFormat::Esm, manifest.module_format);
+        assert_eq!(SourceMapKind::File, manifest.source_map);
+        assert!(manifest.is_source_file("main.rots"));
+        assert!(manifest.is_source_file("./a/b/c.rots"));
+    }
+
+    #[test]
+    fn parse_include_test() {
+        let manifest =
+            Manifest::parse("[project]\nname = \"a\"\n[build]\ninclude = [\"*.rots\"]").unwrap();
+        assert!(manifest.is_source_file("main.rots"));
+        assert!(!manifest.is_source_file("sub/main.rots"));
+    }
+
+    #[test]
+    fn parse_invalid_manifest_test() {
+        let error = |text: &str| Manifest::parse(text).unwrap_err();
+        assert!(matches!(
+            error("[build]\ntarget = \"node\""),
+            ManifestError::InvalidToml(_)
+        ));
+        assert!(matches!(
+            error("[project]\nname = \"a\"\n[build]\ntarget = \"bun\""),
+            ManifestError::InvalidToml(_)
+        ));
+        assert!(matches!(
+            error("[project]\nname = \"a\"\n[build]\nexclude = [\"a/***\"]"),
+            ManifestError::InvalidPattern { .. }
+        ));
+        assert_eq!(
+            "module format `commonjs` is not supported by target `web`",
+            error("[project]\nname = \"a\"\n[build]\ntarget = \"web\"\nmodule = \"commonjs\"")
+                .to_string()
+        );
+    }
+}
@@ -258,22 +258,61 @@ macro_rules! TBR {
             crate::lexer::reserved_word::ReservedWord::ExponentialAssign,
         )
     };
+    ("if") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::If)
+    };
+    ("else") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::Else)
+    };
+    ("match") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::Match)
+    };
+    ("_") => {
+        crate::lexer::token::TokenBase::Reserved(
+            crate::lexer::reserved_word::ReservedWord::Underscore,
+        )
+    };
+    ("..") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::Range)
+    };
+    ("..=") => {
+        crate::lexer::token::TokenBase::Reserved(
+            crate::lexer::reserved_word::ReservedWord::RangeInclusive,
+        )
+    };
+    ("for") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::For)
+    };
+    ("in") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::In)
+    };
+    ("while") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::While)
+    };
+    ("struct") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::Struct)
+    };
+    ("enum") => {
+        crate::lexer::token::TokenBase::Reserved(crate::lexer::reserved_word::ReservedWord::Enum)
+    };
+    ("::") => {
+        crate::lexer::token::TokenBase::Reserved(
+            crate::lexer::reserved_word::ReservedWord::DoubleColon,
+        )
+    };
 }
 
 trait InvalidSyntaxResultHandler {
-    fn handle_scan(self, perror: &mut ParseError2);
     fn handle_consume(self, parser: &mut Parser);
 }
 
 impl InvalidSyntaxResultHandler for Result<(), InvalidSyntax> {
-    fn handle_scan(self, perror: &mut ParseError2) {
-        self.unwrap_or_else(|e| perror.add_error(e));
-    }
-
     fn handle_consume(self, parser: &mut Parser) {
         self.unwrap_or_else(|e| {
-            parser.parse_error.add_error(e);
-            parser.tokens.next();
+            parser.report_error(e);
+            if !parser.is_at_sync_token() {
+                parser.tokens.next();
+            }
         });
     }
 }
@@ -281,10 +320,29 @@ impl InvalidSyntaxResultHandler for Result<(), InvalidSyntax> {
 mod expression_parser;
 mod import_parser;
 
+// the tokens which start a top-level declaration
+const DECLARATION_STARTS: [ReservedWord; 6] = [
+    ReservedWord::Const,
+    ReservedWord::Export,
+    ReservedWord::LeftSquareBracket,
+    ReservedWord::Struct,
+    ReservedWord::Enum,
+    ReservedWord::Import,
+];
+
 pub struct Parser<'a> {
     pub tokens: &'a mut TokenStack<'a>,
     pub ast: Ast,
     parse_error: ParseError2,
+    // true while parsing e.g. the condition of `if` (`if a { ... }` is not a struct literal)
+    no_struct_literal: bool,
+    // true after a syntax error until the parser synchronizes at the end of the statement or
+    // the declaration; the errors in the meantime are not reported since they are caused by the first one
+    panic_mode: bool,
+    // true if a syntax error is found in the current top-level declaration
+    has_error_in_declaration: bool,
+    // the index of the token before the current top-level declaration
+    declaration_start: i32,
 }
 
 impl<'a> Parser<'a> {
@@ -293,11 +351,17 @@ impl<'a> Parser<'a> {
             tokens,
             ast: Ast::new_node_with_leaves(NonTerminal::TranslationUnit, Vec::new()),
             parse_error: ParseError2::new(),
+            no_struct_literal: false,
+            panic_mode: false,
+            has_error_in_declaration: false,
+            declaration_start: -1,
         }
     }
 
     pub fn parse(&mut self) -> Result<(), &ParseError2> {
-        self.parse_translation_unit().unwrap();
+        if self.parse_translation_unit().is_err() {
+            self.handle_unexpected_eof_error(None);
+        }
         if self.parse_error.has_error() {
             Err(&self.parse_error)
         } else {
@@ -305,36 +369,68 @@ impl<'a> Parser<'a> {
         }
     }
 
-    // TranslationUnit = { ImportDeclaration } , { { Attribute } , ExportableConstDeclaration };
+    // TranslationUnit = { ImportDeclaration | ReExportDeclaration | ReExportAllDeclaration } ,
+    //     { ( { Attribute } , ExportableConstDeclaration ) | ExportableTypeDeclaration | ReExportDeclaration | ReExportAllDeclaration };
     fn parse_translation_unit(&mut self) -> Result<(), ParseError> {
-        while let Some(TokenBase::Reserved(ReservedWord::Import)) = self.tokens.look_ahead(1) {
-            let import = self.parse_import_declaration()?;
-            self.ast.add_child(import);
+        loop {
+            let start = self.tokens.ind();
+            self.declaration_start = start;
+            let mut child = if self.is_re_export_declaration() {
+                self.parse_re_export_declaration()?
+            } else if let Some(TokenBase::Reserved(ReservedWord::Import)) =
+                self.tokens.look_ahead(1)
+            {
+                self.parse_import_declaration()?
+            } else {
+                break;
+            };
+            self.recover_declaration(&mut child, start);
+            self.ast.add_child(child);
         }
         while self.tokens.has_next() {
-            let c = match self.tokens.look_ahead(1) {
+            let mut start = self.tokens.ind();
+            self.declaration_start = start;
+            let mut c = match self.tokens.look_ahead(1) {
+                Some(TokenBase::Reserved(ReservedWord::Export))
+                    if self.is_re_export_declaration() =>
+                {
+                    self.parse_re_export_declaration()?
+                }
                 Some(TokenBase::Reserved(ReservedWord::LeftSquareBracket)) => {
+                    // the doc comments may be written before and after the attributes
+                    let mut doc_comments = Vec::new();
                     while Some(TokenBase::Reserved(ReservedWord::LeftSquareBracket))
                         == self.tokens.look_ahead(1)
                     {
-                        let child = self.parse_attribute()?;
+                        doc_comments.extend(self.next_doc_comment());
+                        let mut child = self.parse_attribute()?;
+                        self.recover_declaration(&mut child, start);
                         self.ast.add_child(child);
+                        start = self.tokens.ind();
+                        self.declaration_start = start;
                     }
                     if Some(TokenBase::Reserved(ReservedWord::Const)) == self.tokens.look_ahead(1) {
-                        self.parse_exportable_const_declaration()?
+                        let mut declaration = self.parse_exportable_declaration()?;
+                        doc_comments.extend(declaration.doc_comment.take());
+                        if !doc_comments.is_empty() {
+                            declaration.doc_comment = Some(doc_comments.join("\n"));
+                        }
+                        declaration
                     } else {
                         let target_token = self.tokens.nth(1);
                         self.handle_expected_actually_error(
                             target_token.clone(),
                             vec![TokenBase::Reserved(ReservedWord::Const)],
-                            self.tokens.peek_token().unwrap(),
+                            self.tokens.peek_token(),
                         );
-                        Ast::new_leaf(target_token.unwrap())
+                        self.new_invalid_leaf(target_token)
                     }
                 }
                 Some(TokenBase::Reserved(ReservedWord::Const))
-                | Some(TokenBase::Reserved(ReservedWord::Export)) => {
-                    self.parse_exportable_const_declaration()?
+                | Some(TokenBase::Reserved(ReservedWord::Export))
+                | Some(TokenBase::Reserved(ReservedWord::Struct))
+                | Some(TokenBase::Reserved(ReservedWord::Enum)) => {
+                    self.parse_exportable_declaration()?
                 }
                 Some(_) | None => {
                     let target = self.tokens.nth(1);
@@ -344,12 +440,19 @@ impl<'a> Parser<'a> {
                             TokenBase::Reserved(ReservedWord::LeftSquareBracket),
                             TokenBase::Reserved(ReservedWord::Const),
                             TokenBase::Reserved(ReservedWord::Export),
+                            TokenBase::Reserved(ReservedWord::Struct),
+                            TokenBase::Reserved(ReservedWord::Enum),
                         ],
-                        self.tokens.peek_token().unwrap(),
+                        self.tokens.peek_token(),
                     );
-                    Ast::new_leaf(target.unwrap())
+                    // `import` after the declarations is not skipped by the recovery
+                    if self.tokens.ind() == start {
+                        self.tokens.next();
+                    }
+                    self.new_invalid_leaf(target)
                 }
             };
+            self.recover_declaration(&mut c, start);
             self.ast.add_child(c);
         }
 
@@ -372,9 +475,9 @@ impl<'a> Parser<'a> {
                 self.handle_expected_actually_error(
                     target_token.clone(),
                     vec![TokenBase::default_identifier()],
-                    self.tokens.peek_token().unwrap(),
+                    self.tokens.peek_token(),
                 );
-                Ast::new_leaf(target_token.unwrap())
+                self.new_invalid_leaf(target_token)
             }
         };
 
@@ -382,56 +485,240 @@ impl<'a> Parser<'a> {
     }
 
     // ExportableConstDeclaration = [ "export" , ["default"] ] , ConstDeclaration;
-    fn parse_exportable_const_declaration(&mut self) -> Result<Ast, ParseError> {
+    // ExportableTypeDeclaration = [ "export" , ["default"] ] , ( StructDeclaration | EnumDeclaration );
+    fn parse_exportable_declaration(&mut self) -> Result<Ast, ParseError> {
+        let doc_comment = self.next_doc_comment();
         let mut ast = Vec::new();
 
-        match self.tokens.look_ahead(1) {
-            Some(TokenBase::Reserved(ReservedWord::Export)) => {
+        if let Some(TokenBase::Reserved(ReservedWord::Export)) = self.tokens.look_ahead(1) {
+            ast.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+            if let Some(TokenBase::Reserved(ReservedWord::Default)) = self.tokens.look_ahead(1) {
                 ast.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
-                match self.tokens.look_ahead(1) {
-                    Some(TokenBase::Reserved(ReservedWord::Default)) => {
-                        ast.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
-                        self.tokens
-                            .scan_reserved(ReservedWord::Const)
-                            .handle_scan(&mut self.parse_error);
-                        ast.push(self.parse_const_declaration()?);
-                    }
-                    Some(TokenBase::Reserved(ReservedWord::Const)) => {
-                        ast.push(self.parse_const_declaration()?);
-                    }
-                    Some(_) | None => {
-                        self.handle_expected_actually_error(
-                            self.tokens.nth(1),
-                            vec![
-                                TokenBase::Reserved(ReservedWord::Default),
-                                TokenBase::Reserved(ReservedWord::Const),
-                            ],
-                            self.tokens.peek_token().unwrap(),
-                        );
-                    }
-                }
             }
+        }
+        let mut node_type = NonTerminal::ExportableConstDeclaration;
+        match self.tokens.look_ahead(1) {
             Some(TokenBase::Reserved(ReservedWord::Const)) => {
                 ast.push(self.parse_const_declaration()?);
             }
+            Some(TokenBase::Reserved(ReservedWord::Struct)) => {
+                ast.push(self.parse_struct_declaration()?);
+                node_type = NonTerminal::ExportableTypeDeclaration;
+            }
+            Some(TokenBase::Reserved(ReservedWord::Enum)) => {
+                ast.push(self.parse_enum_declaration()?);
+                node_type = NonTerminal::ExportableTypeDeclaration;
+            }
             Some(_) | None => {
                 let target_token = self.tokens.nth(1);
+                let mut expected = vec![
+                    TokenBase::Reserved(ReservedWord::Const),
+                    TokenBase::Reserved(ReservedWord::Struct),
+                    TokenBase::Reserved(ReservedWord::Enum),
+                ];
+                match ast.len() {
+                    0 => expected.push(TokenBase::Reserved(ReservedWord::Export)),
+                    1 => expected.insert(0, TokenBase::Reserved(ReservedWord::Default)),
+                    _ => {}
+                }
                 self.handle_expected_actually_error(
                     target_token,
-                    vec![
-                        TokenBase::Reserved(ReservedWord::Const),
-                        TokenBase::Reserved(ReservedWord::Export),
-                    ],
-                    self.tokens.peek_token().unwrap(),
+                    expected,
+                    self.tokens.peek_token(),
                 );
             }
         }
+        let mut declaration = Ast::new_node_with_leaves(node_type, ast);
+        declaration.doc_comment = doc_comment;
+        Ok(declaration)
+    }
+
+    /// Returns the doc comments attached to the next token.
+    fn next_doc_comment(&self) -> Option<String> {
+        let token = self.tokens.nth(1)?;
+        token.get_doc_comment().map(|x| x.to_string())
+    }
+
+    // StructDeclaration = "struct" , Identifier , "{" , [ FieldDeclaration , { "," , FieldDeclaration } , [ "," ] ] , "}";
+    fn parse_struct_declaration(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let mut asts = vec![self.parse_type_name()];
+        asts.append(&mut self.parse_field_declarations()?);
+
         Ok(Ast::new_node_with_leaves(
-            NonTerminal::ExportableConstDeclaration,
-            ast,
+            NonTerminal::StructDeclaration,
+            asts,
         ))
     }
 
+    // EnumDeclaration = "enum" , Identifier , "{" , [ EnumVariant , { "," , EnumVariant } , [ "," ] ] , "}";
+    fn parse_enum_declaration(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let mut asts = vec![self.parse_type_name()];
+        self.tokens
+            .consume_reserved(ReservedWord::LeftCurly)
+            .handle_consume(self);
+
+        loop {
+            match self.tokens.look_ahead(1) {
+                Some(TBR!("}")) => {
+                    self.tokens.next();
+                    break;
+                }
+                Some(TokenBase::Identifier(_)) => {
+                    asts.push(self.parse_enum_variant()?);
+                    match self.tokens.look_ahead(1) {
+                        Some(TBR!(",")) => {
+                            self.tokens.next();
+                        }
+                        Some(TBR!("}")) => {
+                            self.tokens.next();
+                            break;
+                        }
+                        Some(_) | None => {
+                            self.handle_expected_actually_error(
+                                self.tokens.nth(1),
+                                vec![TBR!(","), TBR!("}")],
+                                self.tokens.peek_token(),
+                            );
+                            break;
+                        }
+                    }
+                }
+                Some(_) | None => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TokenBase::default_identifier(), TBR!("}")],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+        }
+
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::EnumDeclaration,
+            asts,
+        ))
+    }
+
+    // EnumVariant = Identifier , [ TupleFields | StructFields ];
+    // TupleFields = "(" , [ Identifier , { "," , Identifier } , [ "," ] ] , ")";
+    // StructFields = "{" , [ FieldDeclaration , { "," , FieldDeclaration } , [ "," ] ] , "}";
+    fn parse_enum_variant(&mut self) -> Result<Ast, ParseError> {
+        let mut asts = vec![Ast::new_leaf(self.tokens.next_token().unwrap())];
+        match self.tokens.look_ahead(1) {
+            Some(TBR!("(")) => {
+                self.tokens.next();
+                let mut types = Vec::new();
+                loop {
+                    match self.tokens.look_ahead(1) {
+                        Some(TBR!(")")) => {
+                            self.tokens.next();
+                            break;
+                        }
+                        Some(TokenBase::Identifier(_)) => {
+                            types.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+                            match self.tokens.look_ahead(1) {
+                                Some(TBR!(",")) => {
+                                    self.tokens.next();
+                                }
+                                Some(TBR!(")")) => {
+                                    self.tokens.next();
+                                    break;
+                                }
+                                Some(_) | None => {
+                                    self.handle_expected_actually_error(
+                                        self.tokens.nth(1),
+                                        vec![TBR!(","), TBR!(")")],
+                                        self.tokens.peek_token(),
+                                    );
+                                    break;
+                                }
+                            }
+                        }
+                        Some(_) | None => {
+                            self.handle_expected_actually_error(
+                                self.tokens.nth(1),
+                                vec![TokenBase::default_identifier(), TBR!(")")],
+                                self.tokens.peek_token(),
+                            );
+                            break;
+                        }
+                    }
+                }
+                asts.push(Ast::new_node_with_leaves(NonTerminal::TupleFields, types));
+            }
+            Some(TBR!("{")) => {
+                let fields = self.parse_field_declarations()?;
+                asts.push(Ast::new_node_with_leaves(NonTerminal::StructFields, fields));
+            }
+            _ => {}
+        }
+        Ok(Ast::new_node_with_leaves(NonTerminal::EnumVariant, asts))
+    }
+
+    fn parse_type_name(&mut self) -> Ast {
+        match self.tokens.look_ahead(1) {
+            Some(TokenBase::Identifier(_)) => Ast::new_leaf(self.tokens.next_token().unwrap()),
+            _ => {
+                let target_token = self.tokens.nth(1);
+                self.handle_expected_actually_error(
+                    target_token.clone(),
+                    vec![TokenBase::default_identifier()],
+                    self.tokens.peek_token(),
+                );
+                self.new_invalid_leaf(target_token)
+            }
+        }
+    }
+
+    // "{" , [ FieldDeclaration , { "," , FieldDeclaration } , [ "," ] ] , "}"
+    fn parse_field_declarations(&mut self) -> Result<Vec<Ast>, ParseError> {
+        self.tokens
+            .consume_reserved(ReservedWord::LeftCurly)
+            .handle_consume(self);
+
+        let mut asts = Vec::new();
+        loop {
+            match self.tokens.look_ahead(1) {
+                Some(TBR!("}")) => {
+                    self.tokens.next();
+                    break;
+                }
+                Some(TokenBase::Identifier(_)) => {
+                    asts.push(self.parse_annotated_identifier(NonTerminal::FieldDeclaration)?);
+                    match self.tokens.look_ahead(1) {
+                        Some(TBR!(",")) => {
+                            self.tokens.next();
+                        }
+                        Some(TBR!("}")) => {
+                            self.tokens.next();
+                            break;
+                        }
+                        Some(_) | None => {
+                            self.handle_expected_actually_error(
+                                self.tokens.nth(1),
+                                vec![TBR!(","), TBR!("}")],
+                                self.tokens.peek_token(),
+                            );
+                            break;
+                        }
+                    }
+                }
+                Some(_) | None => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TokenBase::default_identifier(), TBR!("}")],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(asts)
+    }
+
     fn parse_const_declaration(&mut self) -> Result<Ast, ParseError> {
         self.tokens.next();
         Ok(Ast::new_node_with_leaves(
@@ -458,9 +745,9 @@ impl<'a> Parser<'a> {
             self.handle_expected_actually_error(
                 target_token.clone(),
                 vec![TokenBase::default_identifier()],
-                self.tokens.peek_token().unwrap(),
+                self.tokens.peek_token(),
             );
-            Ast::new_leaf(target_token.unwrap())
+            self.new_invalid_leaf(target_token)
         };
 
         asts.push(ident_ast);
@@ -475,7 +762,7 @@ impl<'a> Parser<'a> {
                 _ => self.handle_expected_actually_error(
                     target_token,
                     vec![TokenBase::default_identifier()],
-                    self.tokens.peek_token().unwrap(),
+                    self.tokens.peek_token(),
                 ),
             }
         }
@@ -493,7 +780,7 @@ impl<'a> Parser<'a> {
             self.handle_expected_actually_error(
                 self.tokens.nth(1),
                 vec![TokenBase::Reserved(ReservedWord::SemiColon)],
-                self.tokens.peek_token().unwrap(),
+                self.tokens.peek_token(),
             );
         }
 
@@ -504,6 +791,13 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_args(&mut self) -> Result<Ast, ParseError> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let result = self.parse_args_inner();
+        self.no_struct_literal = no_struct_literal;
+        result
+    }
+
+    fn parse_args_inner(&mut self) -> Result<Ast, ParseError> {
         self.tokens
             .consume_reserved(ReservedWord::LeftParenthesis)
             .handle_consume(self);
@@ -516,8 +810,18 @@ impl<'a> Parser<'a> {
             loop {
                 match self.tokens.look_ahead(1) {
                     Some(TokenBase::String(_))
+                    | Some(TokenBase::Template(..))
                     | Some(TokenBase::Number(_))
                     | Some(TokenBase::Identifier(_))
+                    | Some(TBR!("true"))
+                    | Some(TBR!("false"))
+                    | Some(TBR!("!"))
+                    | Some(TBR!("~"))
+                    | Some(TBR!("+"))
+                    | Some(TBR!("-"))
+                    | Some(TBR!("{"))
+                    | Some(TBR!("if"))
+                    | Some(TBR!("match"))
                     | Some(TokenBase::Reserved(ReservedWord::LeftParenthesis)) => {
                         callers.push(self.parse_expression()?);
                         match self.tokens.look_ahead(1) {
@@ -535,7 +839,7 @@ impl<'a> Parser<'a> {
                                         TokenBase::Reserved(ReservedWord::Comma),
                                         TokenBase::Reserved(ReservedWord::RightParenthesis),
                                     ],
-                                    self.tokens.peek_token().unwrap(),
+                                    self.tokens.peek_token(),
                                 );
                             }
                         }
@@ -547,7 +851,7 @@ impl<'a> Parser<'a> {
                                 TokenBase::Reserved(ReservedWord::Comma),
                                 TokenBase::Reserved(ReservedWord::RightParenthesis),
                             ],
-                            self.tokens.peek_token().unwrap(),
+                            self.tokens.peek_token(),
                         );
                         break;
                     }
@@ -558,10 +862,26 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_assignment_expression(&mut self) -> Result<Ast, ParseError> {
-        let mut astc = vec![Ast::new_leaf(self.tokens.next_token().unwrap())];
-        while self.tokens.look_ahead(1) == Some(TBR!(".")) {
+        // the target is a member path, e.g. `a.b` but not `1` or `a.0`
+        let mut astc = Vec::new();
+        loop {
+            match self.tokens.look_ahead(1) {
+                Some(TokenBase::Identifier(_)) => {
+                    astc.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+                }
+                _ => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TokenBase::default_identifier()],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+            if self.tokens.look_ahead(1) != Some(TBR!(".")) {
+                break;
+            }
             self.tokens.next();
-            astc.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
         }
         match self.tokens.look_ahead(1) {
             Some(TBR!("=")) | Some(TBR!("*=")) | Some(TBR!("/=")) | Some(TBR!("%="))
@@ -586,7 +906,7 @@ impl<'a> Parser<'a> {
                         TBR!("|="),
                         TBR!("**="),
                     ],
-                    self.tokens.peek_token().unwrap(),
+                    self.tokens.peek_token(),
                 );
             }
         }
@@ -604,15 +924,18 @@ impl<'a> Parser<'a> {
         &mut self,
         target_token: Option<Token>,
         expected_tokens: Vec<TokenBase>,
-        before_token: Token,
+        before_token: Option<Token>,
     ) {
         match target_token {
             Some(tk) => {
-                self.parse_error.add_error(InvalidSyntax::new(
+                self.report_error(InvalidSyntax::new(
                     tk.get_token_position(),
                     InvalidSyntaxType::ExpectedNext(ExpectedActuallyTokenPair(expected_tokens, tk)),
                 ));
-                self.tokens.next();
+                // `;`, `}` and the start of the next declaration are left for the synchronization
+                if !self.is_at_sync_token() {
+                    self.tokens.next();
+                }
             }
             None => {
                 self.handle_unexpected_eof_error(before_token);
@@ -620,24 +943,303 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn handle_unexpected_eof_error(&mut self, before_token: Token) {
-        self.parse_error.add_error(InvalidSyntax::new(
-            before_token.get_token_position(),
-            InvalidSyntaxType::UnexpectedEof,
-        ));
+    // reports eof at `before_token`, or at the last token of the file if nothing has been consumed yet
+    fn handle_unexpected_eof_error(&mut self, before_token: Option<Token>) {
+        if let Some(tk) = before_token.or_else(|| self.tokens.last_token()) {
+            self.report_error(InvalidSyntax::new(
+                tk.get_token_position(),
+                InvalidSyntaxType::UnexpectedEof,
+            ));
+        }
+    }
+
+    fn report_error(&mut self, invalid_syntax: InvalidSyntax) {
+        if !self.panic_mode {
+            self.parse_error.add_error(invalid_syntax);
+        }
+        self.panic_mode = true;
+        self.has_error_in_declaration = true;
+    }
+
+    fn is_at_sync_token(&self) -> bool {
+        match self.tokens.look_ahead(1) {
+            Some(TBR!(";")) | Some(TBR!("}")) => true,
+            // the next top-level declaration is kept, e.g. after `const a = 1` without `;`
+            Some(TokenBase::Reserved(r)) if DECLARATION_STARTS.contains(&r) => {
+                self.tokens.count_open_blocks(self.declaration_start) == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the placeholder of the token which caused a syntax error.
+    /// The last token of the file is used at EOF.
+    fn new_invalid_leaf(&self, token: Option<Token>) -> Ast {
+        let token = token
+            .or_else(|| self.tokens.last_token())
+            .expect("a syntax error is found after reading a token");
+        let mut leaf = Ast::new_leaf(token);
+        leaf.set_invalid();
+        leaf
+    }
+
+    /// Marks `ast` as invalid if it has a syntax error and skips the rest of the top-level
+    /// declaration started after the token at `start` if the parser has not synchronized yet.
+    fn recover_declaration(&mut self, ast: &mut Ast, start: i32) {
+        if self.has_error_in_declaration {
+            ast.set_invalid();
+            self.has_error_in_declaration = false;
+        }
+        if !self.panic_mode {
+            return;
+        }
+        // the blocks opened in the declaration (e.g. `const a = match b { 1 2 => 3 };`)
+        // are skipped to their ends
+        let depth = self.tokens.count_open_blocks(start);
+        // EOF is not an error between declarations
+        let _ = self.tokens.skip_reserved_until(&DECLARATION_STARTS, depth);
+        self.panic_mode = false;
+    }
+
+    /// Skips the rest of the statement started after the token at `start` after a syntax error
+    /// and marks `ast` as invalid. The statement ends at `;` (consumed) or `}` (not consumed).
+    fn recover_statement(&mut self, ast: Option<&mut Ast>, start: i32) {
+        if !self.panic_mode {
+            return;
+        }
+        if let Some(ast) = ast {
+            ast.set_invalid();
+        }
+        // the blocks opened in the statement (e.g. `{` of `match a { 1 2 => 3 }`)
+        // must be skipped to their ends to find the end of the statement
+        let depth = self.tokens.count_open_blocks(start);
+        let is_synchronized = depth == 0
+            && self.tokens.ind() > start
+            && matches!(self.tokens.peek(), Some(TBR!(";")) | Some(TBR!("}")));
+        if !is_synchronized {
+            let _ = self
+                .tokens
+                .skip_reserved_until(&[ReservedWord::SemiColon, ReservedWord::RightCurly], depth);
+            if self.tokens.look_ahead(1) == Some(TBR!(";")) {
+                self.tokens.next();
+            }
+        }
+        self.panic_mode = false;
     }
 
+    // the parser can continue after `-1 ** 2` without synchronization
     fn handle_exponential_error(&mut self, current: Token) {
-        self.parse_error.add_error(InvalidSyntax::new(
-            current.get_token_position(),
-            InvalidSyntaxType::ExponentialError(),
-        ));
+        if !self.panic_mode {
+            self.parse_error.add_error(InvalidSyntax::new(
+                current.get_token_position(),
+                InvalidSyntaxType::ExponentialError(),
+            ));
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::test_support::lex;
+
+    use super::{ast::Ast, token_stack::TokenStack, Parser};
+
+    // Returns the AST and the reported errors (`ln:col: message`)
+    fn parse_source(source: &str) -> (Ast, Vec<String>) {
+        let tokens = lex(source);
+        let mut token_stack = TokenStack::new(&tokens);
+        let mut parser = Parser::new(&mut token_stack);
+        let errors = match parser.parse() {
+            Ok(()) => Vec::new(),
+            Err(e) => e
+                .to_diagnostics()
+                .into_iter()
+                .map(|x| {
+                    let span = x.span.unwrap();
+                    format!("{}:{}: {}", span.ln, span.col, x.message)
+                })
+                .collect(),
+        };
+        (parser.ast, errors)
+    }
 
     #[test]
     fn test_name() {}
+
+    #[test]
+    fn recover_statement_test() {
+        let (ast, errors) = parse_source(
+            r#"
+const f = (a: number): number => {
+    let b = a + 1
+    let c = b * 2;
+    let d = (c + ;
+    g(1 2);
+    d
+};
+const g = () => {};"#,
+        );
+        assert_eq!(
+            vec![
+                "4:5: expected `;`, found `let`",
+                "5:18: expected literal, identifier, `(`, `{`, `if`, `match`, found `;`",
+                "6:9: expected `,`, `)`, found `2`",
+            ],
+            errors
+        );
+        assert!(ast.is_invalid());
+        let children = ast.children.as_ref().unwrap();
+        assert_eq!(2, children.len());
+        assert!(children[0].is_invalid());
+        assert!(!children[1].is_invalid());
+    }
+
+    #[test]
+    fn recover_nested_block_test() {
+        let (ast, errors) = parse_source(
+            r#"
+const a = () => {
+    const b = match x { 1 2 => { 3 }, _ => 4 };
+    const c = P { x: 1 y: 2 };
+    const d = match s { S::A { w h } => w, _ => 0 };
+    const e = 1;
+};
+const f = match x { 1 2 => { const q = 1; q }, _ => 4 };
+const g = match x { 1 => { let y = 1 1; y }, _ => 4 };
+const h = () => {};"#,
+        );
+        assert_eq!(
+            vec![
+                "3:27: expected `=>`, found `2`",
+                "4:24: expected `,`, `}`, found `y`",
+                "5:34: expected `,`, `}`, found `h`",
+                "8:23: expected `=>`, found `2`",
+                "9:38: expected `;`, found `1`",
+            ],
+            errors
+        );
+        let children = ast.children.as_ref().unwrap();
+        assert_eq!(4, children.len());
+        assert!(children[..3].iter().all(|x| x.is_invalid()));
+        assert!(!children[3].is_invalid());
+    }
+
+    #[test]
+    fn recover_missing_semicolon_test() {
+        let (ast, errors) = parse_source(
+            r#"import { a } from "./b"
+const c = 1
+const x = 1 +;
+const f = () => { let a = 1 }
+[EntryPoint] struct P { x: number }
+import { d } from "./d";
+const main = () => {};"#,
+        );
+        assert_eq!(
+            vec![
+                "2:1: expected `;`, found `const`",
+                "3:1: expected `;`, found `const`",
+                "3:14: expected literal, identifier, `(`, `{`, `if`, `match`, found `;`",
+                "4:29: expected `;`, found `}`",
+                "5:1: expected `;`, found `[`",
+                "5:14: expected `const`, found `struct`",
+                "6:1: expected `[`, `const`, `export`, `struct`, `enum`, found `import`",
+            ],
+            errors
+        );
+        let children = ast.children.as_ref().unwrap();
+        let invalid = children.iter().map(|x| x.is_invalid()).collect::<Vec<_>>();
+        // the struct after the invalid attribute is parsed
+        assert_eq!(
+            vec![true, true, true, true, false, true, false, true, false],
+            invalid
+        );
+    }
+
+    #[test]
+    fn recover_declaration_test() {
+        let (ast, errors) = parse_source(
+            "const a = ;
+const b = (x y) => { x };
+}
+[EntryPoint]
+const main = () => {};",
+        );
+        assert_eq!(
+            vec![
+                "1:11: expected literal, identifier, `(`, `{`, `if`, `match`, found `;`",
+                "2:14: expected `:`, found `y`",
+                "3:1: expected `[`, `const`, `export`, `struct`, `enum`, found `}`",
+            ],
+            errors
+        );
+        let children = ast.children.as_ref().unwrap();
+        assert!(children[..3].iter().all(|x| x.is_invalid()));
+        assert!(children[3..].iter().all(|x| !x.is_invalid()));
+    }
+
+    #[test]
+    fn parse_assignment_target_test() {
+        let (ast, errors) = parse_source(
+            "const f = (i: number) => {
+    1 = 2;
+    0x1F += i;
+    1e3 = 1;
+    a.b = 1;
+};",
+        );
+        assert_eq!(
+            vec![
+                "2:5: expected identifier, found `1`",
+                "3:5: expected identifier, found `0x1F`",
+                "4:5: expected identifier, found `1e3`",
+            ],
+            errors
+        );
+        assert!(ast.is_invalid());
+    }
+
+    #[test]
+    fn parse_doc_comment_test() {
+        let (ast, errors) = parse_source(
+            "/// A point.
+/// It has x and y.
+export struct Point { x: number, y: number }
+/// The entry point.
+[EntryPoint]
+/// Prints 1.
+const main = () => {
+    /// not attached
+    let a = 1;
+};
+const b = 1;",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        let doc_comments = ast
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|x| x.doc_comment.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some("A point.\nIt has x and y."),
+                None,
+                Some("The entry point.\nPrints 1."),
+                None
+            ],
+            doc_comments
+        );
+    }
+
+    #[test]
+    fn parse_truncated_source_test() {
+        let source = "const f = (a: number): P => { if a > 0 { P { x: a } } else { f(a + 1) } };";
+        for i in 1..source.len() {
+            let (ast, errors) = parse_source(&source[..i]);
+            assert!(!errors.is_empty(), "{}", &source[..i]);
+            assert!(ast.is_invalid(), "{}", &source[..i]);
+        }
+    }
 }
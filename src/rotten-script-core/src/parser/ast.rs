@@ -6,6 +6,8 @@ pub struct Ast {
     pub children: Option<Vec<Ast>>,
     pub token: Option<Token>,
     pub ast_type: AstType,
+    /// The doc comments of a top-level declaration, e.g. `/// ...` before `const a = 1;`.
+    pub doc_comment: Option<String>,
     invalid_ast: bool,
     invalid_root_ast: bool,
 }
@@ -16,6 +18,7 @@ impl Ast {
             children: None,
             token: Some(token),
             ast_type: AstType::Terminal,
+            doc_comment: None,
             invalid_ast: false,
             invalid_root_ast: false,
         }
@@ -27,12 +30,14 @@ impl Ast {
             children: Some(children),
             token: None,
             ast_type: AstType::NonTerminal(node_type),
+            doc_comment: None,
             invalid_ast: has_invalid_ast,
             invalid_root_ast: false,
         }
     }
 
     pub fn add_child(&mut self, ast: Ast) {
+        self.invalid_ast |= ast.invalid_ast;
         if let Some(c) = &mut self.children {
             c.push(ast);
         } else {
@@ -46,6 +51,28 @@ impl Ast {
         }
     }
 
+    /// Returns the leftmost terminal token of this tree.
+    pub fn get_first_token(&self) -> Option<&Token> {
+        match &self.children {
+            Some(children) => children.iter().find_map(|x| x.get_first_token()),
+            None => self.token.as_ref(),
+        }
+    }
+
+    /// Returns true if this tree is a statement of CompoundExpression
+    /// (i.e. it does not become the value of the block).
+    pub fn is_statement(&self) -> bool {
+        matches!(
+            self.ast_type,
+            AstType::NonTerminal(NonTerminal::ExpressionStatement)
+                | AstType::NonTerminal(NonTerminal::ConstDeclaration)
+                | AstType::NonTerminal(NonTerminal::LetDeclaration)
+                | AstType::NonTerminal(NonTerminal::AssignmentStatement)
+                | AstType::NonTerminal(NonTerminal::ForStatement)
+                | AstType::NonTerminal(NonTerminal::WhileStatement)
+        )
+    }
+
     pub fn is_invalid(&self) -> bool {
         self.invalid_ast
     }
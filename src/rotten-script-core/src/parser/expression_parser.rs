@@ -1,8 +1,11 @@
 use super::{
-    ast::Ast, non_terminal::NonTerminal, parse_error::ParseError, InvalidSyntaxResultHandler,
-    Parser,
+    ast::Ast, ast_type::AstType, non_terminal::NonTerminal, parse_error::ParseError,
+    InvalidSyntaxResultHandler, Parser,
+};
+use crate::lexer::{
+    reserved_word::ReservedWord,
+    token::{TemplatePart, TokenBase},
 };
-use crate::lexer::{reserved_word::ReservedWord, token::TokenBase};
 
 macro_rules! parse_left_assoc_binary_expr {
     ($self:ident, $current_type:path , $next_func:ident, $($tokens:pat),+ ) => {
@@ -184,14 +187,34 @@ impl<'a> Parser<'a> {
 
         match self.tokens.look_ahead(1) {
             // Literal or Identifier
+            Some(TokenBase::Identifier(_)) if self.tokens.look_ahead(2) == Some(TBR!("::")) => {
+                let path = self.parse_path_expression();
+                if !self.no_struct_literal && self.tokens.look_ahead(1) == Some(TBR!("{")) {
+                    asts.push(self.parse_struct_expression(path)?);
+                } else {
+                    asts.push(path);
+                }
+            }
+            Some(TokenBase::Identifier(_))
+                if !self.no_struct_literal && self.tokens.look_ahead(2) == Some(TBR!("{")) =>
+            {
+                let name = Ast::new_leaf(self.tokens.next_token().unwrap());
+                asts.push(self.parse_struct_expression(name)?);
+            }
             Some(TokenBase::String(_))
+            | Some(TokenBase::Template(TemplatePart::NoSubstitution, _))
             | Some(TokenBase::Number(_))
+            | Some(TBR!("true"))
+            | Some(TBR!("false"))
             | Some(TokenBase::Identifier(_)) => {
                 asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
             }
+            Some(TokenBase::Template(TemplatePart::Head, _)) => {
+                asts.push(self.parse_template_literal()?);
+            }
             // Function or Parenthesized
             Some(TokenBase::Reserved(ReservedWord::LeftParenthesis)) => {
-                if self.should_continue_as_function_expr()? {
+                if self.should_continue_as_function_expr() {
                     asts.push(self.parse_function_expression()?);
                 } else {
                     asts.push(self.parse_parenthesized_expression()?);
@@ -201,16 +224,25 @@ impl<'a> Parser<'a> {
             Some(TokenBase::Reserved(ReservedWord::LeftCurly)) => {
                 asts.push(self.parse_compound_expression()?);
             }
+            Some(TBR!("if")) => {
+                asts.push(self.parse_if_expression()?);
+            }
+            Some(TBR!("match")) => {
+                asts.push(self.parse_match_expression()?);
+            }
             None | Some(_) => self.handle_expected_actually_error(
                 self.tokens.nth(1),
                 vec![
                     TokenBase::default_string(),
                     TokenBase::default_number(),
+                    TBR!("true"),
                     TokenBase::default_identifier(),
                     TokenBase::Reserved(ReservedWord::LeftParenthesis),
                     TokenBase::Reserved(ReservedWord::LeftCurly),
+                    TBR!("if"),
+                    TBR!("match"),
                 ],
-                self.tokens.peek_token().unwrap(),
+                self.tokens.peek_token(),
             ),
         }
 
@@ -227,7 +259,7 @@ impl<'a> Parser<'a> {
                             self.handle_expected_actually_error(
                                 self.tokens.nth(1),
                                 vec![TokenBase::default_identifier()],
-                                self.tokens.peek_token().unwrap(),
+                                self.tokens.peek_token(),
                             )
                         }
                     }
@@ -245,7 +277,7 @@ impl<'a> Parser<'a> {
                 //         TokenBase::Reserved(ReservedWord::Dot),
                 //         TokenBase::Reserved(ReservedWord::LeftParenthesis),
                 //     ],
-                //     self.tokens.peek_token().unwrap(),
+                //     self.tokens.peek_token(),
                 // ),
                 _ => {
                     break;
@@ -259,15 +291,9 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    // FunctionExpression = ParameterList , [ ":" , Identifier ] , "=>" , CompoundExpression;
     fn parse_function_expression(&mut self) -> Result<Ast, ParseError> {
-        self.tokens
-            .consume_reserved(ReservedWord::LeftParenthesis)
-            .handle_consume(self);
-        self.tokens
-            .consume_reserved(ReservedWord::RightParenthesis)
-            .handle_consume(self);
-
-        let mut asts = Vec::new();
+        let mut asts = vec![self.parse_parameter_list()?];
 
         if self.tokens.look_ahead(1) == Some(TBR!(":")) {
             self.tokens.next();
@@ -278,7 +304,7 @@ impl<'a> Parser<'a> {
                 _ => self.handle_expected_actually_error(
                     self.tokens.nth(1),
                     vec![TokenBase::default_identifier()],
-                    self.tokens.peek_token().unwrap(),
+                    self.tokens.peek_token(),
                 ),
             }
         }
@@ -293,16 +319,449 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    // ParameterList = "(" , [ Parameter , { "," , Parameter } , [ "," ] ] , ")";
+    fn parse_parameter_list(&mut self) -> Result<Ast, ParseError> {
+        self.tokens
+            .consume_reserved(ReservedWord::LeftParenthesis)
+            .handle_consume(self);
+        let mut params = Vec::new();
+
+        loop {
+            match self.tokens.look_ahead(1) {
+                Some(TBR!(")")) => {
+                    self.tokens.next();
+                    break;
+                }
+                Some(TokenBase::Identifier(_)) => {
+                    params.push(self.parse_annotated_identifier(NonTerminal::Parameter)?);
+                    match self.tokens.look_ahead(1) {
+                        Some(TBR!(",")) => {
+                            self.tokens.next();
+                        }
+                        Some(TBR!(")")) => {
+                            self.tokens.next();
+                            break;
+                        }
+                        Some(_) | None => {
+                            self.handle_expected_actually_error(
+                                self.tokens.nth(1),
+                                vec![TBR!(","), TBR!(")")],
+                                self.tokens.peek_token(),
+                            );
+                            break;
+                        }
+                    }
+                }
+                Some(_) | None => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TokenBase::default_identifier(), TBR!(")")],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+        }
+
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::ParameterList,
+            params,
+        ))
+    }
+
+    // Parameter = Identifier , ":" , Identifier;
+    // FieldDeclaration = Identifier , ":" , Identifier;
+    pub(super) fn parse_annotated_identifier(
+        &mut self,
+        non_terminal: NonTerminal,
+    ) -> Result<Ast, ParseError> {
+        let mut asts = vec![Ast::new_leaf(self.tokens.next_token().unwrap())];
+        self.tokens
+            .consume_reserved(ReservedWord::Colon)
+            .handle_consume(self);
+        match self.tokens.look_ahead(1) {
+            Some(TokenBase::Identifier(_)) => {
+                asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+            }
+            _ => self.handle_expected_actually_error(
+                self.tokens.nth(1),
+                vec![TokenBase::default_identifier()],
+                self.tokens.peek_token(),
+            ),
+        }
+        Ok(Ast::new_node_with_leaves(non_terminal, asts))
+    }
+
+    // IfExpression = "if" , Expression , CompoundExpression , [ "else" , ( IfExpression | CompoundExpression ) ];
+    fn parse_if_expression(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let mut asts = vec![
+            self.parse_expression_without_struct_literal()?,
+            self.parse_compound_expression()?,
+        ];
+        if self.tokens.look_ahead(1) == Some(TBR!("else")) {
+            self.tokens.next();
+            if self.tokens.look_ahead(1) == Some(TBR!("if")) {
+                asts.push(self.parse_if_expression()?);
+            } else {
+                asts.push(self.parse_compound_expression()?);
+            }
+        }
+        Ok(Ast::new_node_with_leaves(NonTerminal::IfExpression, asts))
+    }
+
+    // MatchExpression = "match" , Expression , "{" , [ MatchArm , { "," , MatchArm } , [ "," ] ] , "}";
+    fn parse_match_expression(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let mut asts = vec![self.parse_expression_without_struct_literal()?];
+        self.tokens
+            .consume_reserved(ReservedWord::LeftCurly)
+            .handle_consume(self);
+
+        while let Some(tk) = self.tokens.look_ahead(1) {
+            if tk == TBR!("}") {
+                break;
+            }
+            let arm = self.parse_match_arm()?;
+            let is_block_body =
+                Self::is_block_like_expression(arm.children.as_ref().unwrap().last().unwrap());
+            asts.push(arm);
+            match self.tokens.look_ahead(1) {
+                Some(TBR!(",")) => {
+                    self.tokens.next();
+                }
+                Some(TBR!("}")) => break,
+                // e.g. `1 => { a } 2 => { b }`
+                Some(_) if is_block_body => {}
+                Some(_) | None => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TBR!(","), TBR!("}")],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+        }
+        self.tokens
+            .consume_reserved(ReservedWord::RightCurly)
+            .handle_consume(self);
+
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::MatchExpression,
+            asts,
+        ))
+    }
+
+    // MatchArm = Pattern , [ MatchGuard ] , "=>" , Expression;
+    // MatchGuard = "if" , Expression;
+    fn parse_match_arm(&mut self) -> Result<Ast, ParseError> {
+        let mut asts = vec![self.parse_pattern()?];
+        if self.tokens.look_ahead(1) == Some(TBR!("if")) {
+            self.tokens.next();
+            asts.push(Ast::new_node_with_leaves(
+                NonTerminal::MatchGuard,
+                vec![self.parse_expression()?],
+            ));
+        }
+        self.tokens
+            .consume_reserved(ReservedWord::Arrow)
+            .handle_consume(self);
+        asts.push(self.parse_expression()?);
+        Ok(Ast::new_node_with_leaves(NonTerminal::MatchArm, asts))
+    }
+
+    // Pattern = "_" | Identifier | Literal | "-" , Number | PathExpression , [ TuplePattern | StructPattern ];
+    fn parse_pattern(&mut self) -> Result<Ast, ParseError> {
+        let mut asts = Vec::new();
+        match self.tokens.look_ahead(1) {
+            Some(TokenBase::Identifier(_)) if self.tokens.look_ahead(2) == Some(TBR!("::")) => {
+                asts.push(self.parse_path_expression());
+                match self.tokens.look_ahead(1) {
+                    Some(TBR!("(")) => asts.push(self.parse_tuple_pattern()?),
+                    Some(TBR!("{")) => asts.push(self.parse_struct_pattern()?),
+                    _ => {}
+                }
+            }
+            Some(TBR!("_"))
+            | Some(TokenBase::Identifier(_))
+            | Some(TokenBase::String(_))
+            | Some(TokenBase::Number(_))
+            | Some(TBR!("true"))
+            | Some(TBR!("false")) => {
+                asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+            }
+            Some(TBR!("-")) => {
+                asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+                if let Some(TokenBase::Number(_)) = self.tokens.look_ahead(1) {
+                    asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+                } else {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TokenBase::default_number()],
+                        self.tokens.peek_token(),
+                    );
+                }
+            }
+            None | Some(_) => self.handle_expected_actually_error(
+                self.tokens.nth(1),
+                vec![
+                    TBR!("_"),
+                    TokenBase::default_identifier(),
+                    TokenBase::default_string(),
+                    TokenBase::default_number(),
+                    TBR!("true"),
+                ],
+                self.tokens.peek_token(),
+            ),
+        }
+        Ok(Ast::new_node_with_leaves(NonTerminal::Pattern, asts))
+    }
+
+    // TuplePattern = "(" , [ Pattern , { "," , Pattern } , [ "," ] ] , ")";
+    fn parse_tuple_pattern(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let mut asts = Vec::new();
+        loop {
+            if self.tokens.look_ahead(1) == Some(TBR!(")")) {
+                self.tokens.next();
+                break;
+            }
+            asts.push(self.parse_pattern()?);
+            match self.tokens.look_ahead(1) {
+                Some(TBR!(",")) => {
+                    self.tokens.next();
+                }
+                Some(TBR!(")")) => {
+                    self.tokens.next();
+                    break;
+                }
+                Some(_) | None => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TBR!(","), TBR!(")")],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(Ast::new_node_with_leaves(NonTerminal::TuplePattern, asts))
+    }
+
+    // StructPattern = "{" , [ FieldPattern , { "," , FieldPattern } , [ "," ] ] , "}";
+    // FieldPattern = Identifier , [ ":" , Pattern ];
+    fn parse_struct_pattern(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let mut asts = Vec::new();
+        loop {
+            match self.tokens.look_ahead(1) {
+                Some(TBR!("}")) => {
+                    self.tokens.next();
+                    break;
+                }
+                Some(TokenBase::Identifier(_)) => {
+                    let mut field = vec![Ast::new_leaf(self.tokens.next_token().unwrap())];
+                    if self.tokens.look_ahead(1) == Some(TBR!(":")) {
+                        self.tokens.next();
+                        field.push(self.parse_pattern()?);
+                    }
+                    asts.push(Ast::new_node_with_leaves(NonTerminal::FieldPattern, field));
+                    match self.tokens.look_ahead(1) {
+                        Some(TBR!(",")) => {
+                            self.tokens.next();
+                        }
+                        Some(TBR!("}")) => {
+                            self.tokens.next();
+                            break;
+                        }
+                        Some(_) | None => {
+                            self.handle_expected_actually_error(
+                                self.tokens.nth(1),
+                                vec![TBR!(","), TBR!("}")],
+                                self.tokens.peek_token(),
+                            );
+                            break;
+                        }
+                    }
+                }
+                Some(_) | None => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TokenBase::default_identifier(), TBR!("}")],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(Ast::new_node_with_leaves(NonTerminal::StructPattern, asts))
+    }
+
+    // PathExpression = Identifier , "::" , Identifier;
+    fn parse_path_expression(&mut self) -> Ast {
+        let mut asts = vec![Ast::new_leaf(self.tokens.next_token().unwrap())];
+        self.tokens.next();
+        match self.tokens.look_ahead(1) {
+            Some(TokenBase::Identifier(_)) => {
+                asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+            }
+            _ => self.handle_expected_actually_error(
+                self.tokens.nth(1),
+                vec![TokenBase::default_identifier()],
+                self.tokens.peek_token(),
+            ),
+        }
+        Ast::new_node_with_leaves(NonTerminal::PathExpression, asts)
+    }
+
+    // ForStatement = "for" , Identifier , "in" , RangeExpression , CompoundExpression , [ ";" ];
+    fn parse_for_statement(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let mut asts = Vec::new();
+        match self.tokens.look_ahead(1) {
+            Some(TokenBase::Identifier(_)) => {
+                asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+            }
+            _ => self.handle_expected_actually_error(
+                self.tokens.nth(1),
+                vec![TokenBase::default_identifier()],
+                self.tokens.peek_token(),
+            ),
+        }
+        self.tokens
+            .consume_reserved(ReservedWord::In)
+            .handle_consume(self);
+        asts.push(self.parse_range_expression()?);
+        asts.push(self.parse_compound_expression()?);
+        Ok(Ast::new_node_with_leaves(NonTerminal::ForStatement, asts))
+    }
+
+    // RangeExpression = Expression , ( ".." | "..=" ) , Expression;
+    fn parse_range_expression(&mut self) -> Result<Ast, ParseError> {
+        let mut asts = vec![self.parse_expression()?];
+        match self.tokens.look_ahead(1) {
+            Some(TBR!("..")) | Some(TBR!("..=")) => {
+                asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+            }
+            _ => self.handle_expected_actually_error(
+                self.tokens.nth(1),
+                vec![TBR!(".."), TBR!("..=")],
+                self.tokens.peek_token(),
+            ),
+        }
+        asts.push(self.parse_expression_without_struct_literal()?);
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::RangeExpression,
+            asts,
+        ))
+    }
+
+    // WhileStatement = "while" , Expression , CompoundExpression , [ ";" ];
+    fn parse_while_statement(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let asts = vec![
+            self.parse_expression_without_struct_literal()?,
+            self.parse_compound_expression()?,
+        ];
+        Ok(Ast::new_node_with_leaves(NonTerminal::WhileStatement, asts))
+    }
+
+    // StructExpression = ( Identifier | PathExpression ) , "{" , [ FieldInitializer , { "," , FieldInitializer } , [ "," ] ] , "}";
+    // FieldInitializer = Identifier , ":" , Expression;
+    fn parse_struct_expression(&mut self, name: Ast) -> Result<Ast, ParseError> {
+        let mut asts = vec![name];
+        self.tokens.next();
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+
+        loop {
+            match self.tokens.look_ahead(1) {
+                Some(TBR!("}")) => {
+                    self.tokens.next();
+                    break;
+                }
+                Some(TokenBase::Identifier(_)) => {
+                    let name = Ast::new_leaf(self.tokens.next_token().unwrap());
+                    self.tokens
+                        .consume_reserved(ReservedWord::Colon)
+                        .handle_consume(self);
+                    asts.push(Ast::new_node_with_leaves(
+                        NonTerminal::FieldInitializer,
+                        vec![name, self.parse_expression()?],
+                    ));
+                    match self.tokens.look_ahead(1) {
+                        Some(TBR!(",")) => {
+                            self.tokens.next();
+                        }
+                        Some(TBR!("}")) => {
+                            self.tokens.next();
+                            break;
+                        }
+                        Some(_) | None => {
+                            self.handle_expected_actually_error(
+                                self.tokens.nth(1),
+                                vec![TBR!(","), TBR!("}")],
+                                self.tokens.peek_token(),
+                            );
+                            break;
+                        }
+                    }
+                }
+                Some(_) | None => {
+                    self.handle_expected_actually_error(
+                        self.tokens.nth(1),
+                        vec![TokenBase::default_identifier(), TBR!("}")],
+                        self.tokens.peek_token(),
+                    );
+                    break;
+                }
+            }
+        }
+
+        self.no_struct_literal = no_struct_literal;
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::StructExpression,
+            asts,
+        ))
+    }
+
+    /// Parses the expression followed by a block (e.g. the condition of `if`),
+    /// where `a { ... }` is not a struct literal.
+    fn parse_expression_without_struct_literal(&mut self) -> Result<Ast, ParseError> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, true);
+        let result = self.parse_expression();
+        self.no_struct_literal = no_struct_literal;
+        result
+    }
+
     fn parse_compound_expression(&mut self) -> Result<Ast, ParseError> {
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let result = self.parse_compound_expression_inner();
+        self.no_struct_literal = no_struct_literal;
+        result
+    }
+
+    fn parse_compound_expression_inner(&mut self) -> Result<Ast, ParseError> {
         let mut expressions = Vec::new();
         self.tokens
             .consume_reserved(ReservedWord::LeftCurly)
             .handle_consume(self);
         while let Some(tk) = self.tokens.look_ahead(1) {
+            let start = self.tokens.ind();
             match tk {
                 TokenBase::String(_)
+                | TokenBase::Template(..)
                 | TokenBase::Number(_)
                 | TokenBase::Identifier(_)
+                | TBR!("true")
+                | TBR!("false")
+                | TBR!("!")
+                | TBR!("~")
+                | TBR!("+")
+                | TBR!("-")
+                | TBR!("if")
+                | TBR!("match")
+                | TBR!("{")
                 | TokenBase::Reserved(ReservedWord::LeftParenthesis) => {
                     if self.should_continue_as_assignment_expr() {
                         expressions.push(self.parse_assignment_expression()?);
@@ -318,6 +777,15 @@ impl<'a> Parser<'a> {
                                     vec![exp],
                                 ));
                                 true
+                            } else if Self::is_block_like_expression(&exp)
+                                && self.tokens.look_ahead(1) != Some(TBR!("}"))
+                            {
+                                // e.g. `if a { b(); } c();`
+                                expressions.push(Ast::new_node_with_leaves(
+                                    NonTerminal::ExpressionStatement,
+                                    vec![exp],
+                                ));
+                                true
                             } else {
                                 expressions.push(exp);
                                 false
@@ -331,7 +799,7 @@ impl<'a> Parser<'a> {
                             self.handle_expected_actually_error(
                                 self.tokens.nth(1),
                                 vec![TokenBase::Reserved(ReservedWord::SemiColon)],
-                                self.tokens.peek_token().unwrap(),
+                                self.tokens.peek_token(),
                             );
                         }
                     }
@@ -342,6 +810,18 @@ impl<'a> Parser<'a> {
                 TokenBase::Reserved(ReservedWord::Let) => {
                     expressions.push(self.parse_let_declaration()?)
                 }
+                TBR!("for") | TBR!("while") => {
+                    expressions.push(if tk == TBR!("for") {
+                        self.parse_for_statement()?
+                    } else {
+                        self.parse_while_statement()?
+                    });
+                    // e.g. `for i in 0..n { ... };`
+                    if self.tokens.look_ahead(1) == Some(TBR!(";")) {
+                        self.tokens.next();
+                    }
+                }
+                TokenBase::Reserved(ReservedWord::RightCurly) => break,
                 _ => {
                     let target_token = self.tokens.nth(1);
                     self.handle_expected_actually_error(
@@ -352,11 +832,21 @@ impl<'a> Parser<'a> {
                             TokenBase::default_identifier(),
                             TokenBase::Reserved(ReservedWord::LeftParenthesis),
                         ],
-                        self.tokens.peek_token().unwrap(),
+                        self.tokens.peek_token(),
                     );
-                    expressions.push(Ast::new_leaf(target_token.unwrap()))
+                    expressions.push(self.new_invalid_leaf(target_token))
                 }
             }
+            self.recover_statement(expressions.last_mut(), start);
+        }
+        // the statement with an error is followed by the end of the block
+        if self.panic_mode {
+            if let Some(last) = expressions.last_mut() {
+                last.set_invalid();
+            }
+            if self.tokens.look_ahead(1) == Some(TBR!("}")) {
+                self.panic_mode = false;
+            }
         }
         self.tokens
             .consume_reserved(ReservedWord::RightCurly)
@@ -368,11 +858,45 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    /// Returns true if the expression ends with a block and can be a statement without `;`.
+    fn is_block_like_expression(ast: &Ast) -> bool {
+        match ast.children.as_deref() {
+            Some([child])
+                if ast.ast_type == AstType::NonTerminal(NonTerminal::PrimaryExpression) =>
+            {
+                matches!(
+                    child.ast_type,
+                    AstType::NonTerminal(NonTerminal::IfExpression)
+                        | AstType::NonTerminal(NonTerminal::MatchExpression)
+                        | AstType::NonTerminal(NonTerminal::CompoundExpression)
+                )
+            }
+            _ => false,
+        }
+    }
+
     fn should_continue_as_assignment_expr(&self) -> bool {
         let mut count = 1;
         let mut depth = 0;
         while let Some(t) = self.tokens.look_ahead(count) {
-            if t == TBR!("=") {
+            if depth == 0
+                && matches!(
+                    t,
+                    TBR!("=")
+                        | TBR!("*=")
+                        | TBR!("/=")
+                        | TBR!("%=")
+                        | TBR!("+=")
+                        | TBR!("-=")
+                        | TBR!("<<=")
+                        | TBR!(">>=")
+                        | TBR!(">>>=")
+                        | TBR!("&=")
+                        | TBR!("^=")
+                        | TBR!("|=")
+                        | TBR!("**=")
+                )
+            {
                 return true;
             }
             if t == TBR!(";") {
@@ -392,9 +916,50 @@ impl<'a> Parser<'a> {
         false
     }
 
+    // TemplateLiteral = TemplateHead , Expression , { TemplateMiddle , Expression } , TemplateTail;
+    fn parse_template_literal(&mut self) -> Result<Ast, ParseError> {
+        let mut asts = vec![Ast::new_leaf(self.tokens.next_token().unwrap())];
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let result = self.parse_template_substitutions(&mut asts);
+        self.no_struct_literal = no_struct_literal;
+        result?;
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::TemplateLiteral,
+            asts,
+        ))
+    }
+
+    fn parse_template_substitutions(&mut self, asts: &mut Vec<Ast>) -> Result<(), ParseError> {
+        loop {
+            asts.push(self.parse_expression()?);
+            match self.tokens.look_ahead(1) {
+                Some(TokenBase::Template(TemplatePart::Middle, _)) => {
+                    asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+                }
+                Some(TokenBase::Template(TemplatePart::Tail, _)) => {
+                    asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
+                    return Ok(());
+                }
+                _ => {
+                    let target_token = self.tokens.nth(1);
+                    self.handle_expected_actually_error(
+                        target_token.clone(),
+                        vec![TokenBase::Template(TemplatePart::Tail, String::new())],
+                        self.tokens.peek_token(),
+                    );
+                    asts.push(self.new_invalid_leaf(target_token));
+                    return Ok(());
+                }
+            }
+        }
+    }
+
     fn parse_parenthesized_expression(&mut self) -> Result<Ast, ParseError> {
         self.tokens.next();
-        let ast = self.parse_expression()?;
+        let no_struct_literal = std::mem::replace(&mut self.no_struct_literal, false);
+        let ast = self.parse_expression();
+        self.no_struct_literal = no_struct_literal;
+        let ast = ast?;
         self.tokens
             .consume_reserved(ReservedWord::RightParenthesis)
             .handle_consume(self);
@@ -404,9 +969,11 @@ impl<'a> Parser<'a> {
         ))
     }
 
-    fn should_continue_as_function_expr(&self) -> Result<bool, ParseError> {
+    // true if the parenthesis is followed by `=>` or `:` (the return type)
+    fn should_continue_as_function_expr(&self) -> bool {
         let mut count = 1;
         let mut depth = 0;
+        let mut curly_depth = 0;
         loop {
             match self.tokens.look_ahead(count) {
                 Some(TokenBase::Reserved(ReservedWord::LeftParenthesis)) => {
@@ -415,19 +982,230 @@ impl<'a> Parser<'a> {
                 Some(TokenBase::Reserved(ReservedWord::RightParenthesis)) => {
                     depth -= 1;
                 }
+                Some(TBR!("{")) => curly_depth += 1,
+                // the parenthesis is not closed in the statement (e.g. `(a + ;`),
+                // which is reported by `parse_parenthesized_expression`
+                Some(TBR!("}")) | Some(TBR!(";")) if curly_depth == 0 => return false,
+                Some(TBR!("}")) => curly_depth -= 1,
                 Some(_) => {}
-                None => return Err(ParseError::new("unexpected eof")),
+                None => return false,
             }
             count += 1;
             if depth == 0 {
-                return if self.tokens.look_ahead(count) == Some(TBR!("=>"))
-                    || self.tokens.look_ahead(count) == Some(TBR!(":"))
-                {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                };
+                return self.tokens.look_ahead(count) == Some(TBR!("=>"))
+                    || self.tokens.look_ahead(count) == Some(TBR!(":"));
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::lex;
+
+    use super::{
+        super::ast_type::AstType::*, super::non_terminal::NonTerminal::*,
+        super::token_stack::TokenStack, Ast, Parser,
+    };
+
+    fn parse_expression_source(source: &str) -> Ast {
+        let tokens = lex(source);
+        let mut token_stack = TokenStack::new(&tokens);
+        let mut parser = Parser::new(&mut token_stack);
+        let ast = parser.parse_expression().unwrap();
+        assert!(!parser.parse_error.has_error());
+        ast
+    }
+
+    #[test]
+    fn parse_function_expression_with_parameters_test() {
+        let ast = parse_expression_source("(a: number, b: string): number => { a }");
+        let func = &ast.children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(FunctionExpression), func.ast_type);
+
+        let children = func.children.as_ref().unwrap();
+        assert_eq!(3, children.len());
+        assert_eq!(NonTerminal(ParameterList), children[0].ast_type);
+        assert_eq!(Terminal, children[1].ast_type);
+        assert_eq!(NonTerminal(CompoundExpression), children[2].ast_type);
+
+        let params = children[0].children.as_ref().unwrap();
+        assert_eq!(2, params.len());
+        for (param, (name, type_name)) in params.iter().zip([("a", "number"), ("b", "string")]) {
+            assert_eq!(NonTerminal(Parameter), param.ast_type);
+            let param_children = param.children.as_ref().unwrap();
+            assert_eq!(name, param_children[0].token.as_ref().unwrap().to_string());
+            assert_eq!(
+                type_name,
+                param_children[1].token.as_ref().unwrap().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_if_expression_test() {
+        let ast = parse_expression_source("if a { 1 } else if b { 2 } else { 3 }");
+        let if_expr = &ast.children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(IfExpression), if_expr.ast_type);
+
+        let children = if_expr.children.as_ref().unwrap();
+        assert_eq!(3, children.len());
+        assert_eq!(NonTerminal(CompoundExpression), children[1].ast_type);
+        assert_eq!(NonTerminal(IfExpression), children[2].ast_type);
+
+        let else_children = children[2].children.as_ref().unwrap();
+        assert_eq!(3, else_children.len());
+        assert_eq!(NonTerminal(CompoundExpression), else_children[2].ast_type);
+    }
+
+    #[test]
+    fn parse_if_statement_without_semicolon_test() {
+        let ast = parse_expression_source("{ if a { b(); } c() }");
+        let compound = &ast.children.as_ref().unwrap()[0];
+        let children = compound.children.as_ref().unwrap();
+        assert_eq!(2, children.len());
+        assert_eq!(NonTerminal(ExpressionStatement), children[0].ast_type);
+        assert_eq!(NonTerminal(PrimaryExpression), children[1].ast_type);
+    }
+
+    #[test]
+    fn parse_match_expression_test() {
+        let ast = parse_expression_source(
+            r#"match n { 0 => "zero", -1 => { "minus" } x if x > 0 => "positive", _ => "other" }"#,
+        );
+        let match_expr = &ast.children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(MatchExpression), match_expr.ast_type);
+
+        let arms = &match_expr.children.as_ref().unwrap()[1..];
+        assert_eq!(4, arms.len());
+        let arm_lengths = arms
+            .iter()
+            .map(|x| x.children.as_ref().unwrap().len())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![2, 2, 3, 2], arm_lengths);
+
+        let guarded = arms[2].children.as_ref().unwrap();
+        assert_eq!(NonTerminal(Pattern), guarded[0].ast_type);
+        assert_eq!(NonTerminal(MatchGuard), guarded[1].ast_type);
+
+        let negative = arms[1].children.as_ref().unwrap()[0]
+            .children
+            .as_ref()
+            .unwrap();
+        assert_eq!(2, negative.len());
+    }
+
+    #[test]
+    fn parse_loop_statements_test() {
+        let ast = parse_expression_source("{ for i in 0..=n { f(i); }; while a { b(); } c() }");
+        let compound = &ast.children.as_ref().unwrap()[0];
+        let children = compound.children.as_ref().unwrap();
+        assert_eq!(3, children.len());
+        assert_eq!(NonTerminal(ForStatement), children[0].ast_type);
+        assert_eq!(NonTerminal(WhileStatement), children[1].ast_type);
+
+        let for_children = children[0].children.as_ref().unwrap();
+        assert_eq!(Terminal, for_children[0].ast_type);
+        assert_eq!(NonTerminal(RangeExpression), for_children[1].ast_type);
+        assert_eq!(NonTerminal(CompoundExpression), for_children[2].ast_type);
+        let range = for_children[1].children.as_ref().unwrap();
+        assert_eq!("..=", range[1].token.as_ref().unwrap().to_string());
+    }
+
+    #[test]
+    fn parse_struct_expression_test() {
+        let ast = parse_expression_source("Point { x: 1, y: a + b }.x");
+        let children = ast.children.as_ref().unwrap();
+        assert_eq!(3, children.len());
+        assert_eq!(NonTerminal(StructExpression), children[0].ast_type);
+        let initializers = &children[0].children.as_ref().unwrap()[1..];
+        assert_eq!(2, initializers.len());
+        assert!(initializers
+            .iter()
+            .all(|x| x.ast_type == NonTerminal(FieldInitializer)));
+
+        // `a { }` is a block in the condition
+        let ast = parse_expression_source("if a { } else { }");
+        let if_children = ast.children.as_ref().unwrap()[0].children.as_ref().unwrap();
+        assert_eq!(
+            Terminal,
+            if_children[0].children.as_ref().unwrap()[0].ast_type
+        );
+        assert_eq!(NonTerminal(CompoundExpression), if_children[1].ast_type);
+    }
+
+    #[test]
+    fn parse_enum_expression_test() {
+        let ast = parse_expression_source(
+            "match Shape::Circle(1) { Shape::Circle(r) => r, Shape::Rect { w, h: 0 } => w, Shape::Empty => 0 }",
+        );
+        let match_children = ast.children.as_ref().unwrap()[0].children.as_ref().unwrap();
+        let scrutinee = match_children[0].children.as_ref().unwrap();
+        assert_eq!(NonTerminal(PathExpression), scrutinee[0].ast_type);
+        assert_eq!(NonTerminal(Args), scrutinee[1].ast_type);
+
+        let patterns = match_children[1..]
+            .iter()
+            .map(|x| &x.children.as_ref().unwrap()[0])
+            .map(|x| x.children.as_ref().unwrap())
+            .collect::<Vec<_>>();
+        assert!(patterns
+            .iter()
+            .all(|x| x[0].ast_type == NonTerminal(PathExpression)));
+        assert_eq!(NonTerminal(TuplePattern), patterns[0][1].ast_type);
+        assert_eq!(NonTerminal(StructPattern), patterns[1][1].ast_type);
+        let field_lengths = patterns[1][1]
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|x| x.children.as_ref().unwrap().len())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2], field_lengths);
+        assert_eq!(1, patterns[2].len());
+
+        let ast = parse_expression_source("Shape::Rect { w: 1, h: 2 }");
+        let literal = &ast.children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(StructExpression), literal.ast_type);
+        assert_eq!(
+            NonTerminal(PathExpression),
+            literal.children.as_ref().unwrap()[0].ast_type
+        );
+    }
+
+    #[test]
+    fn parse_function_expression_without_parameters_test() {
+        let ast = parse_expression_source("() => { }");
+        let func = &ast.children.as_ref().unwrap()[0];
+        let children = func.children.as_ref().unwrap();
+        assert_eq!(2, children.len());
+        assert!(children[0].children.as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_template_literal_test() {
+        let ast = parse_expression_source("`a${x}b${`c${y}`}d`");
+        let template = &ast.children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(TemplateLiteral), template.ast_type);
+        let children = template.children.as_ref().unwrap();
+        let texts = children
+            .iter()
+            .map(|x| x.token.as_ref().map(|x| x.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some("`a${".to_string()),
+                None,
+                Some("}b${".to_string()),
+                None,
+                Some("}d`".to_string())
+            ],
+            texts
+        );
+        let nested = &children[3].children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(TemplateLiteral), nested.ast_type);
+
+        let ast = parse_expression_source("`a`");
+        assert_eq!(Terminal, ast.children.as_ref().unwrap()[0].ast_type);
+    }
+}
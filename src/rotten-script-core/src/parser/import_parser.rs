@@ -6,9 +6,25 @@ use super::{
 };
 
 impl<'a> Parser<'a> {
+    // NamedImportDeclaration = "import" , ImportSpecifiers , "from" , (DoubleQuotesString | SingleQuotesString);
     #[allow(clippy::unnecessary_wraps)]
     fn parse_named_import_declaration(&mut self) -> Result<Ast, ParseError> {
         self.tokens.next();
+        let mut asts = self.parse_import_specifiers();
+        self.tokens
+            .consume_reserved(ReservedWord::From)
+            .handle_consume(self);
+        self.parse_module_specifier(&mut asts);
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::NamedImportDeclaration,
+            asts,
+        ))
+    }
+
+    // ImportSpecifiers = "{" , ImportSpecifier , { "," , ImportSpecifier } , "}";
+    // ImportSpecifier = Identifier , [ "as" , Identifier ];
+    // An ImportSpecifier without `as` is the leaf of the identifier.
+    fn parse_import_specifiers(&mut self) -> Vec<Ast> {
         self.tokens
             .consume_reserved(ReservedWord::LeftCurly)
             .handle_consume(self);
@@ -17,31 +33,43 @@ impl<'a> Parser<'a> {
         loop {
             match self.tokens.look_ahead(1) {
                 Some(TokenBase::Identifier(_)) => {
-                    let next_token = self.tokens.next_token().unwrap();
-                    asts.push(Ast::new_leaf(next_token));
+                    let name = Ast::new_leaf(self.tokens.next_token().unwrap());
+                    if let Some(TokenBase::Reserved(ReservedWord::As)) = self.tokens.look_ahead(1) {
+                        self.tokens.next();
+                        if let Some(TokenBase::Identifier(_)) = self.tokens.look_ahead(1) {
+                            let alias = Ast::new_leaf(self.tokens.next_token().unwrap());
+                            asts.push(Ast::new_node_with_leaves(
+                                NonTerminal::ImportSpecifier,
+                                vec![name, alias],
+                            ));
+                        } else {
+                            self.handle_expected_actually_error(
+                                self.tokens.nth(1),
+                                vec![TokenBase::default_identifier()],
+                                self.tokens.peek_token(),
+                            );
+                            asts.push(name);
+                        }
+                    } else {
+                        asts.push(name);
+                    }
                     match self.tokens.look_ahead(1) {
-                        Some(TokenBase::Reserved(r))
-                            if r == ReservedWord::Comma || r == ReservedWord::RightCurly =>
-                        {
-                            match r {
-                                ReservedWord::Comma => {
-                                    self.tokens.next();
-                                }
-                                ReservedWord::RightCurly => {
-                                    self.tokens.next();
-                                    break;
-                                }
-                                _ => panic!(),
-                            }
+                        Some(TokenBase::Reserved(ReservedWord::Comma)) => {
+                            self.tokens.next();
+                        }
+                        Some(TokenBase::Reserved(ReservedWord::RightCurly)) => {
+                            self.tokens.next();
+                            break;
                         }
                         Some(_) | None => {
                             self.handle_expected_actually_error(
                                 self.tokens.nth(1),
                                 vec![
+                                    TokenBase::Reserved(ReservedWord::As),
                                     TokenBase::Reserved(ReservedWord::Comma),
                                     TokenBase::Reserved(ReservedWord::RightCurly),
                                 ],
-                                self.tokens.peek_token().unwrap(),
+                                self.tokens.peek_token(),
                             );
                         }
                     }
@@ -50,29 +78,26 @@ impl<'a> Parser<'a> {
                     self.handle_expected_actually_error(
                         self.tokens.nth(1),
                         vec![TokenBase::default_identifier()],
-                        self.tokens.peek_token().unwrap(),
+                        self.tokens.peek_token(),
                     );
                     break;
                 }
             }
         }
-        self.tokens
-            .consume_reserved(ReservedWord::From)
-            .handle_consume(self);
+        asts
+    }
 
+    // (DoubleQuotesString | SingleQuotesString)
+    fn parse_module_specifier(&mut self, asts: &mut Vec<Ast>) {
         if let Some(TokenBase::String(_)) = self.tokens.look_ahead(1) {
             asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
         } else {
             self.handle_expected_actually_error(
                 self.tokens.nth(1),
                 vec![TokenBase::default_string()],
-                self.tokens.peek_token().unwrap(),
+                self.tokens.peek_token(),
             );
         }
-        Ok(Ast::new_node_with_leaves(
-            NonTerminal::NamedImportDeclaration,
-            asts,
-        ))
     }
 
     // DefaultImportDeclaration = "import" , Identifier , "from" , (DoubleQuotesString | SingleQuotesString);
@@ -86,29 +111,50 @@ impl<'a> Parser<'a> {
             self.handle_expected_actually_error(
                 self.tokens.nth(1),
                 vec![TokenBase::default_identifier()],
-                self.tokens.peek_token().unwrap(),
+                self.tokens.peek_token(),
             );
         }
         self.tokens
             .consume_reserved(ReservedWord::From)
             .handle_consume(self);
+        self.parse_module_specifier(&mut asts);
+        Ok(Ast::new_node_with_leaves(
+            NonTerminal::DefaultImportDeclaration,
+            asts,
+        ))
+    }
 
-        if let Some(TokenBase::String(_)) = self.tokens.look_ahead(1) {
+    // NamespaceImportDeclaration = "import" , "*" , "as" , Identifier , "from" , (DoubleQuotesString | SingleQuotesString);
+    #[allow(clippy::unnecessary_wraps)]
+    fn parse_namespace_import_declaration(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        self.tokens
+            .consume_reserved(ReservedWord::Mult)
+            .handle_consume(self);
+        self.tokens
+            .consume_reserved(ReservedWord::As)
+            .handle_consume(self);
+        let mut asts = Vec::new();
+        if let Some(TokenBase::Identifier(_)) = self.tokens.look_ahead(1) {
             asts.push(Ast::new_leaf(self.tokens.next_token().unwrap()));
         } else {
             self.handle_expected_actually_error(
                 self.tokens.nth(1),
-                vec![TokenBase::default_string()],
-                self.tokens.peek_token().unwrap(),
+                vec![TokenBase::default_identifier()],
+                self.tokens.peek_token(),
             );
         }
+        self.tokens
+            .consume_reserved(ReservedWord::From)
+            .handle_consume(self);
+        self.parse_module_specifier(&mut asts);
         Ok(Ast::new_node_with_leaves(
-            NonTerminal::DefaultImportDeclaration,
+            NonTerminal::NamespaceImportDeclaration,
             asts,
         ))
     }
 
-    // ImportDeclaration = (NamedImportDeclaration | DefaultImportDeclaration) , ";";
+    // ImportDeclaration = (NamedImportDeclaration | DefaultImportDeclaration | NamespaceImportDeclaration) , ";";
     pub fn parse_import_declaration(&mut self) -> Result<Ast, ParseError> {
         let result = match self.tokens.look_ahead(2) {
             Some(TokenBase::Reserved(ReservedWord::LeftCurly)) => Ast::new_node_with_leaves(
@@ -119,14 +165,19 @@ impl<'a> Parser<'a> {
                 NonTerminal::ImportDeclaration,
                 vec![self.parse_default_import_declaration()?],
             ),
+            Some(TokenBase::Reserved(ReservedWord::Mult)) => Ast::new_node_with_leaves(
+                NonTerminal::ImportDeclaration,
+                vec![self.parse_namespace_import_declaration()?],
+            ),
             Some(_) | None => {
                 self.handle_expected_actually_error(
                     self.tokens.nth(2),
                     vec![
                         TokenBase::Reserved(ReservedWord::LeftCurly),
+                        TokenBase::Reserved(ReservedWord::Mult),
                         TokenBase::default_identifier(),
                     ],
-                    self.tokens.nth(1).unwrap(),
+                    self.tokens.nth(1),
                 );
                 Ast::new_leaf(self.tokens.next_token().unwrap())
             }
@@ -136,6 +187,43 @@ impl<'a> Parser<'a> {
             .handle_consume(self);
         Ok(result)
     }
+
+    // ReExportDeclaration = "export" , ImportSpecifiers , "from" , (DoubleQuotesString | SingleQuotesString) , ";";
+    // ReExportAllDeclaration = "export" , "*" , "from" , (DoubleQuotesString | SingleQuotesString) , ";";
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn parse_re_export_declaration(&mut self) -> Result<Ast, ParseError> {
+        self.tokens.next();
+        let (non_terminal, mut asts) =
+            if let Some(TokenBase::Reserved(ReservedWord::Mult)) = self.tokens.look_ahead(1) {
+                self.tokens.next();
+                (NonTerminal::ReExportAllDeclaration, Vec::new())
+            } else {
+                (
+                    NonTerminal::ReExportDeclaration,
+                    self.parse_import_specifiers(),
+                )
+            };
+        self.tokens
+            .consume_reserved(ReservedWord::From)
+            .handle_consume(self);
+        self.parse_module_specifier(&mut asts);
+        self.tokens
+            .consume_reserved(ReservedWord::SemiColon)
+            .handle_consume(self);
+        Ok(Ast::new_node_with_leaves(non_terminal, asts))
+    }
+
+    /// Returns true if the next tokens are `export {` or `export *`.
+    pub fn is_re_export_declaration(&self) -> bool {
+        matches!(
+            (self.tokens.look_ahead(1), self.tokens.look_ahead(2)),
+            (
+                Some(TokenBase::Reserved(ReservedWord::Export)),
+                Some(TokenBase::Reserved(ReservedWord::LeftCurly))
+                    | Some(TokenBase::Reserved(ReservedWord::Mult))
+            )
+        )
+    }
 }
 
 #[cfg(test)]
@@ -199,6 +287,100 @@ mod tests {
     #[test]
     fn parse_import_declaration_test() {}
 
+    #[test]
+    fn parse_import_specifier_test() {
+        let add = Identifier("add".to_string());
+        let plus = Identifier("plus".to_string());
+        let sub = Identifier("sub".to_string());
+        let tokens = vec![
+            Reserved(Import),
+            Reserved(LeftCurly),
+            add.clone(),
+            Reserved(As),
+            plus.clone(),
+            Reserved(Comma),
+            sub.clone(),
+            Reserved(RightCurly),
+            Reserved(From),
+            String("./math".to_string()),
+            Reserved(SemiColon),
+        ];
+        let token_list = to_token_list(&tokens);
+        let mut token_stack = TokenStack::new(&token_list);
+        let mut parser = Parser::new(&mut token_stack);
+        let ast = parser.parse_import_declaration().unwrap();
+        let named = &ast.children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(NamedImportDeclaration), named.ast_type);
+        let children = named.children.as_ref().unwrap();
+        assert_eq!(3, children.len());
+        assert_eq!(NonTerminal(ImportSpecifier), children[0].ast_type);
+        let specifier = children[0].children.as_ref().unwrap();
+        assert_eq!(add, specifier[0].token.clone().unwrap());
+        assert_eq!(plus, specifier[1].token.clone().unwrap());
+        assert_eq!(sub, children[1].token.clone().unwrap());
+    }
+
+    #[test]
+    fn parse_namespace_import_declaration_test() {
+        let math = Identifier("math".to_string());
+        let tokens = vec![
+            Reserved(Import),
+            Reserved(Mult),
+            Reserved(As),
+            math.clone(),
+            Reserved(From),
+            String("./math".to_string()),
+            Reserved(SemiColon),
+        ];
+        let token_list = to_token_list(&tokens);
+        let mut token_stack = TokenStack::new(&token_list);
+        let mut parser = Parser::new(&mut token_stack);
+        let ast = parser.parse_import_declaration().unwrap();
+        let namespace = &ast.children.as_ref().unwrap()[0];
+        assert_eq!(NonTerminal(NamespaceImportDeclaration), namespace.ast_type);
+        let children = namespace.children.as_ref().unwrap();
+        assert_eq!(count_without_reserved_token_base(&tokens), children.len());
+        assert_eq!(math, children[0].token.clone().unwrap());
+    }
+
+    #[test]
+    fn parse_re_export_declaration_test() {
+        let math = String("./math".to_string());
+        let tokens = vec![
+            Reserved(Export),
+            Reserved(LeftCurly),
+            Identifier("add".to_string()),
+            Reserved(As),
+            Identifier("plus".to_string()),
+            Reserved(RightCurly),
+            Reserved(From),
+            math.clone(),
+            Reserved(SemiColon),
+            Reserved(Export),
+            Reserved(Mult),
+            Reserved(From),
+            math.clone(),
+            Reserved(SemiColon),
+        ];
+        let token_list = to_token_list(&tokens);
+        let mut token_stack = TokenStack::new(&token_list);
+        let mut parser = Parser::new(&mut token_stack);
+        assert!(parser.is_re_export_declaration());
+        let ast = parser.parse_re_export_declaration().unwrap();
+        assert_eq!(NonTerminal(ReExportDeclaration), ast.ast_type);
+        let children = ast.children.as_ref().unwrap();
+        assert_eq!(NonTerminal(ImportSpecifier), children[0].ast_type);
+        assert_eq!(math, children[1].token.clone().unwrap());
+
+        assert!(parser.is_re_export_declaration());
+        let ast = parser.parse_re_export_declaration().unwrap();
+        assert_eq!(NonTerminal(ReExportAllDeclaration), ast.ast_type);
+        let children = ast.children.as_ref().unwrap();
+        assert_eq!(1, children.len());
+        assert_eq!(math, children[0].token.clone().unwrap());
+        assert!(!parser.tokens.has_next());
+    }
+
     fn count_without_reserved_token_base(tokens: &[crate::lexer::token::TokenBase]) -> usize {
         tokens.iter().filter(|x| !matches!(x, Reserved(_))).count()
     }
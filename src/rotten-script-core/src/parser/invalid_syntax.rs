@@ -2,9 +2,12 @@ use std::{collections::HashSet, fmt::Display};
 
 use colored::Colorize;
 
-use crate::lexer::{
-    reserved_word::ReservedWord,
-    token::{Token, TokenBase, TokenPosition},
+use crate::{
+    diagnostic::Diagnostic,
+    lexer::{
+        reserved_word::ReservedWord,
+        token::{TemplatePart, Token, TokenBase, TokenPosition},
+    },
 };
 
 #[derive(Debug)]
@@ -35,6 +38,39 @@ impl InvalidSyntax {
     }
 }
 
+impl InvalidSyntaxType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            InvalidSyntaxType::ExpectedNext(_) => "E0101",
+            InvalidSyntaxType::UnexpectedEof => "E0102",
+            InvalidSyntaxType::ExponentialError() => "E0103",
+        }
+    }
+
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            InvalidSyntaxType::ExponentialError() => {
+                Some("parenthesize the unary expression, e.g. `(-1) ** 2` instead of `-1 ** 2`")
+            }
+            InvalidSyntaxType::ExpectedNext(_) | InvalidSyntaxType::UnexpectedEof => None,
+        }
+    }
+}
+
+impl From<&InvalidSyntax> for Diagnostic {
+    fn from(invalid_syntax: &InvalidSyntax) -> Self {
+        let diagnostic = Diagnostic::error(
+            invalid_syntax.invalid_syntax_type.code(),
+            invalid_syntax.invalid_syntax_type.to_string(),
+        )
+        .with_span(&invalid_syntax.position);
+        match invalid_syntax.invalid_syntax_type.help() {
+            Some(help) => diagnostic.with_help(help),
+            None => diagnostic,
+        }
+    }
+}
+
 impl Display for InvalidSyntax {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -64,25 +100,32 @@ impl Display for InvalidSyntaxType {
                 write!(f, "unexpected EOF")
             }
             InvalidSyntaxType::ExponentialError() => {
-                write!(f, "cannot allow exponential operator (**) after the unary operated expression (e.g. +1 ** 2), 
-                you need to use parenthesized unary operator (e.g. (+1) ** 2)")
+                write!(
+                    f,
+                    "unary operator cannot be used before `**` without parentheses"
+                )
             }
         }
     }
 }
 
 fn token_base_array_to_string(arr: &[TokenBase]) -> String {
+    let mut shown = HashSet::new();
     arr.iter()
         .map(|x| match x {
             TokenBase::String(_)
+            | TokenBase::Template(TemplatePart::NoSubstitution, _)
+            | TokenBase::Template(TemplatePart::Head, _)
             | TokenBase::Number(_)
             | TokenBase::Reserved(ReservedWord::True)
             | TokenBase::Reserved(ReservedWord::False) => "literal".to_string(),
+            // the end of a substitution
+            TokenBase::Template(..) => "`}`".to_string(),
             TokenBase::Reserved(r) => format!("`{}`", r),
             TokenBase::Identifier(_) => "identifier".to_string(),
         })
-        .collect::<HashSet<_>>()
-        .into_iter()
+        // keeps the order of `arr`
+        .filter(|x| shown.insert(x.clone()))
         .collect::<Vec<_>>()
         .join(", ")
 }
@@ -8,12 +8,18 @@ pub enum NonTerminal {
     DeclarationBody,
     Expression,
     FunctionExpression,
+    ParameterList,
+    Parameter,
     CompoundExpression,
     Args,
     ExpressionStatement,
     NamedImportDeclaration,
     DefaultImportDeclaration,
     ImportDeclaration,
+    ImportSpecifier,
+    NamespaceImportDeclaration,
+    ReExportDeclaration,
+    ReExportAllDeclaration,
     PrimaryExpression,
     ParenthesizedExpression,
     UnaryExpression,
@@ -29,4 +35,26 @@ pub enum NonTerminal {
     LogicalAndExpression,
     LogicalOrExpression,
     AssignmentStatement,
+    IfExpression,
+    MatchExpression,
+    MatchArm,
+    MatchGuard,
+    Pattern,
+    ForStatement,
+    RangeExpression,
+    WhileStatement,
+    ExportableTypeDeclaration,
+    StructDeclaration,
+    FieldDeclaration,
+    StructExpression,
+    FieldInitializer,
+    EnumDeclaration,
+    EnumVariant,
+    TupleFields,
+    StructFields,
+    PathExpression,
+    TuplePattern,
+    StructPattern,
+    FieldPattern,
+    TemplateLiteral,
 }
@@ -1,5 +1,7 @@
 use std::{error::Error, fmt::Display, write};
 
+use crate::diagnostic::Diagnostic;
+
 use super::invalid_syntax::{InvalidSyntax, InvalidSyntaxType};
 
 #[derive(Debug)]
@@ -44,19 +46,24 @@ impl ParseError2 {
     pub fn add_error(&mut self, invalid_syntax: InvalidSyntax) {
         self.errors.push(invalid_syntax);
     }
+
+    /// Returns the errors to report. The errors after the first unexpected EOF are omitted.
+    fn get_reported_errors(&self) -> impl Iterator<Item = &InvalidSyntax> {
+        let eof = self
+            .errors
+            .iter()
+            .position(|x| matches!(x.get_type(), InvalidSyntaxType::UnexpectedEof));
+        self.errors[..eof.map_or(self.errors.len(), |x| x + 1)].iter()
+    }
+
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        self.get_reported_errors().map(Diagnostic::from).collect()
+    }
 }
 
 impl Display for ParseError2 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut has_eof_error = false;
-        for error in &self.errors {
-            if has_eof_error {
-                continue;
-            }
-            if let InvalidSyntaxType::UnexpectedEof = error.get_type() {
-                has_eof_error = true;
-            }
-
+        for error in self.get_reported_errors() {
             writeln!(f, "{}", error)?;
         }
         Ok(())
@@ -3,10 +3,7 @@ use crate::lexer::{
     token::{Token, TokenBase},
 };
 
-use super::{
-    invalid_syntax::{ExpectedActuallyTokenPair, InvalidSyntax, InvalidSyntaxType},
-    parse_error::ParseError,
-};
+use super::invalid_syntax::{ExpectedActuallyTokenPair, InvalidSyntax, InvalidSyntaxType};
 // FIXME: None also indicates lexing error, this will fix use Token instead of TokenBase.
 pub struct TokenStack<'a> {
     tokens: &'a [Token],
@@ -83,12 +80,18 @@ impl<'a> TokenStack<'a> {
         self.current.clone()
     }
 
+    pub fn last_token(&self) -> Option<Token> {
+        self.tokens.last().cloned()
+    }
+
+    #[allow(clippy::result_large_err)]
     pub fn consume_reserved(&mut self, reserved: ReservedWord) -> Result<(), InvalidSyntax> {
         self.scan_reserved(reserved)?;
         self.next();
         Ok(())
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn scan_reserved(&self, reserved: ReservedWord) -> Result<(), InvalidSyntax> {
         if let Some(next) = self.nth(1) {
             match next.get_token() {
@@ -114,7 +117,7 @@ impl<'a> TokenStack<'a> {
                 )),
             }
         } else {
-            let tk = self.peek_token().unwrap();
+            let tk = self.peek_token().or_else(|| self.last_token()).unwrap();
             Err(InvalidSyntax::new(
                 tk.get_token_position(),
                 InvalidSyntaxType::UnexpectedEof,
@@ -122,8 +125,50 @@ impl<'a> TokenStack<'a> {
         }
     }
 
-    pub fn skip_reserved_until(&mut self, _reserved: ReservedWord) -> Result<(), InvalidSyntax> {
-        todo!()
+    /// Returns the number of the blocks (`{ ... }`) opened by the tokens after the token at `start`
+    /// up to the current one and not closed yet, e.g. 1 after `match a { 1`.
+    pub fn count_open_blocks(&self, start: i32) -> usize {
+        if self.index <= start {
+            return 0;
+        }
+        self.tokens[(start + 1) as usize..=self.index as usize]
+            .iter()
+            .fold(0, |depth, x| match x.get_token() {
+                Some(TokenBase::Reserved(ReservedWord::LeftCurly)) => depth + 1,
+                Some(TokenBase::Reserved(ReservedWord::RightCurly)) => depth.saturating_sub(1),
+                _ => depth,
+            })
+    }
+
+    /// Skips the tokens until the next one is any of `reserved` outside of the `depth` blocks
+    /// (`{ ... }`) already opened and the blocks started by the skipped tokens.
+    /// Returns an error at EOF.
+    #[allow(clippy::result_large_err)]
+    pub fn skip_reserved_until(
+        &mut self,
+        reserved: &[ReservedWord],
+        mut depth: usize,
+    ) -> Result<(), InvalidSyntax> {
+        loop {
+            match self.look_ahead(1) {
+                Some(TokenBase::Reserved(r)) if depth == 0 && reserved.contains(&r) => {
+                    return Ok(());
+                }
+                Some(TokenBase::Reserved(ReservedWord::LeftCurly)) => depth += 1,
+                Some(TokenBase::Reserved(ReservedWord::RightCurly)) if depth > 0 => depth -= 1,
+                Some(_) => {}
+                // an invalid token
+                None if self.has_next() => {}
+                None => {
+                    let tk = self.peek_token().or_else(|| self.last_token()).unwrap();
+                    return Err(InvalidSyntax::new(
+                        tk.get_token_position(),
+                        InvalidSyntaxType::UnexpectedEof,
+                    ));
+                }
+            }
+            self.next();
+        }
     }
 }
 
@@ -201,6 +246,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_skip_reserved_until() {
+        // { a { } ; } ;
+        let tokens = [
+            create_token_data(TokenBase::Reserved(ReservedWord::LeftCurly)),
+            create_token_data(TokenBase::Identifier("a".to_string())),
+            create_token_data(TokenBase::Reserved(ReservedWord::LeftCurly)),
+            create_token_data(TokenBase::Reserved(ReservedWord::RightCurly)),
+            create_token_data(TokenBase::Reserved(ReservedWord::SemiColon)),
+            create_token_data(TokenBase::Reserved(ReservedWord::RightCurly)),
+            create_token_data(TokenBase::Reserved(ReservedWord::SemiColon)),
+        ];
+
+        let mut token_stack = TokenStack::new(&tokens);
+        token_stack.next();
+        token_stack.next();
+        let depth = token_stack.count_open_blocks(-1);
+        assert_eq!(1, depth);
+        token_stack
+            .skip_reserved_until(&[ReservedWord::SemiColon], depth)
+            .unwrap();
+        assert_eq!(5, token_stack.ind());
+        assert_eq!(0, token_stack.count_open_blocks(-1));
+        token_stack.next();
+        assert!(token_stack
+            .skip_reserved_until(&[ReservedWord::SemiColon], 0)
+            .is_err());
+    }
+
     fn create_token_data(token_base: TokenBase) -> Token {
         Token::new(Ok(token_base), 0, 0, 0, Rc::new("".to_string()))
     }
@@ -1,28 +1,124 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+pub use project::FileMapCache;
 use project::Project;
 
-use crate::{builder::Builder, parser::ast::Ast};
+use crate::{
+    builder::{BuildOutput, Builder},
+    declaration_builder::DeclarationBuilder,
+    manifest::{Manifest, ModuleFormat},
+    parser::ast::Ast,
+    source_map::{SourceMap, SourceMapKind},
+};
 
 mod dependency_graph;
-mod file_map;
+pub(crate) mod file_map;
 mod func;
 pub(crate) mod func_info;
 mod member_map;
 pub(crate) mod project;
+pub mod semantic_error;
+mod type_checker;
 
-pub fn analyze(ast_list: Vec<(String, &'_ Ast)>) -> Project {
+pub fn analyze(ast_list: Vec<(String, &'_ Ast)>) -> Project<'_> {
     let mut tree = Project::new(ast_list);
     tree.analyze();
     tree
 }
 
+/// Analyzes the files of the project declared by the manifest but checks only the members of `paths`
+/// (e.g. the changed files), the files which import them and the members they refer to.
+/// The file maps of the files not in `paths` are reused from `cache`.
+pub fn analyze_project_files<'a>(
+    manifest: &Manifest,
+    ast_list: Vec<(String, &'a Ast)>,
+    paths: &HashSet<String>,
+    cache: &mut FileMapCache,
+) -> Project<'a> {
+    let mut tree = Project::new(ast_list);
+    tree.set_project_name(manifest.name.clone());
+    tree.analyze_files(paths, cache);
+    tree
+}
+
+/// Analyzes the files of the project declared by the manifest.
+pub fn analyze_project<'a>(manifest: &Manifest, ast_list: Vec<(String, &'a Ast)>) -> Project<'a> {
+    let mut tree = Project::new(ast_list);
+    tree.set_project_name(manifest.name.clone());
+    tree.analyze();
+    tree
+}
+
 impl Project<'_> {
-    pub fn call_builder(&self, is_debug: bool) -> HashMap<String, String> {
-        let mut builder = Builder::new(&self);
-        if is_debug {
+    /// Builds the JavaScript and the TypeScript declarations of each file.
+    pub fn call_builder(&self, is_debug: bool) -> HashMap<String, BuildOutput> {
+        self.build(&BuildOptions {
+            is_debug,
+            ..BuildOptions::default()
+        })
+    }
+
+    pub fn build(&self, options: &BuildOptions) -> HashMap<String, BuildOutput> {
+        let paths = self.file_maps.keys().cloned().collect();
+        self.build_files(options, &paths)
+    }
+
+    /// Same as `build` but builds only the files of `paths`.
+    pub fn build_files(
+        &self,
+        options: &BuildOptions,
+        paths: &HashSet<String>,
+    ) -> HashMap<String, BuildOutput> {
+        let mut builder = Builder::new(self);
+        if options.is_debug {
             builder.set_debug_mode();
         }
-        builder.unparse()
+        builder.set_module_format(options.module_format);
+        let mut declarations = DeclarationBuilder::new(self).unparse_files(paths);
+        builder
+            .unparse_files(paths)
+            .into_iter()
+            .map(|(path, (mut code, mappings))| {
+                let declaration = declarations.remove(&path).unwrap_or_default();
+                let mut source_map = None;
+                if options.source_map != SourceMapKind::None {
+                    let file_name = get_output_file_name(&path);
+                    let map = SourceMap::new(
+                        file_name.clone(),
+                        path.clone(),
+                        options.sources_content.get(&path).cloned(),
+                        &code,
+                        &mappings,
+                    );
+                    if options.source_map == SourceMapKind::Inline {
+                        code.push_str(&map.to_inline_comment());
+                    } else {
+                        code.push_str(&SourceMap::to_url_comment(&format!("{}.map", file_name)));
+                        source_map = Some(map);
+                    }
+                }
+                let output = BuildOutput {
+                    code,
+                    declaration,
+                    source_map,
+                };
+                (path, output)
+            })
+            .collect()
     }
 }
+
+#[derive(Default)]
+pub struct BuildOptions {
+    pub is_debug: bool,
+    pub source_map: SourceMapKind,
+    /// The source texts (key: path) embedded in the source maps as `sourcesContent`
+    pub sources_content: HashMap<String, String>,
+    pub module_format: ModuleFormat,
+}
+
+/// Returns the name of the JavaScript file built from the source, e.g. `main.js` for `./src/main.rots`.
+pub fn get_output_file_name(path: &str) -> String {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap();
+    format!("{}.js", file_name.trim_end_matches(".rots"))
+}
@@ -1,22 +1,117 @@
-use daggy::Dag;
+use std::collections::{HashMap, HashSet};
 
+use daggy::{Dag, NodeIndex, Walker};
+
+/// The files of the project and the imports between them.
+/// An edge goes from the importer to the imported file.
 pub struct DependencyGraph {
-    graph: Vec<Node>,
-    dag: Dag<u32, u32, u32>,
+    dag: Dag<String, ()>,
+    // key: path, value: node of the file
+    nodes: HashMap<String, NodeIndex>,
 }
 
-struct FullPath(String);
-
 impl DependencyGraph {
-    fn push(&mut self, value: FullPath) -> i32 {
-        let id = self.graph.len();
-        let node = Node(id as i32, value, Vec::new());
-        self.graph.push(node);
-        id as i32
+    pub fn new() -> Self {
+        Self {
+            dag: Dag::new(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn add_file(&mut self, path: &str) -> NodeIndex {
+        if let Some(node) = self.nodes.get(path) {
+            return *node;
+        }
+        let node = self.dag.add_node(path.to_string());
+        self.nodes.insert(path.to_string(), node);
+        node
+    }
+
+    /// Adds the import from `importer` to `imported`.
+    /// Returns the cycle (e.g. `[a, b, a]` for `a` -> `b` -> `a`) if the import makes one.
+    pub fn add_import(&mut self, importer: &str, imported: &str) -> Result<(), Vec<String>> {
+        let from = self.add_file(importer);
+        let to = self.add_file(imported);
+        if self.dag.find_edge(from, to).is_some() {
+            return Ok(());
+        }
+        if from == to {
+            return Err(vec![importer.to_string(), importer.to_string()]);
+        }
+        self.dag.add_edge(from, to, ()).map(|_| ()).map_err(|_| {
+            let mut cycle = vec![importer.to_string()];
+            self.find_path(to, from, &mut HashSet::new(), &mut cycle);
+            cycle
+        })
+    }
+
+    /// Returns the files which import any of `paths` directly or indirectly.
+    pub fn get_importers(&self, paths: &HashSet<String>) -> HashSet<String> {
+        let mut importers = HashSet::new();
+        let mut targets = paths
+            .iter()
+            .filter_map(|x| self.nodes.get(x).copied())
+            .collect::<Vec<_>>();
+        while let Some(target) = targets.pop() {
+            for (_, parent) in self.dag.parents(target).iter(&self.dag) {
+                if importers.insert(self.dag[parent].clone()) {
+                    targets.push(parent);
+                }
+            }
+        }
+        importers
     }
-    fn get(&self, id: i32) -> &Node {
-        &self.graph[id as usize]
+
+    /// Finds the imports from `from` to `to` and appends the files on the way to `path`.
+    fn find_path(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+        visited: &mut HashSet<NodeIndex>,
+        path: &mut Vec<String>,
+    ) -> bool {
+        path.push(self.dag[from].clone());
+        if from == to {
+            return true;
+        }
+        if visited.insert(from) {
+            for (_, child) in self.dag.children(from).iter(&self.dag) {
+                if self.find_path(child, to, visited, path) {
+                    return true;
+                }
+            }
+        }
+        path.pop();
+        false
     }
 }
 
-struct Node(i32, FullPath, Vec<i32>);
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::DependencyGraph;
+
+    #[test]
+    fn dependency_graph_test() {
+        let mut graph = DependencyGraph::new();
+        assert_eq!(Ok(()), graph.add_import("a", "b"));
+        assert_eq!(Ok(()), graph.add_import("b", "c"));
+        assert_eq!(Ok(()), graph.add_import("d", "c"));
+        assert_eq!(Ok(()), graph.add_import("a", "b"));
+        assert_eq!(
+            Err(vec!["c".to_string(), "a".into(), "b".into(), "c".into()]),
+            graph.add_import("c", "a")
+        );
+        assert_eq!(
+            Err(vec!["d".to_string(), "d".into()]),
+            graph.add_import("d", "d")
+        );
+
+        let paths = ["c".to_string()].iter().cloned().collect();
+        let mut importers = graph.get_importers(&paths).into_iter().collect::<Vec<_>>();
+        importers.sort();
+        assert_eq!(vec!["a", "b", "d"], importers);
+        assert!(graph.get_importers(&HashSet::new()).is_empty());
+    }
+}
@@ -1,27 +1,112 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use crate::parser::{ast::Ast, ast_type::AstType, non_terminal::NonTerminal};
+use crate::{
+    lexer::token::{Token, TokenBase},
+    parser::{ast::Ast, ast_type::AstType, non_terminal::NonTerminal},
+};
 
 use super::{
     func::Func,
-    func_info::{ExportedType, FuncInfo},
+    func_info::{Arguments, ExportedType, FuncInfo, MemberKind, Type, Variant, VariantFields},
+    semantic_error::{SemanticError, SemanticErrorType},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Import {
-    import_path: String,
-    import_member: Vec<String>,
+    /// The module specifier, e.g. `./sub/util`
+    pub import_path: String,
+    /// The path of the imported file, or `None` if the module is not a RottenScript file
+    /// (e.g. `./lib.mjs`, `fs`)
+    pub resolved_path: Option<String>,
+    pub clause: ImportClause,
+    // the token of the module specifier
+    pub path_token: Token,
+}
+
+/// What an import binds, or re-exports.
+#[derive(Debug, Clone)]
+pub enum ImportClause {
+    /// `import { a, b as c } from "...";`
+    Named(Vec<ImportMember>),
+    /// `import a from "...";`
+    Default(Token),
+    /// `import * as ns from "...";`
+    Namespace(Token),
+    /// `export { a, b as c } from "...";`
+    ReExport(Vec<ImportMember>),
+    /// `export * from "...";`
+    ReExportAll,
+}
+
+/// `a` or `a as b` of an import or a re-export.
+#[derive(Debug, Clone)]
+pub struct ImportMember {
+    /// The name exported by the module
+    pub name: Token,
+    /// `b` of `a as b`
+    pub alias: Option<Token>,
+}
+
+impl ImportMember {
+    fn new(ast: &Ast) -> Self {
+        match ast.children.as_deref() {
+            Some([name, alias]) => Self {
+                name: name.token.clone().unwrap(),
+                alias: alias.token.clone(),
+            },
+            _ => Self {
+                name: ast.token.clone().unwrap(),
+                alias: None,
+            },
+        }
+    }
+
+    /// Returns the name bound in the importer (or exported by the re-exporter).
+    pub fn get_local_name(&self) -> String {
+        self.alias.as_ref().unwrap_or(&self.name).to_string()
+    }
+}
+
+/// The member which a name exported by a file refers to.
+#[derive(Debug, PartialEq)]
+pub enum ExportedMember {
+    /// The member `name` declared in the file of `path`
+    Member { path: String, name: String },
+    /// A member of a JavaScript module, whose type is unknown
+    External,
 }
 
+#[allow(dead_code)]
 pub struct FileMap<'a> {
     pub imports: Vec<Import>,
     exports: Vec<String>,
     pub path: String,
     pub members: HashMap<String, Rc<FuncInfo>>,
+    /// The name tokens of the members exported by `export default`, in the source order
+    pub default_exports: Vec<Token>,
     pub functions: HashMap<String, Rc<Func<'a>>>,
     file_name: String,
     pub func_count: u32,
     pub ast: &'a Ast,
+    /// The errors found while collecting the members (e.g. duplicate declarations)
+    pub errors: Vec<SemanticError>,
+    // the indices of the declarations of `functions` in the translation unit
+    declarations: HashMap<String, usize>,
+}
+
+/// The parts of a file map which do not refer to the AST.
+/// They are kept for the unchanged files between the analyses (see `FileMapCache`).
+pub struct CachedFileMap {
+    imports: Vec<Import>,
+    exports: Vec<String>,
+    members: HashMap<String, Rc<FuncInfo>>,
+    default_exports: Vec<Token>,
+    func_count: u32,
+    errors: Vec<SemanticError>,
+    declarations: HashMap<String, usize>,
 }
 
 impl<'a> FileMap<'a> {
@@ -30,37 +115,65 @@ impl<'a> FileMap<'a> {
         let mut attributes = Vec::new();
         let mut imports = Vec::new();
         let mut exports = Vec::new();
+        let mut default_exports = Vec::new();
         let mut funcs = HashMap::new();
+        let mut errors = Vec::new();
+        let mut declarations = HashMap::new();
+        // the name tokens of the declared members
+        let mut names = HashMap::new();
 
+        let type_names = Self::collect_type_names(&path, translation_unit);
         let mut map = HashMap::new();
-        for ast in translation_unit.children.as_ref().unwrap() {
+        for (index, ast) in translation_unit
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .enumerate()
+        {
             if let AstType::NonTerminal(nt) = &ast.ast_type {
                 match nt {
-                    NonTerminal::ImportDeclaration => {
-                        match &ast.children.as_ref().unwrap()[0].ast_type {
-                            AstType::NonTerminal(nt)
-                                if nt == &NonTerminal::DefaultImportDeclaration =>
-                            {
-                                panic!("does not support default import currently")
+                    NonTerminal::ImportDeclaration
+                    | NonTerminal::ReExportDeclaration
+                    | NonTerminal::ReExportAllDeclaration => {
+                        let declaration = match nt {
+                            NonTerminal::ImportDeclaration => &ast.children.as_ref().unwrap()[0],
+                            _ => ast,
+                        };
+                        // the module specifier is the last child
+                        let children = declaration.children.as_ref().unwrap();
+                        let members = &children[..children.len() - 1];
+                        let clause = match &declaration.ast_type {
+                            AstType::NonTerminal(NonTerminal::NamedImportDeclaration) => {
+                                ImportClause::Named(members.iter().map(ImportMember::new).collect())
                             }
-                            AstType::NonTerminal(nt)
-                                if nt == &NonTerminal::NamedImportDeclaration =>
-                            {
-                                let named = &ast.children.as_ref().unwrap()[0];
-                                let named_children = named.children.as_ref().unwrap();
-                                let import_members = named_children[..named_children.len() - 1]
-                                    .iter()
-                                    .map(|x| x.token.clone().unwrap().to_string())
-                                    .collect::<Vec<_>>();
-                                let from_file =
-                                    named_children.last().unwrap().token.clone().unwrap();
-                                imports.push(Import {
-                                    import_member: import_members,
-                                    import_path: from_file.to_string(),
-                                });
+                            AstType::NonTerminal(NonTerminal::DefaultImportDeclaration) => {
+                                ImportClause::Default(members[0].token.clone().unwrap())
                             }
-                            AstType::NonTerminal(_) | AstType::Terminal => panic!(),
-                        }
+                            AstType::NonTerminal(NonTerminal::NamespaceImportDeclaration) => {
+                                ImportClause::Namespace(members[0].token.clone().unwrap())
+                            }
+                            AstType::NonTerminal(NonTerminal::ReExportDeclaration) => {
+                                ImportClause::ReExport(
+                                    members.iter().map(ImportMember::new).collect(),
+                                )
+                            }
+                            AstType::NonTerminal(NonTerminal::ReExportAllDeclaration) => {
+                                ImportClause::ReExportAll
+                            }
+                            AstType::NonTerminal(_) | AstType::Terminal => continue,
+                        };
+                        let path_token = children.last().unwrap().token.clone().unwrap();
+                        let from_file = match path_token.get_token() {
+                            Some(TokenBase::String(path)) => path.clone(),
+                            _ => continue,
+                        };
+                        imports.push(Import {
+                            clause,
+                            resolved_path: resolve_import_path(&path, &from_file),
+                            import_path: from_file,
+                            path_token,
+                        });
                     }
                     NonTerminal::Attribute => {
                         attributes.push(
@@ -72,37 +185,31 @@ impl<'a> FileMap<'a> {
                         );
                     }
                     NonTerminal::ExportableConstDeclaration => {
-                        let has_export;
-                        let has_default;
                         let ast_len = ast.children.as_ref().unwrap().len();
-                        if ast_len >= 2 {
-                            has_export = true;
-                            if ast_len == 3 {
-                                has_default = true;
-                            } else {
-                                has_default = false;
-                            }
-                        } else {
-                            has_export = false;
-                            has_default = false;
-                        }
-                        let const_declar_body = &ast.children.as_ref().unwrap()[if has_default {
-                            2
-                        } else if has_export {
-                            1
-                        } else {
-                            0
-                        }];
-
-                        let declar_body = &const_declar_body.children.as_ref().unwrap()[0];
-                        let func_name = declar_body.children.as_ref().unwrap()[0]
+                        let has_export = ast_len >= 2;
+                        let has_default = ast_len == 3;
+                        let declar_body = Self::get_declaration_body(ast);
+                        let name_token = declar_body.children.as_ref().unwrap()[0]
                             .token
                             .as_ref()
-                            .unwrap()
-                            .to_string();
+                            .unwrap();
+                        let func_name = name_token.to_string();
+                        if let Some(error) = Self::check_duplicate(
+                            &mut names,
+                            name_token,
+                            SemanticErrorType::DuplicateDefinition,
+                        ) {
+                            errors.push(error);
+                            attributes.clear();
+                            continue;
+                        }
+                        if has_default {
+                            default_exports.push(name_token.clone());
+                        }
                         if has_export {
                             exports.push(func_name.clone());
                         }
+                        let (args, return_type) = Self::extract_signature(declar_body, &type_names);
                         let func_info = FuncInfo::new(
                             func_name,
                             path.to_string(),
@@ -115,6 +222,7 @@ impl<'a> FileMap<'a> {
                             } else {
                                 ExportedType::None
                             },
+                            MemberKind::Function { args, return_type },
                         );
                         count += 1;
                         attributes.clear();
@@ -124,11 +232,81 @@ impl<'a> FileMap<'a> {
 
                         map.insert(func_info_rc.name.clone(), func_info_rc.clone());
                         funcs.insert(func_info_rc.clone().name.clone(), Rc::new(func));
+                        declarations.insert(func_info_rc.name.clone(), index);
                     }
-                    _ => panic!(),
+                    NonTerminal::ExportableTypeDeclaration => {
+                        let children = ast.children.as_ref().unwrap();
+                        let exported_type = match children.len() {
+                            3 => ExportedType::DefaultExport,
+                            2 => ExportedType::Export,
+                            _ => ExportedType::None,
+                        };
+                        let declaration = children.last().unwrap();
+                        let declaration_children = declaration.children.as_ref().unwrap();
+                        let name_token = declaration_children[0].token.as_ref().unwrap();
+                        let name = name_token.to_string();
+                        if let Some(error) = Self::check_duplicate(
+                            &mut names,
+                            name_token,
+                            SemanticErrorType::DuplicateDefinition,
+                        ) {
+                            errors.push(error);
+                            continue;
+                        }
+                        if children.len() == 3 {
+                            default_exports.push(name_token.clone());
+                        }
+                        if children.len() >= 2 {
+                            exports.push(name.clone());
+                        }
+                        let kind = if declaration.ast_type
+                            == AstType::NonTerminal(NonTerminal::EnumDeclaration)
+                        {
+                            let variants = &declaration_children[1..];
+                            errors.extend(Self::check_duplicate_names(
+                                variants,
+                                SemanticErrorType::DuplicateVariant,
+                            ));
+                            for fields in variants.iter().filter_map(|x| {
+                                x.children.as_ref().unwrap().get(1).filter(|x| {
+                                    x.ast_type == AstType::NonTerminal(NonTerminal::StructFields)
+                                })
+                            }) {
+                                errors.extend(Self::check_duplicate_names(
+                                    fields.children.as_ref().unwrap(),
+                                    SemanticErrorType::DuplicateField,
+                                ));
+                            }
+                            MemberKind::Enum {
+                                variants: Self::extract_variants(variants, &type_names),
+                            }
+                        } else {
+                            errors.extend(Self::check_duplicate_names(
+                                &declaration_children[1..],
+                                SemanticErrorType::DuplicateField,
+                            ));
+                            MemberKind::Struct {
+                                fields: Self::extract_annotated_identifiers(
+                                    &declaration_children[1..],
+                                    &type_names,
+                                ),
+                            }
+                        };
+                        let func_info = FuncInfo::new(
+                            name,
+                            path.to_string(),
+                            count + cumulative_func_count,
+                            attributes.clone(),
+                            exported_type,
+                            kind,
+                        );
+                        count += 1;
+                        attributes.clear();
+                        map.insert(func_info.name.clone(), Rc::new(func_info));
+                    }
+                    // the remains of a syntax error
+                    _ => {}
                 }
-            } else {
-                panic!()
             }
         }
         let file_name = Self::extract_file_name_from_full_path(path.clone());
@@ -138,14 +316,357 @@ impl<'a> FileMap<'a> {
             exports,
             path,
             members: map,
+            default_exports,
             file_name,
             func_count: count,
             ast: translation_unit,
+            errors,
+            declarations,
+        }
+    }
+
+    /// Returns the parts of the file map to be reused while the file is unchanged.
+    pub fn to_cache(&self) -> CachedFileMap {
+        CachedFileMap {
+            imports: self.imports.clone(),
+            exports: self.exports.clone(),
+            members: self.members.clone(),
+            default_exports: self.default_exports.clone(),
+            func_count: self.func_count,
+            errors: self.errors.clone(),
+            declarations: self.declarations.clone(),
+        }
+    }
+
+    /// Restores the file map of the unchanged file from the cache and its translation unit.
+    pub fn from_cache(path: String, cached: &CachedFileMap, translation_unit: &'a Ast) -> Self {
+        let children = translation_unit.children.as_ref().unwrap();
+        let functions = cached
+            .declarations
+            .iter()
+            .map(|(name, index)| {
+                let declar_body = Self::get_declaration_body(&children[*index]);
+                let func = Func::new(declar_body, cached.members[name].clone());
+                (name.clone(), Rc::new(func))
+            })
+            .collect();
+        let file_name = Self::extract_file_name_from_full_path(path.clone());
+        Self {
+            functions,
+            imports: cached.imports.clone(),
+            exports: cached.exports.clone(),
+            path,
+            members: cached.members.clone(),
+            default_exports: cached.default_exports.clone(),
+            file_name,
+            func_count: cached.func_count,
+            ast: translation_unit,
+            errors: cached.errors.clone(),
+            declarations: cached.declarations.clone(),
         }
     }
 
+    /// Returns the `DeclarationBody` of the `ExportableConstDeclaration`.
+    fn get_declaration_body(ast: &Ast) -> &Ast {
+        // the `ConstDeclaration` follows `export` and `default`
+        let const_declaration = ast.children.as_ref().unwrap().last().unwrap();
+        &const_declaration.children.as_ref().unwrap()[0]
+    }
+
+    /// Returns an error for each of `asts` (e.g. fields, variants) whose name, the first child,
+    /// is the same as the name of a former one.
+    fn check_duplicate_names(
+        asts: &[Ast],
+        error_type: fn(String) -> SemanticErrorType,
+    ) -> Vec<SemanticError> {
+        let mut names = HashMap::new();
+        asts.iter()
+            .filter_map(|x| x.children.as_ref()?.first()?.token.as_ref())
+            .filter_map(|x| Self::check_duplicate(&mut names, x, error_type))
+            .collect()
+    }
+
+    /// Records the name of `token` in `names`.
+    /// Returns an error labeled with the first declaration if the name is already recorded.
+    fn check_duplicate(
+        names: &mut HashMap<String, Token>,
+        token: &Token,
+        error_type: fn(String) -> SemanticErrorType,
+    ) -> Option<SemanticError> {
+        let name = token.to_string();
+        match names.get(&name) {
+            Some(first) => Some(
+                SemanticError::new(token.get_token_position(), error_type(name.clone()))
+                    .with_label(
+                        first.get_token_position(),
+                        format!("previous declaration of `{}` is here", name),
+                    ),
+            ),
+            None => {
+                names.insert(name, token.clone());
+                None
+            }
+        }
+    }
+
+    /// Returns true if the name is bound by an import of the file.
+    pub fn is_imported(&self, name: &str) -> bool {
+        self.get_import(name).is_some()
+    }
+
+    /// Returns true if the member declared in the file is exported by `export`.
+    pub fn is_exported(&self, name: &str) -> bool {
+        self.members
+            .get(name)
+            .is_some_and(|x| matches!(x.get_exported_type(), ExportedType::Export))
+    }
+
+    /// Returns the import which binds the name.
+    pub fn get_import(&self, name: &str) -> Option<&Import> {
+        self.imports.iter().find(|x| match &x.clause {
+            ImportClause::Named(members) => members.iter().any(|x| x.get_local_name() == name),
+            ImportClause::Default(token) | ImportClause::Namespace(token) => {
+                token.to_string() == name
+            }
+            ImportClause::ReExport(_) | ImportClause::ReExportAll => false,
+        })
+    }
+
+    /// Collects the names of the types declared in the file (e.g. `struct Point { ... }`, `enum Shape { ... }`).
+    fn collect_type_names(path: &str, translation_unit: &Ast) -> HashMap<String, Type> {
+        translation_unit
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            // the first declaration wins over the duplicates, which are reported by `new`
+            .rev()
+            .filter(|x| x.ast_type == AstType::NonTerminal(NonTerminal::ExportableTypeDeclaration))
+            .map(|x| {
+                let declaration = x.children.as_ref().unwrap().last().unwrap();
+                let name = declaration.children.as_ref().unwrap()[0]
+                    .token
+                    .as_ref()
+                    .unwrap()
+                    .to_string();
+                let full_path = format!("{}#{}", path, name);
+                let ty =
+                    if declaration.ast_type == AstType::NonTerminal(NonTerminal::EnumDeclaration) {
+                        Type::Enum(full_path)
+                    } else {
+                        Type::Struct(full_path)
+                    };
+                (name, ty)
+            })
+            .collect()
+    }
+
+    /// Resolves the type annotation with the types declared in the file.
+    /// Unknown type names are left as `any` here and reported by the type checker.
+    fn resolve_annotation(type_name: &Ast, type_names: &HashMap<String, Type>) -> Type {
+        let name = type_name.token.as_ref().unwrap().to_string();
+        Type::from_annotation(&name)
+            .or_else(|| type_names.get(&name).cloned())
+            .unwrap_or(Type::Any)
+    }
+
+    // Parameter = Identifier , ":" , Identifier;
+    // FieldDeclaration = Identifier , ":" , Identifier;
+    fn extract_annotated_identifiers(
+        asts: &[Ast],
+        type_names: &HashMap<String, Type>,
+    ) -> Arguments {
+        let arguments = asts
+            .iter()
+            .filter_map(|x| match x.children.as_ref().unwrap().as_slice() {
+                [name, type_name] => Some((
+                    name.token.as_ref().unwrap().to_string(),
+                    Self::resolve_annotation(type_name, type_names),
+                )),
+                _ => None,
+            })
+            .collect();
+        Arguments { arguments }
+    }
+
+    // EnumVariant = Identifier , [ TupleFields | StructFields ];
+    fn extract_variants(asts: &[Ast], type_names: &HashMap<String, Type>) -> Vec<Variant> {
+        asts.iter()
+            .map(|x| {
+                let children = x.children.as_ref().unwrap();
+                let name = children[0].token.as_ref().unwrap().to_string();
+                let fields = match children.get(1) {
+                    Some(fields)
+                        if fields.ast_type == AstType::NonTerminal(NonTerminal::TupleFields) =>
+                    {
+                        VariantFields::Tuple(
+                            fields
+                                .children
+                                .as_ref()
+                                .unwrap()
+                                .iter()
+                                .map(|x| Self::resolve_annotation(x, type_names))
+                                .collect(),
+                        )
+                    }
+                    Some(fields) => VariantFields::Struct(Self::extract_annotated_identifiers(
+                        fields.children.as_ref().unwrap(),
+                        type_names,
+                    )),
+                    None => VariantFields::Unit,
+                };
+                Variant { name, fields }
+            })
+            .collect()
+    }
+
+    /// Extracts the parameters and the annotated return type
+    /// when the declaration body is a function expression.
+    fn extract_signature(
+        declar_body: &Ast,
+        type_names: &HashMap<String, Type>,
+    ) -> (Arguments, Type) {
+        let void = Type::void();
+        let expr = declar_body.children.as_ref().unwrap().last().unwrap();
+        let func_expr = match expr.children.as_deref() {
+            Some([func_expr])
+                if func_expr.ast_type == AstType::NonTerminal(NonTerminal::FunctionExpression) =>
+            {
+                func_expr
+            }
+            _ => return (Arguments::default(), void),
+        };
+        let children = func_expr.children.as_ref().unwrap();
+        let arguments =
+            Self::extract_annotated_identifiers(children[0].children.as_ref().unwrap(), type_names);
+        let return_type = if children.len() == 3 {
+            Self::resolve_annotation(&children[1], type_names)
+        } else {
+            void
+        };
+        (arguments, return_type)
+    }
+
     fn extract_file_name_from_full_path(full_path: String) -> String {
-        let ind = full_path.rfind(|x| x == '/' || x == '\\').unwrap();
+        let ind = full_path.rfind(['/', '\\']).unwrap();
         full_path[ind + 1..].to_string()
     }
 }
+
+/// Resolves the name exported by the file of `path`, following `export { a as b } from "...";`
+/// and `export * from "...";`. Returns `None` if the file does not export the name.
+pub fn resolve_export(
+    file_maps: &HashMap<String, FileMap>,
+    path: &str,
+    name: &str,
+) -> Option<ExportedMember> {
+    resolve_export_rec(file_maps, path, name, &mut HashSet::new())
+}
+
+fn resolve_export_rec(
+    file_maps: &HashMap<String, FileMap>,
+    path: &str,
+    name: &str,
+    // (path, name) already searched; re-exports may make a cycle
+    visited: &mut HashSet<(String, String)>,
+) -> Option<ExportedMember> {
+    if !visited.insert((path.to_string(), name.to_string())) {
+        return None;
+    }
+    let file_map = file_maps.get(path)?;
+    if file_map.is_exported(name) {
+        return Some(ExportedMember::Member {
+            path: path.to_string(),
+            name: name.to_string(),
+        });
+    }
+    let mut has_external_star = false;
+    for import in &file_map.imports {
+        let target = import.resolved_path.as_deref();
+        match &import.clause {
+            ImportClause::ReExport(members) => {
+                let member = match members.iter().find(|x| x.get_local_name() == name) {
+                    Some(member) => member,
+                    None => continue,
+                };
+                return match target {
+                    Some(target) => {
+                        resolve_export_rec(file_maps, target, &member.name.to_string(), visited)
+                    }
+                    None => Some(ExportedMember::External),
+                };
+            }
+            ImportClause::ReExportAll => match target {
+                Some(target) => {
+                    if let Some(member) = resolve_export_rec(file_maps, target, name, visited) {
+                        return Some(member);
+                    }
+                }
+                None => has_external_star = true,
+            },
+            _ => {}
+        }
+    }
+    // `export * from "./lib.mjs";` may export any name
+    if has_external_star {
+        Some(ExportedMember::External)
+    } else {
+        None
+    }
+}
+
+/// Resolves the module specifier of an import to the path of the source file,
+/// e.g. `./sub/util` imported from `./src/main.rots` is `./src/sub/util.rots`.
+/// Returns `None` if the module is not a RottenScript file, i.e. the specifier is not relative
+/// (e.g. `fs`) or has another extension (e.g. `./lib.mjs`).
+pub fn resolve_import_path(importer: &str, specifier: &str) -> Option<String> {
+    if !specifier.starts_with("./") && !specifier.starts_with("../") {
+        return None;
+    }
+    let file_name = specifier.rsplit('/').next().unwrap();
+    if file_name.contains('.') && !file_name.ends_with(".rots") {
+        return None;
+    }
+    let mut segments = importer.split('/').collect::<Vec<_>>();
+    // the file name of the importer
+    segments.pop();
+    for segment in specifier.split('/') {
+        match segment {
+            "." | "" => {}
+            ".." if matches!(segments.last(), Some(x) if !matches!(*x, ".." | "." | "")) => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    let path = segments.join("/");
+    if path.ends_with(".rots") {
+        Some(path)
+    } else {
+        Some(format!("{}.rots", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_import_path;
+
+    #[test]
+    fn resolve_import_path_test() {
+        let cases = [
+            ("./src/main.rots", "./sub/util", Some("./src/sub/util.rots")),
+            ("./src/main.rots", "../lib/a.rots", Some("./lib/a.rots")),
+            ("./main.rots", "../a", Some("./../a.rots")),
+            ("/tmp/p/main.rots", "./a", Some("/tmp/p/a.rots")),
+            ("main.rots", "./a/./b", Some("a/b.rots")),
+            ("./main.rots", "./lib.mjs", None),
+            ("./main.rots", "fs", None),
+        ];
+        for (importer, specifier, expected) in cases.iter() {
+            assert_eq!(
+                expected.map(|x| x.to_string()),
+                resolve_import_path(importer, specifier)
+            );
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use crate::parser::ast::Ast;
 
-use super::func_info::FuncInfo;
+use super::func_info::{FuncInfo, Type};
 
 pub struct Func<'a> {
     ast: &'a Ast,
@@ -24,6 +24,15 @@ impl<'a> Func<'a> {
     pub fn get_func_info(&self) -> Rc<FuncInfo> {
         self.func_info.clone()
     }
+    pub fn get_tree(&self) -> Option<&AnalyzedFuncTree> {
+        self.tree.as_ref()
+    }
+    pub fn set_tree(&mut self, tree: AnalyzedFuncTree) {
+        self.tree = Some(tree);
+    }
 }
 
-pub struct AnalyzedFuncTree {}
+pub struct AnalyzedFuncTree {
+    /// The inferred type of the member (e.g. `(number, number) => number`)
+    pub ty: Type,
+}
@@ -1,11 +1,12 @@
+use std::fmt::Display;
+
 #[derive(Debug)]
 pub struct FuncInfo {
     pub name: String,
     pub full_path: String,
     pub file_name: String,
     exported_type: ExportedType,
-    args: Arguments,
-    return_type: Type,
+    kind: MemberKind,
     // uuid is a good choice for func_id?
     pub func_id: u32,
     pub is_entry: bool,
@@ -19,6 +20,7 @@ impl FuncInfo {
         id: u32,
         attributes: Vec<String>,
         exported_type: ExportedType,
+        kind: MemberKind,
     ) -> FuncInfo {
         let is_entry = attributes.iter().any(|x| x == &String::from("EntryPoint"));
         FuncInfo {
@@ -26,15 +28,40 @@ impl FuncInfo {
             full_path: format!("{}#{}", path, name),
             file_name: path,
             exported_type,
-            args: Arguments {
-                arguments: Vec::new(),
-            },
-            return_type: Type::Primitive(PrimitiveType::Void),
+            kind,
             func_id: id,
             is_entry,
             attributes,
         }
     }
+
+    pub fn get_exported_type(&self) -> &ExportedType {
+        &self.exported_type
+    }
+
+    pub fn get_kind(&self) -> &MemberKind {
+        &self.kind
+    }
+
+    /// Returns the fields if the member is a struct.
+    pub fn get_fields(&self) -> Option<&Arguments> {
+        match &self.kind {
+            MemberKind::Struct { fields } => Some(fields),
+            MemberKind::Function { .. } | MemberKind::Enum { .. } => None,
+        }
+    }
+
+    /// Returns the variants if the member is an enum.
+    pub fn get_variants(&self) -> Option<&[Variant]> {
+        match &self.kind {
+            MemberKind::Enum { variants } => Some(variants),
+            MemberKind::Function { .. } | MemberKind::Struct { .. } => None,
+        }
+    }
+
+    pub fn get_attributes(&self) -> &[String] {
+        &self.attributes
+    }
 }
 
 #[derive(Debug)]
@@ -45,18 +72,130 @@ pub enum ExportedType {
 }
 
 #[derive(Debug)]
-struct Arguments {
-    arguments: Vec<(String, Type)>,
+pub enum MemberKind {
+    /// `const name = ...;` (the signature is known only if the value is a function expression)
+    Function { args: Arguments, return_type: Type },
+    /// `struct Name { field: type, ... }`
+    Struct { fields: Arguments },
+    /// `enum Name { Variant, Variant(type, ...), Variant { field: type, ... }, ... }`
+    Enum { variants: Vec<Variant> },
 }
-#[derive(Debug)]
-enum Type {
+
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub name: String,
+    pub fields: VariantFields,
+}
+
+#[derive(Debug, Clone)]
+pub enum VariantFields {
+    Unit,
+    Tuple(Vec<Type>),
+    Struct(Arguments),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Arguments {
+    pub arguments: Vec<(String, Type)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
     Primitive(PrimitiveType),
-    Object,
+    Function(Vec<Type>, Box<Type>),
+    /// A struct declared by `struct Name { ... }`, identified by its full path (e.g. `./a.rots#Point`).
+    /// Structs with the same fields are still different types.
+    Struct(String),
+    /// An enum declared by `enum Name { ... }`, identified by its full path like `Struct`.
+    Enum(String),
+    /// The type of values which come from outside of RottenScript (e.g. `console`).
+    /// It is compatible with every other type.
+    Any,
 }
-#[derive(Debug)]
-enum PrimitiveType {
+
+impl Type {
+    /// Resolves a type annotation such as `number` in `(a: number) => { ... }`.
+    pub fn from_annotation(name: &str) -> Option<Type> {
+        match name {
+            "number" => Some(Type::Primitive(PrimitiveType::Number)),
+            "bigint" => Some(Type::Primitive(PrimitiveType::BigInt)),
+            "string" => Some(Type::Primitive(PrimitiveType::String)),
+            "boolean" => Some(Type::Primitive(PrimitiveType::Boolean)),
+            "void" => Some(Type::Primitive(PrimitiveType::Void)),
+            _ => None,
+        }
+    }
+
+    pub fn number() -> Type {
+        Type::Primitive(PrimitiveType::Number)
+    }
+
+    pub fn bigint() -> Type {
+        Type::Primitive(PrimitiveType::BigInt)
+    }
+
+    pub fn string() -> Type {
+        Type::Primitive(PrimitiveType::String)
+    }
+
+    pub fn boolean() -> Type {
+        Type::Primitive(PrimitiveType::Boolean)
+    }
+
+    pub fn void() -> Type {
+        Type::Primitive(PrimitiveType::Void)
+    }
+
+    /// Returns true if a value of type `other` can be used where `self` is expected.
+    pub fn accepts(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Function(args, ret), Type::Function(other_args, other_ret)) => {
+                args.len() == other_args.len()
+                    && args.iter().zip(other_args).all(|(x, y)| y.accepts(x))
+                    && ret.accepts(other_ret)
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Primitive(p) => write!(f, "{}", p),
+            Type::Function(args, ret) => {
+                let args = args.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                write!(f, "({}) => {}", args.join(", "), ret)
+            }
+            Type::Struct(full_path) | Type::Enum(full_path) => {
+                let name = full_path.rsplit('#').next().unwrap();
+                write!(f, "{}", name)
+            }
+            Type::Any => write!(f, "any"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrimitiveType {
     Number,
+    /// An integer of arbitrary precision such as `10n`, which cannot be mixed with `number`.
+    BigInt,
     String,
     Boolean,
     Void,
 }
+
+impl Display for PrimitiveType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            PrimitiveType::Number => "number",
+            PrimitiveType::BigInt => "bigint",
+            PrimitiveType::String => "string",
+            PrimitiveType::Boolean => "boolean",
+            PrimitiveType::Void => "void",
+        };
+        write!(f, "{}", text)
+    }
+}
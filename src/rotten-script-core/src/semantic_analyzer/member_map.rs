@@ -1,5 +1,6 @@
 use super::func_info::FuncInfo;
 use std::{collections::HashMap, fmt::Debug, ops::Index, rc::Rc};
+#[derive(Default)]
 pub struct MemberMap {
     // key: func_id, value: FuncInfo
     members: HashMap<u32, Rc<FuncInfo>>,
@@ -35,19 +36,7 @@ impl Debug for MemberMap {
     }
 }
 
-impl Default for MemberMap {
-    fn default() -> Self {
-        MemberMap {
-            members: HashMap::new(),
-            func_path_to_func_id_map: HashMap::new(),
-            func_ids_of_files: HashMap::new(),
-            count: 0,
-            entries: Vec::new(),
-        }
-    }
-}
-
-impl<'a> MemberMap {
+impl MemberMap {
     pub fn new() -> MemberMap {
         MemberMap::default()
     }
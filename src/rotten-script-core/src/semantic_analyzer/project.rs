@@ -1,12 +1,33 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use crate::parser::ast::Ast;
+use crate::{
+    diagnostic::{Diagnostic, Diagnostics},
+    parser::ast::Ast,
+};
 
 use super::{
-    dependency_graph::DependencyGraph, file_map::FileMap, func_info::FuncInfo,
+    dependency_graph::DependencyGraph,
+    file_map::{resolve_export, CachedFileMap, FileMap, ImportClause},
+    func::AnalyzedFuncTree,
+    func_info::{FuncInfo, Type},
     member_map::MemberMap,
+    semantic_error::{SemanticError, SemanticErrorType},
+    type_checker::TypeChecker,
 };
 
+/// The file maps kept between the analyses of a project (e.g. the rebuilds of `rotc watch`)
+/// so that only the changed files are collected again.
+#[derive(Default)]
+pub struct FileMapCache {
+    file_maps: HashMap<String, CachedFileMap>,
+    // the ids of the members are unique across the analyses
+    func_id_count: u32,
+}
+
+#[allow(dead_code)]
 pub struct Project<'a> {
     pub member_map: MemberMap,
     pub file_maps: HashMap<String, FileMap<'a>>,
@@ -15,6 +36,9 @@ pub struct Project<'a> {
     entry_point_id: Option<u32>,
     func_id_count: u32,
     project_name: String,
+    semantic_errors: Vec<SemanticError>,
+    // the files whose members are checked
+    checked_files: HashSet<String>,
 }
 
 impl<'a> Project<'a> {
@@ -27,55 +51,326 @@ impl<'a> Project<'a> {
             entry_point_id: None,
             func_id_count: 0,
             project_name: "".to_string(),
+            semantic_errors: Vec::new(),
+            checked_files: HashSet::new(),
         }
     }
 
     pub fn analyze(&mut self) {
+        self.collect_file_maps();
+        let import_errors = self.resolve_imports();
+        let entry_point_errors = self.search_entry_point();
+        let (member_types, errors) = TypeChecker::new(&self.file_maps).check_all();
+        self.set_types(member_types, errors);
+        self.add_errors(self.get_declaration_errors());
+        self.add_errors(import_errors);
+        self.add_errors(entry_point_errors);
+        self.checked_files = self.file_maps.keys().cloned().collect();
+    }
+
+    /// Analyzes the project but checks only the members of `paths` (e.g. the changed files),
+    /// the files which import them and the members they refer to.
+    /// The other members have no analyzed tree.
+    /// The file maps of the other files are restored from `cache`, so `paths` must contain every
+    /// file changed since the last analysis with the cache.
+    pub fn analyze_files(&mut self, paths: &HashSet<String>, cache: &mut FileMapCache) {
+        self.collect_cached_file_maps(paths, cache);
+        let import_errors = self.resolve_imports();
+        let entry_point_errors = self.search_entry_point();
+        let mut checked_files = self.get_importers(paths);
+        checked_files.extend(paths.iter().cloned());
+        let (member_types, errors) = TypeChecker::new(&self.file_maps).check_files(&checked_files);
+        self.set_types(member_types, errors);
+        self.add_errors(
+            self.get_declaration_errors()
+                .into_iter()
+                .chain(import_errors)
+                .filter(|x| checked_files.contains(x.get_position().path.as_str()))
+                .collect(),
+        );
+        self.add_errors(entry_point_errors);
+        self.checked_files = checked_files;
+    }
+
+    /// Returns the files checked by `analyze` or `analyze_files`.
+    pub fn get_checked_files(&self) -> &HashSet<String> {
+        &self.checked_files
+    }
+
+    fn collect_file_maps(&mut self) {
         for (path, tunit) in &self.ast_list.clone() {
-            let map = FileMap::new(path.clone(), &tunit, self.func_id_count);
-            map.members
-                .iter()
-                .for_each(|(_, f)| self.member_map.insert(f.clone()).unwrap());
+            let map = FileMap::new(path.clone(), tunit, self.func_id_count);
             self.func_id_count += map.func_count;
-            self.file_maps.insert(map.path.clone(), map);
+            self.insert_file_map(map);
         }
-        self.search_entry_point();
     }
 
-    fn search_entry_point(&mut self) {
-        let points = self.member_map.get_entrypoint_ids();
-        match points.len() {
-            0 => {}
-            1 => {
-                self.entry_point_id = Some(points[0]);
+    /// Same as `collect_file_maps` but collects only the files of `paths` and the files not in
+    /// `cache`, and restores the others from `cache`.
+    fn collect_cached_file_maps(&mut self, paths: &HashSet<String>, cache: &mut FileMapCache) {
+        let ast_list = self.ast_list.clone();
+        // the removed files
+        cache
+            .file_maps
+            .retain(|path, _| ast_list.iter().any(|(x, _)| x == path));
+        for (path, tunit) in ast_list {
+            let map = match cache.file_maps.get(&path) {
+                Some(cached) if !paths.contains(&path) => FileMap::from_cache(path, cached, tunit),
+                _ => {
+                    let map = FileMap::new(path.clone(), tunit, cache.func_id_count);
+                    cache.func_id_count += map.func_count;
+                    cache.file_maps.insert(path, map.to_cache());
+                    map
+                }
+            };
+            self.insert_file_map(map);
+        }
+        self.func_id_count = cache.func_id_count;
+    }
+
+    fn insert_file_map(&mut self, map: FileMap<'a>) {
+        map.members
+            .iter()
+            .for_each(|(_, f)| self.member_map.insert(f.clone()).unwrap());
+        self.file_maps.insert(map.path.clone(), map);
+    }
+
+    fn set_types(&mut self, member_types: HashMap<String, Type>, errors: Vec<SemanticError>) {
+        for map in self.file_maps.values_mut() {
+            for func in map.functions.values_mut() {
+                if let Some(ty) = member_types.get(&func.get_func_info().full_path) {
+                    Rc::get_mut(func)
+                        .expect("functions must not be shared before type checking")
+                        .set_tree(AnalyzedFuncTree { ty: ty.clone() });
+                }
+            }
+        }
+        self.semantic_errors = errors;
+    }
+
+    /// Builds the dependency graph of the files and checks that the imported files exist and
+    /// export the imported members, and that every file has at most one default export.
+    /// Returns the found errors.
+    fn resolve_imports(&mut self) -> Vec<SemanticError> {
+        let mut errors = Vec::new();
+        let mut graph = DependencyGraph::new();
+        let mut paths = self.file_maps.keys().collect::<Vec<_>>();
+        paths.sort();
+        for path in paths {
+            graph.add_file(path);
+            for token in self.file_maps[path].default_exports.iter().skip(1) {
+                errors.push(SemanticError::new(
+                    token.get_token_position(),
+                    SemanticErrorType::MultipleDefaultExports,
+                ));
             }
-            _ => {
-                panic!(
-                    "cannot determine entry point because compiler found {} entry points.",
-                    points.len()
-                );
+            for import in &self.file_maps[path].imports {
+                let resolved_path = match &import.resolved_path {
+                    Some(resolved_path) => resolved_path,
+                    // JavaScript modules are not checked
+                    None => continue,
+                };
+                if let Err(cycle) = graph.add_import(path, resolved_path) {
+                    errors.push(SemanticError::new(
+                        import.path_token.get_token_position(),
+                        SemanticErrorType::ImportCycle(cycle),
+                    ));
+                }
+                let imported = match self.file_maps.get(resolved_path) {
+                    Some(imported) => imported,
+                    None => {
+                        errors.push(SemanticError::new(
+                            import.path_token.get_token_position(),
+                            SemanticErrorType::UnresolvedImport(import.import_path.clone()),
+                        ));
+                        continue;
+                    }
+                };
+                let members = match &import.clause {
+                    ImportClause::Default(member) => {
+                        if imported.default_exports.is_empty() {
+                            errors.push(SemanticError::new(
+                                member.get_token_position(),
+                                SemanticErrorType::NoDefaultExport(import.import_path.clone()),
+                            ));
+                        }
+                        continue;
+                    }
+                    ImportClause::Named(members) | ImportClause::ReExport(members) => members,
+                    ImportClause::Namespace(_) | ImportClause::ReExportAll => continue,
+                };
+                for member in members {
+                    let name = member.name.to_string();
+                    if resolve_export(&self.file_maps, resolved_path, &name).is_none() {
+                        errors.push(SemanticError::new(
+                            member.name.get_token_position(),
+                            SemanticErrorType::NoExportedMember {
+                                module: import.import_path.clone(),
+                                member: name,
+                            },
+                        ));
+                    }
+                }
             }
         }
+        self.project_dependency = Some(graph);
+        errors
     }
 
-    pub fn get_entrypoint_func(&self) -> Option<&FuncInfo> {
-        if self.entry_point_id.is_none() {
-            None
-        } else {
-            let value = &self.member_map[self.entry_point_id.as_ref().unwrap()];
-            Some(value)
+    /// Returns the errors found while collecting the members of the files.
+    fn get_declaration_errors(&self) -> Vec<SemanticError> {
+        self.file_maps
+            .values()
+            .flat_map(|x| x.errors.iter().cloned())
+            .collect()
+    }
+
+    fn add_errors(&mut self, errors: Vec<SemanticError>) {
+        self.semantic_errors.extend(errors);
+        self.semantic_errors.sort_by(|x, y| {
+            let (x, y) = (x.get_position(), y.get_position());
+            (&x.path, x.ind).cmp(&(&y.path, y.ind))
+        });
+    }
+
+    /// Returns the files which import any of `paths` directly or indirectly.
+    pub fn get_importers(&self, paths: &HashSet<String>) -> HashSet<String> {
+        match &self.project_dependency {
+            Some(graph) => graph.get_importers(paths),
+            None => HashSet::new(),
         }
     }
 
+    pub fn set_project_name(&mut self, project_name: String) {
+        self.project_name = project_name;
+    }
+
+    pub fn get_project_name(&self) -> &str {
+        &self.project_name
+    }
+
+    pub fn get_semantic_errors(&self) -> &[SemanticError] {
+        &self.semantic_errors
+    }
+
+    pub fn has_error(&self) -> bool {
+        !self.semantic_errors.is_empty()
+    }
+
+    /// Reports the semantic errors to `diagnostics`.
+    pub fn report_errors(&self, diagnostics: &mut Diagnostics) {
+        diagnostics.extend(self.semantic_errors.iter().map(Diagnostic::from));
+    }
+
+    /// Sets the entry point if the project has exactly one.
+    /// Returns an error for each entry point but the first one otherwise.
+    fn search_entry_point(&mut self) -> Vec<SemanticError> {
+        let points = self.member_map.get_entrypoint_ids();
+        if points.len() == 1 {
+            self.entry_point_id = Some(points[0]);
+        }
+        if points.len() <= 1 {
+            return Vec::new();
+        }
+        let mut positions = points
+            .iter()
+            .filter_map(|id| {
+                let info = &self.member_map[id];
+                let func = self.file_maps[&info.file_name].functions.get(&info.name)?;
+                let name = func.get_ast().children.as_ref()?[0].token.as_ref()?;
+                Some(name.get_token_position())
+            })
+            .collect::<Vec<_>>();
+        positions.sort_by(|x, y| (&x.path, x.ind).cmp(&(&y.path, y.ind)));
+        let first = positions[0].clone();
+        positions
+            .into_iter()
+            .skip(1)
+            .map(|position| {
+                SemanticError::new(
+                    position,
+                    SemanticErrorType::MultipleEntryPoints(points.len()),
+                )
+                .with_label(first.clone(), "first entry point is defined here")
+            })
+            .collect()
+    }
+
+    pub fn get_entrypoint_func(&self) -> Option<&FuncInfo> {
+        self.entry_point_id
+            .as_ref()
+            .map(|entry_point_id| &self.member_map[entry_point_id])
+    }
+
     pub(crate) fn print_project_tree(&self) {
+        println!("project: {}", self.project_name);
         println!(
             "entry point: {}",
-            if self.entry_point_id.is_some() {
-                &self.member_map[&self.entry_point_id.unwrap()].name
-            } else {
-                "[none]"
+            match &self.entry_point_id {
+                Some(entry_point_id) => &self.member_map[entry_point_id].name,
+                None => "[none]",
             }
         );
         println!("members: \n{:?}", self.member_map);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::test_support::parse;
+
+    use super::{FileMapCache, Project};
+
+    #[test]
+    fn analyze_files_with_cache_test() {
+        let parse_file = |path: &str, source: &str| parse(&[(path, source)]).remove(0).1;
+        let main = parse_file(
+            "./main.rots",
+            r#"import { one } from "./a"; const b: string = one;"#,
+        );
+        let a = parse_file("./a.rots", "export const one = 1;");
+        let changed_a = parse_file("./a.rots", r#"export const one = "1";"#);
+        let mut cache = FileMapCache::default();
+        let paths = ["./main.rots", "./a.rots"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<HashSet<_>>();
+
+        let mut project = Project::new(vec![
+            ("./main.rots".to_string(), &main),
+            ("./a.rots".to_string(), &a),
+        ]);
+        project.analyze_files(&paths, &mut cache);
+        assert_eq!(1, project.get_semantic_errors().len());
+        let main_id = project.file_maps["./main.rots"].members["b"].func_id;
+        let a_id = project.file_maps["./a.rots"].members["one"].func_id;
+
+        // only `./a.rots` is collected again
+        let mut project = Project::new(vec![
+            ("./main.rots".to_string(), &main),
+            ("./a.rots".to_string(), &changed_a),
+        ]);
+        project.analyze_files(&HashSet::from(["./a.rots".to_string()]), &mut cache);
+        assert!(project.get_semantic_errors().is_empty());
+        assert_eq!(
+            main_id,
+            project.file_maps["./main.rots"].members["b"].func_id
+        );
+        let changed_a_id = project.file_maps["./a.rots"].members["one"].func_id;
+        assert!(changed_a_id != a_id && changed_a_id != main_id);
+        assert!(project.file_maps["./main.rots"].functions["b"]
+            .get_tree()
+            .is_some());
+
+        // the removed file is dropped from the cache
+        let mut project = Project::new(vec![("./main.rots".to_string(), &main)]);
+        project.analyze_files(&HashSet::new(), &mut cache);
+        assert_eq!(1, cache.file_maps.len());
+        assert_eq!(
+            main_id,
+            project.file_maps["./main.rots"].members["b"].func_id
+        );
+    }
+}
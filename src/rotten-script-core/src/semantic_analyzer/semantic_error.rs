@@ -0,0 +1,285 @@
+use std::fmt::Display;
+
+use colored::Colorize;
+
+use crate::{
+    diagnostic::Diagnostic,
+    lexer::{reserved_word::ReservedWord, token::TokenPosition},
+};
+
+use super::func_info::Type;
+
+#[derive(Debug, Clone)]
+pub struct SemanticError {
+    position: TokenPosition,
+    semantic_error_type: SemanticErrorType,
+    // the related positions, e.g. the first definition
+    labels: Vec<(TokenPosition, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SemanticErrorType {
+    MismatchedTypes {
+        expected: Type,
+        found: Type,
+    },
+    InvalidOperand {
+        operator: ReservedWord,
+        found: Type,
+    },
+    MismatchedOperands {
+        operator: ReservedWord,
+        left: Type,
+        right: Type,
+    },
+    UnknownType(String),
+    NotCallable(Type),
+    WrongArgumentCount {
+        expected: usize,
+        found: usize,
+    },
+    NoField {
+        ty: Type,
+        field: String,
+    },
+    MissingFields {
+        ty: Type,
+        fields: Vec<String>,
+    },
+    // e.g. `false` for a boolean match which only has `true` arm
+    NonExhaustivePatterns(Vec<String>),
+    NoVariant {
+        ty: Type,
+        variant: String,
+    },
+    WrongFieldCount {
+        expected: usize,
+        found: usize,
+    },
+    // e.g. `Shape::Rect(w, h)` for `Rect { w: number, h: number }`
+    UnexpectedVariantPattern {
+        expected: &'static str,
+        found: String,
+    },
+    // `kind` is used for the discriminant of the emitted objects
+    ReservedFieldName(String),
+    UnresolvedImport(String),
+    NoExportedMember {
+        module: String,
+        member: String,
+    },
+    // the paths of the files on the cycle, e.g. `[a, b, a]`
+    ImportCycle(Vec<String>),
+    NoDefaultExport(String),
+    // reported at the second and later `export default`
+    MultipleDefaultExports,
+    // reported at the second and later `[EntryPoint]` with the number of the entry points
+    MultipleEntryPoints(usize),
+    // reported at the second and later declarations of a member or a type of a file
+    DuplicateDefinition(String),
+    // reported at the second and later fields of the same name of a struct
+    DuplicateField(String),
+    // reported at the second and later variants of the same name of an enum
+    DuplicateVariant(String),
+    // e.g. `x = 1;` for `const x = 0;`
+    AssignmentToConstant(String),
+}
+
+impl SemanticError {
+    pub fn new(position: TokenPosition, semantic_error_type: SemanticErrorType) -> Self {
+        Self {
+            position,
+            semantic_error_type,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, position: TokenPosition, message: impl Into<String>) -> Self {
+        self.labels.push((position, message.into()));
+        self
+    }
+
+    pub fn get_type(&self) -> &SemanticErrorType {
+        &self.semantic_error_type
+    }
+
+    pub fn get_position(&self) -> &TokenPosition {
+        &self.position
+    }
+}
+
+impl SemanticErrorType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticErrorType::MismatchedTypes { .. } => "E0201",
+            SemanticErrorType::InvalidOperand { .. } => "E0202",
+            SemanticErrorType::MismatchedOperands { .. } => "E0203",
+            SemanticErrorType::UnknownType(_) => "E0204",
+            SemanticErrorType::NotCallable(_) => "E0205",
+            SemanticErrorType::WrongArgumentCount { .. } => "E0206",
+            SemanticErrorType::NoField { .. } => "E0207",
+            SemanticErrorType::MissingFields { .. } => "E0208",
+            SemanticErrorType::NonExhaustivePatterns(_) => "E0209",
+            SemanticErrorType::NoVariant { .. } => "E0210",
+            SemanticErrorType::WrongFieldCount { .. } => "E0211",
+            SemanticErrorType::UnexpectedVariantPattern { .. } => "E0212",
+            SemanticErrorType::ReservedFieldName(_) => "E0213",
+            SemanticErrorType::UnresolvedImport(_) => "E0214",
+            SemanticErrorType::NoExportedMember { .. } => "E0215",
+            SemanticErrorType::ImportCycle(_) => "E0216",
+            SemanticErrorType::NoDefaultExport(_) => "E0217",
+            SemanticErrorType::MultipleDefaultExports => "E0218",
+            SemanticErrorType::MultipleEntryPoints(_) => "E0219",
+            SemanticErrorType::DuplicateDefinition(_) => "E0220",
+            SemanticErrorType::DuplicateField(_) => "E0221",
+            SemanticErrorType::DuplicateVariant(_) => "E0222",
+            SemanticErrorType::AssignmentToConstant(_) => "E0223",
+        }
+    }
+}
+
+impl From<&SemanticError> for Diagnostic {
+    fn from(error: &SemanticError) -> Self {
+        error.labels.iter().fold(
+            Diagnostic::error(
+                error.semantic_error_type.code(),
+                error.semantic_error_type.to_string(),
+            )
+            .with_span(&error.position),
+            |diagnostic, (position, message)| diagnostic.with_label(position, message.as_str()),
+        )
+    }
+}
+
+impl Display for SemanticError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}\n\t --> {}:{}:{}",
+            "error".red().bold(),
+            self.semantic_error_type,
+            self.position.path,
+            self.position.ln,
+            self.position.col
+        )
+    }
+}
+
+impl Display for SemanticErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SemanticErrorType::MismatchedTypes { expected, found } => {
+                write!(
+                    f,
+                    "mismatched types: expected `{}`, found `{}`",
+                    expected, found
+                )
+            }
+            SemanticErrorType::InvalidOperand { operator, found } => {
+                write!(
+                    f,
+                    "cannot apply operator `{}` to type `{}`",
+                    operator, found
+                )
+            }
+            SemanticErrorType::MismatchedOperands {
+                operator,
+                left,
+                right,
+            } => {
+                write!(
+                    f,
+                    "cannot apply operator `{}` to types `{}` and `{}`",
+                    operator, left, right
+                )
+            }
+            SemanticErrorType::UnknownType(name) => {
+                write!(f, "cannot find type `{}`", name)
+            }
+            SemanticErrorType::NotCallable(found) => {
+                write!(f, "expression of type `{}` is not callable", found)
+            }
+            SemanticErrorType::WrongArgumentCount { expected, found } => {
+                write!(
+                    f,
+                    "this function takes {} argument(s) but {} argument(s) were supplied",
+                    expected, found
+                )
+            }
+            SemanticErrorType::NoField { ty, field } => {
+                write!(f, "no field `{}` on type `{}`", field, ty)
+            }
+            SemanticErrorType::MissingFields { ty, fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|x| format!("`{}`", x))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "missing field(s) {} in initializer of `{}`", fields, ty)
+            }
+            SemanticErrorType::NonExhaustivePatterns(missing) => {
+                let missing = missing
+                    .iter()
+                    .map(|x| format!("`{}`", x))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                write!(f, "non-exhaustive patterns: {} not covered", missing)
+            }
+            SemanticErrorType::NoVariant { ty, variant } => {
+                write!(f, "no variant `{}` on type `{}`", variant, ty)
+            }
+            SemanticErrorType::WrongFieldCount { expected, found } => {
+                write!(
+                    f,
+                    "this pattern has {} field(s) but the variant has {} field(s)",
+                    found, expected
+                )
+            }
+            SemanticErrorType::UnexpectedVariantPattern { expected, found } => {
+                write!(f, "expected {}, found `{}`", expected, found)
+            }
+            SemanticErrorType::ReservedFieldName(name) => {
+                write!(f, "field name `{}` is reserved in enum variants", name)
+            }
+            SemanticErrorType::UnresolvedImport(module) => {
+                write!(f, "cannot find module `{}`", module)
+            }
+            SemanticErrorType::NoExportedMember { module, member } => {
+                write!(f, "module `{}` has no exported member `{}`", module, member)
+            }
+            SemanticErrorType::ImportCycle(paths) => {
+                let paths = paths
+                    .iter()
+                    .map(|x| format!("`{}`", x))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                write!(f, "import cycle detected: {}", paths)
+            }
+            SemanticErrorType::NoDefaultExport(module) => {
+                write!(f, "module `{}` has no default export", module)
+            }
+            SemanticErrorType::MultipleDefaultExports => {
+                write!(f, "a module cannot have multiple default exports")
+            }
+            SemanticErrorType::MultipleEntryPoints(count) => {
+                write!(
+                    f,
+                    "cannot determine the entry point because {} entry points were found",
+                    count
+                )
+            }
+            SemanticErrorType::DuplicateDefinition(name) => {
+                write!(f, "the name `{}` is defined multiple times", name)
+            }
+            SemanticErrorType::DuplicateField(name) => {
+                write!(f, "field `{}` is already declared", name)
+            }
+            SemanticErrorType::DuplicateVariant(name) => {
+                write!(f, "variant `{}` is already declared", name)
+            }
+            SemanticErrorType::AssignmentToConstant(name) => {
+                write!(f, "cannot assign to `{}` because it is a constant", name)
+            }
+        }
+    }
+}
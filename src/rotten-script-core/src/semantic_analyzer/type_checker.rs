@@ -0,0 +1,1676 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    lexer::{
+        number_literal,
+        reserved_word::ReservedWord,
+        token::{Token, TokenBase},
+    },
+    parser::{ast::Ast, ast_type::AstType, non_terminal::NonTerminal},
+};
+
+use super::{
+    file_map::{resolve_export, ExportedMember, FileMap, ImportClause},
+    func_info::{Type, Variant, VariantFields},
+    semantic_error::{SemanticError, SemanticErrorType},
+};
+
+/// Infers the type of every member in the project and reports type mismatches.
+pub struct TypeChecker<'a, 'b> {
+    file_maps: &'b HashMap<String, FileMap<'a>>,
+    // key: func_name(full), value: inferred type
+    member_types: HashMap<String, Type>,
+    // members whose declarations are currently being checked (used for recursive references)
+    checking: HashSet<String>,
+    scopes: Vec<HashMap<String, Binding>>,
+    current_file: String,
+    errors: Vec<SemanticError>,
+}
+
+/// A name bound in a function body (e.g. parameters, `const`, `let`, pattern bindings).
+struct Binding {
+    ty: Type,
+    // the name token of the declaration
+    token: Token,
+    // `const` declarations and pattern bindings cannot be reassigned
+    is_const: bool,
+}
+
+/// Which values of the scrutinee a pattern matches (used for the exhaustiveness check).
+enum Coverage {
+    /// `_` and bindings
+    All,
+    /// `true`, `false` and enum variants whose payload patterns match any value
+    Constructor(String),
+    /// other literals and variants with refutable payload patterns
+    Partial,
+}
+
+impl<'a, 'b> TypeChecker<'a, 'b> {
+    pub fn new(file_maps: &'b HashMap<String, FileMap<'a>>) -> Self {
+        Self {
+            file_maps,
+            member_types: HashMap::new(),
+            checking: HashSet::new(),
+            scopes: Vec::new(),
+            current_file: String::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Checks all members and returns their types (key: func_name(full)) and found errors.
+    pub fn check_all(self) -> (HashMap<String, Type>, Vec<SemanticError>) {
+        let paths = self.file_maps.keys().cloned().collect();
+        self.check(paths)
+    }
+
+    /// Checks the members of the files. The members of the other files are checked only if they
+    /// are referred, and their errors are not reported.
+    pub fn check_files(
+        self,
+        paths: &HashSet<String>,
+    ) -> (HashMap<String, Type>, Vec<SemanticError>) {
+        let targets = paths
+            .iter()
+            .filter(|x| self.file_maps.contains_key(*x))
+            .cloned()
+            .collect();
+        let (member_types, mut errors) = self.check(targets);
+        errors.retain(|x| paths.contains(x.get_position().path.as_str()));
+        (member_types, errors)
+    }
+
+    fn check(mut self, mut paths: Vec<String>) -> (HashMap<String, Type>, Vec<SemanticError>) {
+        paths.sort();
+        for path in paths {
+            self.check_type_declarations(&path);
+            let mut names = self.file_maps[&path]
+                .functions
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            names.sort();
+            for name in names {
+                self.check_member(&path, &name);
+            }
+        }
+        self.errors.sort_by(|x, y| {
+            let (x, y) = (x.get_position(), y.get_position());
+            (&x.path, x.ind).cmp(&(&y.path, y.ind))
+        });
+        (self.member_types, self.errors)
+    }
+
+    fn check_member(&mut self, path: &str, name: &str) -> Type {
+        let func = self.file_maps[path].functions[name].clone();
+        let full_path = func.get_func_info().full_path.clone();
+        if let Some(ty) = self.member_types.get(&full_path) {
+            return ty.clone();
+        }
+        if self.checking.contains(&full_path) {
+            return Type::Any;
+        }
+
+        self.checking.insert(full_path.clone());
+        let scopes = std::mem::take(&mut self.scopes);
+        let current_file = std::mem::replace(&mut self.current_file, path.to_string());
+
+        let ty = self.check_declaration_body(func.get_ast());
+
+        self.scopes = scopes;
+        self.current_file = current_file;
+        self.checking.remove(&full_path);
+        self.member_types.insert(full_path, ty.clone());
+        ty
+    }
+
+    /// Checks the annotations of the fields of the structs and enums declared in the file.
+    fn check_type_declarations(&mut self, path: &str) {
+        let current_file = std::mem::replace(&mut self.current_file, path.to_string());
+        let translation_unit = self.file_maps[path].ast;
+        for ast in translation_unit.children.as_ref().unwrap() {
+            if ast.ast_type != AstType::NonTerminal(NonTerminal::ExportableTypeDeclaration) {
+                continue;
+            }
+            let declaration = ast.children.as_ref().unwrap().last().unwrap();
+            if declaration.ast_type != AstType::NonTerminal(NonTerminal::EnumDeclaration) {
+                for field in &declaration.children.as_ref().unwrap()[1..] {
+                    self.resolve_annotation(&field.children.as_ref().unwrap()[1]);
+                }
+                continue;
+            }
+            // EnumVariant = Identifier , [ TupleFields | StructFields ];
+            for variant in &declaration.children.as_ref().unwrap()[1..] {
+                let fields = match variant.children.as_ref().unwrap().get(1) {
+                    Some(fields) => fields,
+                    None => continue,
+                };
+                for field in fields.children.as_ref().unwrap() {
+                    match field.children.as_deref() {
+                        Some([name, type_name]) => {
+                            let token = name.token.as_ref().unwrap();
+                            if token.to_string() == "kind" {
+                                self.add_error(
+                                    token,
+                                    SemanticErrorType::ReservedFieldName(token.to_string()),
+                                );
+                            }
+                            self.resolve_annotation(type_name);
+                        }
+                        _ => {
+                            self.resolve_annotation(field);
+                        }
+                    }
+                }
+            }
+        }
+        self.current_file = current_file;
+    }
+
+    fn lookup(&mut self, name: &str) -> Type {
+        for scope in self.scopes.iter().rev() {
+            if let Some(binding) = scope.get(name) {
+                return binding.ty.clone();
+            }
+        }
+        if self.file_maps[&self.current_file]
+            .functions
+            .contains_key(name)
+        {
+            let path = self.current_file.clone();
+            return self.check_member(&path, name);
+        }
+        if let Some((path, member)) = self.get_imported_member(name) {
+            if self.file_maps[&path].functions.contains_key(&member) {
+                return self.check_member(&path, &member);
+            }
+        }
+        // e.g. `console`, members of JavaScript modules
+        Type::Any
+    }
+
+    /// Returns the path of the file which exports the imported member and the name of the member
+    /// in the file (the default export for `import name from "...";`).
+    /// Returns `None` if the member is not imported or the import is not resolved
+    /// (e.g. the module is a JavaScript module).
+    fn get_imported_member(&self, name: &str) -> Option<(String, String)> {
+        let import = self.file_maps[&self.current_file].get_import(name)?;
+        let path = import.resolved_path.as_ref()?;
+        let file_map = self.file_maps.get(path)?;
+        match &import.clause {
+            ImportClause::Default(_) => match file_map.default_exports.as_slice() {
+                [member] => Some((path.clone(), member.to_string())),
+                _ => None,
+            },
+            ImportClause::Named(members) => {
+                let member = members.iter().find(|x| x.get_local_name() == name)?;
+                match resolve_export(self.file_maps, path, &member.name.to_string())? {
+                    ExportedMember::Member { path, name } => Some((path, name)),
+                    ExportedMember::External => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Binds the name of `token` in the innermost scope.
+    /// The name must not be bound in the scope yet, as in JavaScript.
+    fn bind(&mut self, token: &Token, ty: Type, is_const: bool) {
+        let name = token.to_string();
+        if let Some(first) = self.scopes.last().unwrap().get(&name) {
+            let error = SemanticError::new(
+                token.get_token_position(),
+                SemanticErrorType::DuplicateDefinition(name.clone()),
+            )
+            .with_label(
+                first.token.get_token_position(),
+                format!("previous declaration of `{}` is here", name),
+            );
+            self.errors.push(error);
+            return;
+        }
+        let binding = Binding {
+            ty,
+            token: token.clone(),
+            is_const,
+        };
+        self.scopes.last_mut().unwrap().insert(name, binding);
+    }
+
+    // DeclarationBody = Identifier , [ ":", Identifier ] , "=" , Expression , ";";
+    fn check_declaration_body(&mut self, ast: &Ast) -> Type {
+        let children = ast.children.as_ref().unwrap();
+        let expr = children.last().unwrap();
+        let value = self.infer(expr);
+        if children.len() == 3 {
+            let declared = self.resolve_annotation(&children[1]);
+            self.expect(&declared, &value, expr);
+            declared
+        } else {
+            value
+        }
+    }
+
+    fn resolve_annotation(&mut self, ast: &Ast) -> Type {
+        let token = ast.token.as_ref().unwrap();
+        let name = token.to_string();
+        Type::from_annotation(&name)
+            .or_else(|| self.lookup_type(&name))
+            .unwrap_or_else(|| {
+                self.add_error(token, SemanticErrorType::UnknownType(name));
+                Type::Any
+            })
+    }
+
+    /// Finds the type declared in or imported to the current file (e.g. `struct Point { ... }`).
+    /// Types imported from JavaScript modules are `any`.
+    fn lookup_type(&self, name: &str) -> Option<Type> {
+        let file_map = &self.file_maps[&self.current_file];
+        if file_map.is_imported(name) {
+            return match self.get_imported_member(name) {
+                Some((path, member)) => self.find_type(&path, &member),
+                None => Some(Type::Any),
+            };
+        }
+        self.find_type(&self.current_file, name)
+    }
+
+    fn find_type(&self, path: &str, name: &str) -> Option<Type> {
+        match self.file_maps[path].members.get(name) {
+            Some(member) if member.get_fields().is_some() => {
+                Some(Type::Struct(member.full_path.clone()))
+            }
+            Some(member) if member.get_variants().is_some() => {
+                Some(Type::Enum(member.full_path.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the fields of the struct type.
+    fn get_fields(&self, ty: &Type) -> Option<Vec<(String, Type)>> {
+        let full_path = match ty {
+            Type::Struct(full_path) => full_path,
+            _ => return None,
+        };
+        let (path, name) = full_path.rsplit_once('#').unwrap();
+        let fields = self.file_maps[path].members[name].get_fields()?;
+        Some(fields.arguments.clone())
+    }
+
+    /// Returns the variants of the enum type.
+    fn get_variants(&self, ty: &Type) -> Option<Vec<Variant>> {
+        let full_path = match ty {
+            Type::Enum(full_path) => full_path,
+            _ => return None,
+        };
+        let (path, name) = full_path.rsplit_once('#').unwrap();
+        let variants = self.file_maps[path].members[name].get_variants()?;
+        Some(variants.to_vec())
+    }
+
+    /// Resolves `Enum::Variant` and returns the enum type and the fields of the variant.
+    /// Returns `None` if the variant is unknown (e.g. the enum is imported).
+    // PathExpression = Identifier , "::" , Identifier;
+    fn lookup_variant(&mut self, path: &Ast) -> Option<(Type, VariantFields)> {
+        let children = path.children.as_ref().unwrap();
+        let token = children[0].token.as_ref().unwrap();
+        let name = token.to_string();
+        let ty = match self.lookup_type(&name) {
+            Some(Type::Any) => return None,
+            Some(ty) => ty,
+            None => {
+                self.add_error(token, SemanticErrorType::UnknownType(name));
+                return None;
+            }
+        };
+        let variant_token = children[1].token.as_ref().unwrap();
+        let variant_name = variant_token.to_string();
+        let variant = self
+            .get_variants(&ty)
+            .and_then(|x| x.into_iter().find(|x| x.name == variant_name));
+        match variant {
+            Some(variant) => Some((ty, variant.fields)),
+            None => {
+                self.add_error(
+                    variant_token,
+                    SemanticErrorType::NoVariant {
+                        ty,
+                        variant: variant_name,
+                    },
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns the type of `ty.field`.
+    /// Infers `ns.member` of `import * as ns from "...";`.
+    /// Returns `None` if the expression does not start with a namespace import.
+    fn infer_namespace_member(&mut self, children: &[Ast]) -> Option<Type> {
+        let name = children[0].token.as_ref()?.to_string();
+        let field = children.get(2)?.token.as_ref()?;
+        if children[1].ast_type == AstType::NonTerminal(NonTerminal::Args)
+            || self.scopes.iter().any(|x| x.contains_key(&name))
+        {
+            return None;
+        }
+        let import = self.file_maps[&self.current_file].get_import(&name)?;
+        if !matches!(import.clause, ImportClause::Namespace(_)) {
+            return None;
+        }
+        let path = match &import.resolved_path {
+            Some(path) if self.file_maps.contains_key(path) => path,
+            // e.g. `import * as fs from "fs";`
+            _ => return Some(Type::Any),
+        };
+        let member = field.to_string();
+        match resolve_export(self.file_maps, path, &member) {
+            Some(ExportedMember::Member { path, name }) => {
+                if self.file_maps[&path].functions.contains_key(&name) {
+                    Some(self.check_member(&path, &name))
+                } else {
+                    // structs and enums
+                    Some(Type::Any)
+                }
+            }
+            Some(ExportedMember::External) => Some(Type::Any),
+            None => {
+                let module = import.import_path.clone();
+                self.add_error(
+                    field,
+                    SemanticErrorType::NoExportedMember { module, member },
+                );
+                Some(Type::Any)
+            }
+        }
+    }
+
+    fn get_field_type(&mut self, ty: &Type, field: &Ast) -> Type {
+        match self.get_fields(ty) {
+            Some(fields) => self.find_field(ty, &fields, field),
+            // e.g. `console.log`
+            None => Type::Any,
+        }
+    }
+
+    fn find_field(&mut self, ty: &Type, fields: &[(String, Type)], field: &Ast) -> Type {
+        let token = field.token.as_ref().unwrap();
+        let name = token.to_string();
+        match fields.iter().find(|(x, _)| x == &name) {
+            Some((_, field_type)) => field_type.clone(),
+            None => {
+                self.add_error(
+                    token,
+                    SemanticErrorType::NoField {
+                        ty: ty.clone(),
+                        field: name,
+                    },
+                );
+                Type::Any
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: &Type, found: &Type, ast: &Ast) {
+        if !expected.accepts(found) {
+            if let Some(token) = ast.get_first_token() {
+                let token = token.clone();
+                self.add_error(
+                    &token,
+                    SemanticErrorType::MismatchedTypes {
+                        expected: expected.clone(),
+                        found: found.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn add_error(&mut self, token: &Token, semantic_error_type: SemanticErrorType) {
+        self.errors.push(SemanticError::new(
+            token.get_token_position(),
+            semantic_error_type,
+        ));
+    }
+
+    fn infer(&mut self, ast: &Ast) -> Type {
+        let nt = match &ast.ast_type {
+            AstType::Terminal => return self.infer_terminal(ast.token.as_ref().unwrap()),
+            AstType::NonTerminal(nt) => nt,
+        };
+        let children = ast.children.as_ref().unwrap();
+        match nt {
+            NonTerminal::PrimaryExpression => self.infer_primary_expression(children),
+            NonTerminal::ParenthesizedExpression => self.infer(&children[0]),
+            NonTerminal::FunctionExpression => self.infer_function_expression(children),
+            NonTerminal::CompoundExpression => self.infer_compound_expression(children),
+            NonTerminal::UnaryExpression => self.infer_unary_expression(children),
+            NonTerminal::IfExpression => self.infer_if_expression(children),
+            NonTerminal::MatchExpression => self.infer_match_expression(children),
+            NonTerminal::StructExpression => self.infer_struct_expression(children),
+            NonTerminal::TemplateLiteral => {
+                // any value can be interpolated
+                for substitution in children.iter().skip(1).step_by(2) {
+                    self.infer(substitution);
+                }
+                Type::string()
+            }
+            NonTerminal::PathExpression => self.infer_path_expression(ast),
+            NonTerminal::ExponentiationExpression => {
+                let left = self.infer(&children[0]);
+                let right = self.infer(&children[1]);
+                let operator = ReservedWord::Exponential;
+                self.binary_result(operator, &left, &right, &children[0])
+            }
+            NonTerminal::AdditiveExpression
+            | NonTerminal::MultiplicativeExpression
+            | NonTerminal::ShiftExpression
+            | NonTerminal::RelationalExpression
+            | NonTerminal::EqualityExpression
+            | NonTerminal::BitwiseAndExpression
+            | NonTerminal::BitwiseXorExpression
+            | NonTerminal::BitwiseOrExpression
+            | NonTerminal::LogicalAndExpression
+            | NonTerminal::LogicalOrExpression => {
+                let mut left = self.infer(&children[0]);
+                for pair in children[1..].chunks(2) {
+                    let right = self.infer(&pair[1]);
+                    let operator = match pair[0].token.as_ref().unwrap().get_token() {
+                        Some(TokenBase::Reserved(r)) => *r,
+                        _ => return Type::Any,
+                    };
+                    left = self.binary_result(operator, &left, &right, &pair[0]);
+                }
+                left
+            }
+            _ => Type::Any,
+        }
+    }
+
+    fn infer_terminal(&mut self, token: &Token) -> Type {
+        match token.get_token() {
+            Some(TokenBase::String(_)) | Some(TokenBase::Template(..)) => Type::string(),
+            Some(TokenBase::Number(n)) if number_literal::is_bigint(n) => Type::bigint(),
+            Some(TokenBase::Number(_)) => Type::number(),
+            Some(TokenBase::Reserved(ReservedWord::True))
+            | Some(TokenBase::Reserved(ReservedWord::False)) => Type::boolean(),
+            Some(TokenBase::Identifier(name)) => self.lookup(name),
+            Some(TokenBase::Reserved(_)) | None => Type::Any,
+        }
+    }
+
+    // PrimaryExpression = (Literal | Identifier | ...), { { ".", Identifier }, { Args } };
+    fn infer_primary_expression(&mut self, children: &[Ast]) -> Type {
+        let (mut ty, mut rest) = match self.infer_namespace_member(children) {
+            Some(ty) => (ty, children[3..].iter()),
+            None => (self.infer(&children[0]), children[1..].iter()),
+        };
+        while let Some(item) = rest.next() {
+            if item.ast_type == AstType::NonTerminal(NonTerminal::Args) {
+                ty = self.infer_call(ty, item, &children[0]);
+            } else {
+                let field = rest.next().unwrap();
+                ty = self.get_field_type(&ty, field);
+            }
+        }
+        ty
+    }
+
+    fn infer_call(&mut self, callee: Type, args: &Ast, callee_ast: &Ast) -> Type {
+        let args = args.children.as_ref().unwrap();
+        let arg_types = args.iter().map(|x| self.infer(x)).collect::<Vec<_>>();
+        match callee {
+            Type::Function(params, ret) => {
+                if params.len() != arg_types.len() {
+                    let token = callee_ast.get_first_token().unwrap().clone();
+                    self.add_error(
+                        &token,
+                        SemanticErrorType::WrongArgumentCount {
+                            expected: params.len(),
+                            found: arg_types.len(),
+                        },
+                    );
+                } else {
+                    for ((param, arg), arg_ast) in params.iter().zip(&arg_types).zip(args) {
+                        self.expect(param, arg, arg_ast);
+                    }
+                }
+                *ret
+            }
+            Type::Any => Type::Any,
+            other => {
+                let token = callee_ast.get_first_token().unwrap().clone();
+                self.add_error(&token, SemanticErrorType::NotCallable(other));
+                Type::Any
+            }
+        }
+    }
+
+    // PathExpression = Identifier , "::" , Identifier;
+    fn infer_path_expression(&mut self, ast: &Ast) -> Type {
+        let (ty, fields) = match self.lookup_variant(ast) {
+            Some(variant) => variant,
+            None => return Type::Any,
+        };
+        match fields {
+            VariantFields::Unit => ty,
+            VariantFields::Tuple(types) => Type::Function(types, Box::new(ty)),
+            VariantFields::Struct(fields) => {
+                let token = ast.get_first_token().unwrap().clone();
+                self.add_error(
+                    &token,
+                    SemanticErrorType::MissingFields {
+                        ty: ty.clone(),
+                        fields: fields.arguments.into_iter().map(|(x, _)| x).collect(),
+                    },
+                );
+                ty
+            }
+        }
+    }
+
+    // StructExpression = ( Identifier | PathExpression ) , "{" , [ FieldInitializer , { "," , FieldInitializer } , [ "," ] ] , "}";
+    fn infer_struct_expression(&mut self, children: &[Ast]) -> Type {
+        let token = children[0].get_first_token().unwrap();
+        let (ty, fields) =
+            if children[0].ast_type == AstType::NonTerminal(NonTerminal::PathExpression) {
+                match self.lookup_variant(&children[0]) {
+                    Some((ty, VariantFields::Struct(fields))) => (ty, Some(fields.arguments)),
+                    Some((ty, _)) => {
+                        self.add_error(
+                            token,
+                            SemanticErrorType::UnexpectedVariantPattern {
+                                expected: "struct variant",
+                                found: Self::path_to_string(&children[0]),
+                            },
+                        );
+                        (ty, None)
+                    }
+                    None => (Type::Any, None),
+                }
+            } else {
+                let name = token.to_string();
+                let ty = self.lookup_type(&name).unwrap_or_else(|| {
+                    self.add_error(token, SemanticErrorType::UnknownType(name));
+                    Type::Any
+                });
+                let fields = self.get_fields(&ty);
+                (ty, fields)
+            };
+        let mut missing = fields
+            .iter()
+            .flatten()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+
+        // FieldInitializer = Identifier , ":" , Expression;
+        for initializer in &children[1..] {
+            let initializer = initializer.children.as_ref().unwrap();
+            let value = self.infer(&initializer[1]);
+            let field_name = initializer[0].token.as_ref().unwrap().to_string();
+            missing.retain(|x| x != &field_name);
+            let expected = match &fields {
+                Some(fields) => self.find_field(&ty, fields, &initializer[0]),
+                None => Type::Any,
+            };
+            self.expect(&expected, &value, &initializer[1]);
+        }
+
+        if !missing.is_empty() {
+            self.add_error(
+                token,
+                SemanticErrorType::MissingFields {
+                    ty: ty.clone(),
+                    fields: missing,
+                },
+            );
+        }
+        ty
+    }
+
+    // FunctionExpression = ParameterList , [ ":" , Identifier ] , "=>" , CompoundExpression;
+    fn infer_function_expression(&mut self, children: &[Ast]) -> Type {
+        // the parameters share the scope with the declarations of the body as in JavaScript
+        self.scopes.push(HashMap::new());
+        let mut params = Vec::new();
+        for param in children[0].children.as_ref().unwrap() {
+            if let [name, type_name] = param.children.as_ref().unwrap().as_slice() {
+                let ty = self.resolve_annotation(type_name);
+                self.bind(name.token.as_ref().unwrap(), ty.clone(), false);
+                params.push(ty);
+            }
+        }
+        let declared = if children.len() == 3 {
+            Some(self.resolve_annotation(&children[1]))
+        } else {
+            None
+        };
+
+        let body = children.last().unwrap();
+        let body_type = self.infer_statements(body.children.as_ref().unwrap());
+        self.scopes.pop();
+
+        let ret = match declared {
+            Some(declared) => {
+                let body_children = body.children.as_ref().unwrap();
+                let tail = match body_children.last() {
+                    Some(last) if !last.is_statement() => last,
+                    _ => &children[1],
+                };
+                self.expect(&declared, &body_type, tail);
+                declared
+            }
+            None => body_type,
+        };
+        Type::Function(params, Box::new(ret))
+    }
+
+    // CompoundExpression = "{" , { Statement } , [ Expression ] , "}";
+    fn infer_compound_expression(&mut self, children: &[Ast]) -> Type {
+        self.scopes.push(HashMap::new());
+        let ty = self.infer_statements(children);
+        self.scopes.pop();
+        ty
+    }
+
+    /// Checks the statements of a block in the innermost scope and returns the type of the block.
+    fn infer_statements(&mut self, children: &[Ast]) -> Type {
+        let mut ty = Type::void();
+        for item in children {
+            match &item.ast_type {
+                AstType::NonTerminal(NonTerminal::ConstDeclaration)
+                | AstType::NonTerminal(NonTerminal::LetDeclaration) => {
+                    let body = &item.children.as_ref().unwrap()[0];
+                    let bound = self.check_declaration_body(body);
+                    let name = body.children.as_ref().unwrap()[0].token.as_ref().unwrap();
+                    let is_const =
+                        item.ast_type == AstType::NonTerminal(NonTerminal::ConstDeclaration);
+                    self.bind(name, bound, is_const);
+                    ty = Type::void();
+                }
+                AstType::NonTerminal(NonTerminal::ExpressionStatement) => {
+                    self.infer(&item.children.as_ref().unwrap()[0]);
+                    ty = Type::void();
+                }
+                AstType::NonTerminal(NonTerminal::AssignmentStatement) => {
+                    self.check_assignment_statement(item.children.as_ref().unwrap());
+                    ty = Type::void();
+                }
+                AstType::NonTerminal(NonTerminal::ForStatement) => {
+                    self.check_for_statement(item.children.as_ref().unwrap());
+                    ty = Type::void();
+                }
+                AstType::NonTerminal(NonTerminal::WhileStatement) => {
+                    let children = item.children.as_ref().unwrap();
+                    let condition = self.infer(&children[0]);
+                    self.expect(&Type::boolean(), &condition, &children[0]);
+                    self.infer(&children[1]);
+                    ty = Type::void();
+                }
+                _ => ty = self.infer(item),
+            }
+        }
+        ty
+    }
+
+    // ForStatement = "for" , Identifier , "in" , RangeExpression , CompoundExpression , [ ";" ];
+    fn check_for_statement(&mut self, children: &[Ast]) {
+        // RangeExpression = Expression , ( ".." | "..=" ) , Expression;
+        let range = children[1].children.as_ref().unwrap();
+        for bound in [&range[0], &range[2]] {
+            let ty = self.infer(bound);
+            self.expect(&Type::number(), &ty, bound);
+        }
+        self.scopes.push(HashMap::new());
+        self.bind(children[0].token.as_ref().unwrap(), Type::number(), false);
+        self.infer(&children[2]);
+        self.scopes.pop();
+    }
+
+    // IfExpression = "if" , Expression , CompoundExpression , [ "else" , ( IfExpression | CompoundExpression ) ];
+    fn infer_if_expression(&mut self, children: &[Ast]) -> Type {
+        let condition = self.infer(&children[0]);
+        self.expect(&Type::boolean(), &condition, &children[0]);
+        let then_type = self.infer(&children[1]);
+        match children.get(2) {
+            Some(else_ast) => {
+                let else_type = self.infer(else_ast);
+                let tail = match Self::get_tail(else_ast) {
+                    tail if tail.get_first_token().is_some() => tail,
+                    _ => &children[0],
+                };
+                self.expect(&then_type, &else_type, tail);
+                if then_type == Type::Any {
+                    else_type
+                } else {
+                    then_type
+                }
+            }
+            None => Type::void(),
+        }
+    }
+
+    // MatchExpression = "match" , Expression , "{" , [ MatchArm , { "," , MatchArm } , [ "," ] ] , "}";
+    fn infer_match_expression(&mut self, children: &[Ast]) -> Type {
+        let scrutinee = self.infer(&children[0]);
+        let mut result: Option<Type> = None;
+        let mut has_irrefutable_arm = false;
+        let mut covered = HashSet::new();
+
+        for arm in &children[1..] {
+            // MatchArm = Pattern , [ MatchGuard ] , "=>" , Expression;
+            let arm_children = arm.children.as_ref().unwrap();
+            let pattern = &arm_children[0];
+            let body = arm_children.last().unwrap();
+            let has_guard = arm_children.len() == 3;
+
+            self.scopes.push(HashMap::new());
+            let coverage = self.check_pattern(pattern, &scrutinee);
+            if has_guard {
+                let guard = &arm_children[1].children.as_ref().unwrap()[0];
+                let guard_type = self.infer(guard);
+                self.expect(&Type::boolean(), &guard_type, guard);
+            } else {
+                match coverage {
+                    Coverage::All => has_irrefutable_arm = true,
+                    Coverage::Constructor(name) => {
+                        covered.insert(name);
+                    }
+                    Coverage::Partial => {}
+                }
+            }
+            let body_type = self.infer(body);
+            self.scopes.pop();
+
+            result = match result {
+                Some(expected) => {
+                    let tail = match Self::get_tail(body) {
+                        tail if tail.get_first_token().is_some() => tail,
+                        _ => pattern,
+                    };
+                    self.expect(&expected, &body_type, tail);
+                    if expected == Type::Any {
+                        Some(body_type)
+                    } else {
+                        Some(expected)
+                    }
+                }
+                None => Some(body_type),
+            };
+        }
+
+        let missing = if has_irrefutable_arm {
+            vec![]
+        } else if scrutinee == Type::boolean() {
+            ["true", "false"]
+                .iter()
+                .filter(|x| !covered.contains(**x))
+                .map(|x| x.to_string())
+                .collect()
+        } else if let Some(variants) = self.get_variants(&scrutinee) {
+            variants
+                .iter()
+                .filter(|x| !covered.contains(&x.name))
+                // a variant without a payload pattern matches any payload
+                .map(|x| format!("{}::{}", scrutinee, x.name))
+                .collect()
+        } else {
+            vec!["_".to_string()]
+        };
+        if !missing.is_empty() {
+            if let Some(token) = children[0].get_first_token() {
+                let token = token.clone();
+                self.add_error(&token, SemanticErrorType::NonExhaustivePatterns(missing));
+            }
+        }
+        result.unwrap_or_else(Type::void)
+    }
+
+    /// Checks the pattern against the type of the scrutinee, binds the identifier patterns
+    /// and returns which values the pattern matches.
+    // Pattern = "_" | Identifier | Literal | "-" , Number | PathExpression , [ TuplePattern | StructPattern ];
+    fn check_pattern(&mut self, pattern: &Ast, scrutinee: &Type) -> Coverage {
+        let children = pattern.children.as_ref().unwrap();
+        if children[0].ast_type == AstType::NonTerminal(NonTerminal::PathExpression) {
+            return self.check_variant_pattern(children, scrutinee);
+        }
+        match Self::get_pattern_token(pattern) {
+            Some(TokenBase::Reserved(ReservedWord::Underscore)) => Coverage::All,
+            Some(TokenBase::Identifier(_)) => {
+                let token = pattern.get_first_token().unwrap();
+                self.bind(token, scrutinee.clone(), true);
+                Coverage::All
+            }
+            Some(token) => {
+                let found = self.infer(children.last().unwrap());
+                self.expect(scrutinee, &found, pattern);
+                match token {
+                    TokenBase::Reserved(ReservedWord::True) => {
+                        Coverage::Constructor("true".to_string())
+                    }
+                    TokenBase::Reserved(ReservedWord::False) => {
+                        Coverage::Constructor("false".to_string())
+                    }
+                    _ => Coverage::Partial,
+                }
+            }
+            None => Coverage::Partial,
+        }
+    }
+
+    /// Checks `Enum::Variant`, `Enum::Variant(pattern, ...)` and `Enum::Variant { field, ... }`.
+    /// A variant without a payload pattern matches any payload.
+    fn check_variant_pattern(&mut self, children: &[Ast], scrutinee: &Type) -> Coverage {
+        let path = &children[0];
+        let (ty, fields) = match self.lookup_variant(path) {
+            Some((ty, fields)) => {
+                self.expect(scrutinee, &ty, path);
+                (ty, Some(fields))
+            }
+            None => (Type::Any, None),
+        };
+        let payload = match children.get(1) {
+            Some(payload) => payload,
+            None if fields.is_some() => {
+                return Coverage::Constructor(Self::get_variant_name(path));
+            }
+            None => return Coverage::Partial,
+        };
+        let payload_children = payload.children.as_ref().unwrap();
+        let mut is_irrefutable = fields.is_some();
+
+        if payload.ast_type == AstType::NonTerminal(NonTerminal::TuplePattern) {
+            let types = match &fields {
+                Some(VariantFields::Tuple(types)) => {
+                    if types.len() != payload_children.len() {
+                        self.add_pattern_error(
+                            path,
+                            SemanticErrorType::WrongFieldCount {
+                                expected: types.len(),
+                                found: payload_children.len(),
+                            },
+                        );
+                    }
+                    types.clone()
+                }
+                Some(_) => {
+                    self.add_pattern_error(
+                        path,
+                        SemanticErrorType::UnexpectedVariantPattern {
+                            expected: "tuple variant",
+                            found: Self::path_to_string(path),
+                        },
+                    );
+                    vec![]
+                }
+                None => vec![],
+            };
+            for (i, sub_pattern) in payload_children.iter().enumerate() {
+                let ty = types.get(i).cloned().unwrap_or(Type::Any);
+                if !matches!(self.check_pattern(sub_pattern, &ty), Coverage::All) {
+                    is_irrefutable = false;
+                }
+            }
+        } else {
+            let fields = match &fields {
+                Some(VariantFields::Struct(fields)) => Some(fields.arguments.clone()),
+                Some(_) => {
+                    self.add_pattern_error(
+                        path,
+                        SemanticErrorType::UnexpectedVariantPattern {
+                            expected: "struct variant",
+                            found: Self::path_to_string(path),
+                        },
+                    );
+                    None
+                }
+                None => None,
+            };
+            // FieldPattern = Identifier , [ ":" , Pattern ];
+            for field_pattern in payload_children {
+                let field_children = field_pattern.children.as_ref().unwrap();
+                let field_type = match &fields {
+                    Some(fields) => self.find_field(&ty, fields, &field_children[0]),
+                    None => Type::Any,
+                };
+                match field_children.get(1) {
+                    Some(sub_pattern) => {
+                        if !matches!(self.check_pattern(sub_pattern, &field_type), Coverage::All) {
+                            is_irrefutable = false;
+                        }
+                    }
+                    None => {
+                        self.bind(field_children[0].token.as_ref().unwrap(), field_type, true);
+                    }
+                }
+            }
+        }
+
+        if is_irrefutable {
+            Coverage::Constructor(Self::get_variant_name(path))
+        } else {
+            Coverage::Partial
+        }
+    }
+
+    fn add_pattern_error(&mut self, path: &Ast, semantic_error_type: SemanticErrorType) {
+        let token = path.get_first_token().unwrap().clone();
+        self.add_error(&token, semantic_error_type);
+    }
+
+    fn get_variant_name(path: &Ast) -> String {
+        path.children.as_ref().unwrap()[1]
+            .token
+            .as_ref()
+            .unwrap()
+            .to_string()
+    }
+
+    fn path_to_string(path: &Ast) -> String {
+        let children = path.children.as_ref().unwrap();
+        format!(
+            "{}::{}",
+            children[0].token.as_ref().unwrap(),
+            children[1].token.as_ref().unwrap()
+        )
+    }
+
+    fn get_pattern_token(pattern: &Ast) -> Option<&TokenBase> {
+        match pattern.children.as_deref() {
+            Some([leaf]) => leaf.token.as_ref()?.get_token().as_ref(),
+            Some([_, number]) => number.token.as_ref()?.get_token().as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the expression which decides the value of the block-like expression.
+    fn get_tail(ast: &Ast) -> &Ast {
+        match (&ast.ast_type, ast.children.as_deref()) {
+            (AstType::NonTerminal(NonTerminal::CompoundExpression), Some([.., last]))
+                if !last.is_statement() =>
+            {
+                Self::get_tail(last)
+            }
+            (AstType::NonTerminal(NonTerminal::PrimaryExpression), Some([child])) => {
+                Self::get_tail(child)
+            }
+            (AstType::NonTerminal(NonTerminal::IfExpression), Some([_, then_ast, ..]))
+            | (AstType::NonTerminal(NonTerminal::MatchExpression), Some([_, then_ast, ..])) => {
+                Self::get_tail(then_ast)
+            }
+            (AstType::NonTerminal(NonTerminal::MatchArm), Some([.., body])) => Self::get_tail(body),
+            _ => ast,
+        }
+    }
+
+    // AssignmentStatement = Identifier , { "." Identifier } , AssignmentOperator, Expression;
+    fn check_assignment_statement(&mut self, children: &[Ast]) {
+        if children.len() == 3 {
+            self.check_reassignment(children[0].token.as_ref().unwrap());
+        }
+        let operator_ast = &children[children.len() - 2];
+        let value_ast = children.last().unwrap();
+        let mut target = self.infer(&children[0]);
+        for field in &children[1..children.len() - 2] {
+            target = self.get_field_type(&target, field);
+        }
+        let value = self.infer(value_ast);
+        let operator = match operator_ast.token.as_ref().unwrap().get_token() {
+            Some(TokenBase::Reserved(r)) => *r,
+            _ => return,
+        };
+        let value = match Self::to_binary_operator(operator) {
+            Some(binary) => self.binary_result(binary, &target, &value, operator_ast),
+            None => value,
+        };
+        self.expect(&target, &value, value_ast);
+    }
+
+    /// Reports the assignment to a constant: a `const` declaration, a pattern binding,
+    /// a member of the file or an imported name.
+    fn check_reassignment(&mut self, target: &Token) {
+        let name = target.to_string();
+        let declaration = match self.scopes.iter().rev().find_map(|x| x.get(&name)) {
+            Some(binding) if binding.is_const => Some(binding.token.clone()),
+            Some(_) => return,
+            None => match self.file_maps[&self.current_file].functions.get(&name) {
+                Some(func) => func.get_ast().get_first_token().cloned(),
+                None if self.file_maps[&self.current_file].is_imported(&name) => None,
+                // e.g. `console`
+                None => return,
+            },
+        };
+        let error = SemanticError::new(
+            target.get_token_position(),
+            SemanticErrorType::AssignmentToConstant(name.clone()),
+        );
+        self.errors.push(match declaration {
+            Some(token) => error.with_label(
+                token.get_token_position(),
+                format!("`{}` is declared here", name),
+            ),
+            None => error,
+        });
+    }
+
+    fn infer_unary_expression(&mut self, children: &[Ast]) -> Type {
+        let operand = self.infer(&children[1]);
+        let operator = match children[0].token.as_ref().unwrap().get_token() {
+            Some(TokenBase::Reserved(r)) => *r,
+            _ => return Type::Any,
+        };
+        let expected = match operator {
+            ReservedWord::LogicalNot => Type::boolean(),
+            // `-10n` and `~10n` are bigints but `+10n` throws a TypeError
+            ReservedWord::Sub | ReservedWord::Not if operand == Type::bigint() => Type::bigint(),
+            _ => Type::number(),
+        };
+        if !expected.accepts(&operand) {
+            let token = children[0].token.clone().unwrap();
+            self.add_error(
+                &token,
+                SemanticErrorType::InvalidOperand {
+                    operator,
+                    found: operand,
+                },
+            );
+        }
+        expected
+    }
+
+    fn binary_result(
+        &mut self,
+        operator: ReservedWord,
+        left: &Type,
+        right: &Type,
+        operator_ast: &Ast,
+    ) -> Type {
+        let (number, string, boolean) = (Type::number(), Type::string(), Type::boolean());
+        let bigint = Type::bigint();
+        let both = |expected: &Type| expected.accepts(left) && expected.accepts(right);
+        // `number` and `bigint` cannot be mixed except in comparisons
+        let numeric = || {
+            [&number, &bigint]
+                .iter()
+                .find(|x| both(x))
+                .map(|x| (*x).clone())
+        };
+        let result = match operator {
+            ReservedWord::Add => {
+                if *left == Type::Any || *right == Type::Any {
+                    Some(Type::Any)
+                } else if let Some(ty) = numeric() {
+                    Some(ty)
+                } else if (*left == string || *right == string)
+                    && [left, right].iter().all(|x| **x != Type::void())
+                {
+                    Some(string)
+                } else {
+                    None
+                }
+            }
+            ReservedWord::Sub
+            | ReservedWord::Mult
+            | ReservedWord::Div
+            | ReservedWord::Mod
+            | ReservedWord::Exponential
+            | ReservedWord::LeftShift
+            | ReservedWord::RightShift
+            | ReservedWord::And
+            | ReservedWord::Or
+            | ReservedWord::Xor => numeric(),
+            // bigints have no unsigned right shift
+            ReservedWord::UnsignedRightShift => both(&number).then_some(number.clone()),
+            ReservedWord::Less
+            | ReservedWord::Greater
+            | ReservedWord::LessOrEq
+            | ReservedWord::GreaterOrEq => {
+                let is_numeric = |x: &Type| number.accepts(x) || bigint.accepts(x);
+                ((is_numeric(left) && is_numeric(right)) || both(&string)).then_some(boolean)
+            }
+            ReservedWord::Equal | ReservedWord::NotEqual => {
+                (left.accepts(right) || right.accepts(left)).then_some(boolean)
+            }
+            ReservedWord::LogicalAnd | ReservedWord::LogicalOr => both(&boolean).then_some(boolean),
+            _ => Some(Type::Any),
+        };
+        result.unwrap_or_else(|| {
+            let token = operator_ast.get_first_token().unwrap().clone();
+            self.add_error(
+                &token,
+                SemanticErrorType::MismatchedOperands {
+                    operator,
+                    left: left.clone(),
+                    right: right.clone(),
+                },
+            );
+            Type::Any
+        })
+    }
+
+    fn to_binary_operator(assign_operator: ReservedWord) -> Option<ReservedWord> {
+        match assign_operator {
+            ReservedWord::AdditiveAssign => Some(ReservedWord::Add),
+            ReservedWord::SubtractiveAssign => Some(ReservedWord::Sub),
+            ReservedWord::MultiplicativeAssign => Some(ReservedWord::Mult),
+            ReservedWord::DivisiveAssign => Some(ReservedWord::Div),
+            ReservedWord::ModuloAssign => Some(ReservedWord::Mod),
+            ReservedWord::LeftShiftAssign => Some(ReservedWord::LeftShift),
+            ReservedWord::RightShiftAssign => Some(ReservedWord::RightShift),
+            ReservedWord::UnsignedRightShiftAssign => Some(ReservedWord::UnsignedRightShift),
+            ReservedWord::AndAssign => Some(ReservedWord::And),
+            ReservedWord::XorAssign => Some(ReservedWord::Xor),
+            ReservedWord::OrAssign => Some(ReservedWord::Or),
+            ReservedWord::ExponentialAssign => Some(ReservedWord::Exponential),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{analyze, parse};
+
+    fn check_source(source: &str) -> Vec<String> {
+        check_files(&[("./test.rots", source)])
+            .into_iter()
+            .map(|x| x.trim_start_matches("./test.rots:").to_string())
+            .collect()
+    }
+
+    /// Checks the files of (path, source) and returns the errors as `path:ln:col: message`.
+    fn check_files(files: &[(&str, &str)]) -> Vec<String> {
+        let asts = parse(files);
+        let project = analyze(&asts);
+        project
+            .get_semantic_errors()
+            .iter()
+            .map(|x| {
+                let position = x.get_position();
+                format!(
+                    "{}:{}:{}: {}",
+                    position.path,
+                    position.ln,
+                    position.col,
+                    x.get_type()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_well_typed_source_test() {
+        let errors = check_source(
+            r#"
+const add = (a: number, b: number): number => { a + b };
+const greet = (name: string) => { "Hello, " + name };
+const main = () => {
+    let x: number = add(1, 2) * 3;
+    let ok: boolean = x >= 2 && !false;
+    let message: string = greet("world") + x;
+    x = x + 1;
+    console.log(message, ok);
+};
+"#,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn check_declaration_mismatch_test() {
+        let errors = check_source(r#"const a: number = "x";"#);
+        assert_eq!(
+            vec!["1:19: mismatched types: expected `number`, found `string`"],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_template_literal_test() {
+        let errors = check_source("const a: number = `${1}`;\nconst c: string = `${1 + true}`;");
+        assert_eq!(
+            vec![
+                "1:19: mismatched types: expected `number`, found `string`",
+                "2:24: cannot apply operator `+` to types `number` and `boolean`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_return_type_mismatch_test() {
+        let errors = check_source("const f = (a: number): string => { a + 1 };");
+        assert_eq!(
+            vec!["1:36: mismatched types: expected `string`, found `number`"],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_call_test() {
+        let errors = check_source(
+            r#"
+const add = (a: number, b: number): number => { a + b };
+const main = () => {
+    add("1", 2);
+    add(1);
+    let s: string = add(1, 2);
+};
+"#,
+        );
+        assert_eq!(
+            vec![
+                "4:9: mismatched types: expected `number`, found `string`",
+                "5:5: this function takes 2 argument(s) but 1 argument(s) were supplied",
+                "6:21: mismatched types: expected `string`, found `number`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_operator_test() {
+        let errors = check_source(
+            r#"
+const a = 1 - "a";
+const b = !1;
+const c = true == 1;
+"#,
+        );
+        assert_eq!(
+            vec![
+                "2:13: cannot apply operator `-` to types `number` and `string`",
+                "3:11: cannot apply operator `!` to type `number`",
+                "4:16: cannot apply operator `==` to types `boolean` and `number`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_bigint_test() {
+        let errors = check_source(
+            r#"
+const a: bigint = 0xFFn * -2n + (1n << 3n);
+const b: boolean = a > 1 && 1_000 <= a;
+const c: string = "a" + 10n;
+const d = a + 1;
+const e = +a;
+const f = a >>> 1n;
+const g: number = 10n;
+"#,
+        );
+        assert_eq!(
+            vec![
+                "5:13: cannot apply operator `+` to types `bigint` and `number`",
+                "6:11: cannot apply operator `+` to type `bigint`",
+                "7:13: cannot apply operator `>>>` to types `bigint` and `bigint`",
+                "8:19: mismatched types: expected `number`, found `bigint`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_if_expression_test() {
+        let errors = check_source(
+            r#"
+const a = (n: number): string => { if n > 0 { "positive" } else { "negative" } };
+const b = (n: number) => { if n { 1 } else { 2 } };
+const c = (n: number) => { if n > 0 { 1 } else { "zero" } };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "3:31: mismatched types: expected `boolean`, found `number`",
+                "4:50: mismatched types: expected `number`, found `string`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_match_expression_test() {
+        let errors = check_source(
+            r#"
+const a = (n: number): string => { match n { 0 => "zero", m if m > 0 => "positive", _ => "negative" } };
+const b = (f: boolean): number => { match f { true => 1, false => 0 } };
+const c = (n: number) => { match n { 0 => 1, 1 => "one", "two" => 2, _ => 3 } };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "4:51: mismatched types: expected `number`, found `string`",
+                "4:58: mismatched types: expected `number`, found `string`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_non_exhaustive_match_test() {
+        let errors = check_source(
+            r#"
+const a = (f: boolean) => { match f { true => 1 } };
+const b = (n: number) => { match n { 0 => 1, m if m > 0 => 2 } };
+const c = (f: boolean) => { match f { true => 1, _ if f => 2, false => 3 } };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "2:35: non-exhaustive patterns: `false` not covered",
+                "3:34: non-exhaustive patterns: `_` not covered",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_loop_statements_test() {
+        let errors = check_source(
+            r#"
+const a = (n: number) => { for i in 0..n { let s: string = i; } };
+const b = (n: string) => { for i in 0..=n { } };
+const c = (n: number) => { while n { } };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "2:60: mismatched types: expected `string`, found `number`",
+                "3:41: mismatched types: expected `number`, found `string`",
+                "4:34: mismatched types: expected `boolean`, found `number`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_struct_test() {
+        let errors = check_source(
+            r#"
+struct Point { x: number, y: number }
+struct Vec2 { x: number, y: number }
+const len = (p: Point): number => { p.x + p.y };
+const a = () => { len(Vec2 { x: 1, y: 2 }) };
+const b = () => { let p = Point { x: 1, z: 2 }; p.x = "1"; p.w };
+const c = () => { len(Point { x: 1, y: 2 }) + Point { x: 1, y: 2 }.y };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "5:23: mismatched types: expected `Point`, found `Vec2`",
+                "6:27: missing field(s) `y` in initializer of `Point`",
+                "6:41: no field `z` on type `Point`",
+                "6:55: mismatched types: expected `number`, found `string`",
+                "6:62: no field `w` on type `Point`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_enum_test() {
+        let errors = check_source(
+            r#"
+enum Shape { Circle(number), Rect { w: number, h: number }, Empty }
+const area = (s: Shape): number => { match s { Shape::Circle(r) => r * r, Shape::Rect { w, h } => w * h, Shape::Empty => 0 } };
+const a = () => { area(Shape::Circle("1")) + area(Shape::Rect { w: 1 }) + area(Shape::Square) };
+const b = () => { let s: Shape = Shape::Rect; };
+const c = (s: Shape) => { match s { Shape::Circle(r, x) => 1, Shape::Empty(e) => 2, _ => 3 } };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "4:38: mismatched types: expected `number`, found `string`",
+                "4:51: missing field(s) `h` in initializer of `Shape`",
+                "4:87: no variant `Square` on type `Shape`",
+                "5:34: missing field(s) `w`, `h` in initializer of `Shape`",
+                "6:37: this pattern has 2 field(s) but the variant has 1 field(s)",
+                "6:63: expected tuple variant, found `Shape::Empty`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_non_exhaustive_enum_match_test() {
+        let errors = check_source(
+            r#"
+enum Shape { Circle(number), Rect { w: number, h: number }, Empty }
+const a = (s: Shape) => { match s { Shape::Circle(0) => 1, Shape::Rect { w, h } => 2 } };
+const b = (s: Shape) => { match s { Shape::Circle(_) => 1, Shape::Rect { w: 0 } => 2, Shape::Rect => 3, Shape::Empty => 4 } };
+enum Bad { A { kind: string } }
+"#,
+        );
+        assert_eq!(
+            vec![
+                "3:33: non-exhaustive patterns: `Shape::Circle` and `Shape::Empty` not covered",
+                "5:16: field name `kind` is reserved in enum variants",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_duplicate_declaration_test() {
+        let errors = check_source(
+            r#"
+struct Point { x: number, y: number, x: string }
+const a = 1;
+const a = "1";
+enum Point { A }
+const Point = 2;
+const b: number = Point { x: 1, y: 2 }.x;
+"#,
+        );
+        assert_eq!(
+            vec![
+                "2:38: field `x` is already declared",
+                "4:7: the name `a` is defined multiple times",
+                "5:6: the name `Point` is defined multiple times",
+                "6:7: the name `Point` is defined multiple times",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_duplicate_variant_test() {
+        let errors = check_source(
+            r#"
+enum Shape { Circle(number), Rect { w: number, w: number }, Circle }
+const a = (s: Shape) => { match s { Shape::Circle(r) => r, Shape::Rect { w } => w } };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "2:48: field `w` is already declared",
+                "2:61: variant `Circle` is already declared",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_duplicate_binding_test() {
+        let errors = check_source(
+            r#"
+const a = (x: number, y: number, x: string) => { x };
+const b = (n: number) => { const m = 1; let m = 2; const n = 3; { const m = "4"; m } };
+const c = () => { for i in 0..10 { const i = 1; } };
+"#,
+        );
+        assert_eq!(
+            vec![
+                "2:34: the name `x` is defined multiple times",
+                "3:45: the name `m` is defined multiple times",
+                "3:58: the name `n` is defined multiple times",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_assignment_to_constant_test() {
+        let errors = check_source(
+            r#"
+import { x } from "./lib.js";
+struct Point { x: number }
+const a = 1;
+const b = (n: number) => {
+    const c = 1;
+    let d = 2;
+    const p = Point { x: 1 };
+    n = 1; c = 2; d = 3; a = 4; x = 5; p.x = 6; console = 7;
+    match n { m => { m += 1; } }
+    for i in 0..10 { i = 1; }
+};
+"#,
+        );
+        assert_eq!(
+            vec![
+                "9:12: cannot assign to `c` because it is a constant",
+                "9:26: cannot assign to `a` because it is a constant",
+                "9:33: cannot assign to `x` because it is a constant",
+                "10:22: cannot assign to `m` because it is a constant",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_unknown_type_test() {
+        let errors = check_source("const a: numbr = 1;");
+        assert_eq!(vec!["1:10: cannot find type `numbr`"], errors);
+    }
+
+    #[test]
+    fn check_imports_test() {
+        let errors = check_files(&[
+            (
+                "./main.rots",
+                r#"import { add, Point, Shape, hidden, missing } from "./math";
+import { f } from "./none.rots";
+import { readFile } from "fs";
+export const p: Point = Point { x: 1 };
+export const q: Point = Point { x: "1" };
+export const s: string = add(1, 2);
+export const n: number = Shape::Empty;
+export const r: string = readFile;"#,
+            ),
+            (
+                "./math.rots",
+                r#"export const add = (a: number, b: number) => { a + b };
+const hidden = 1;
+export struct Point { x: number }
+export enum Shape { Empty }"#,
+            ),
+        ]);
+        assert_eq!(
+            vec![
+                "./main.rots:1:29: module `./math` has no exported member `hidden`",
+                "./main.rots:1:37: module `./math` has no exported member `missing`",
+                "./main.rots:2:19: cannot find module `./none.rots`",
+                "./main.rots:5:36: mismatched types: expected `number`, found `string`",
+                "./main.rots:6:26: mismatched types: expected `string`, found `number`",
+                "./main.rots:7:26: mismatched types: expected `number`, found `Shape`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_import_cycle_test() {
+        let errors = check_files(&[
+            (
+                "./a.rots",
+                r#"import { b } from "./b"; export const a = 1;"#,
+            ),
+            (
+                "./b.rots",
+                r#"import { c } from "./c"; export const b = 1;"#,
+            ),
+            (
+                "./c.rots",
+                r#"import { a } from "./a"; export const c = 1;"#,
+            ),
+        ]);
+        assert_eq!(
+            vec!["./c.rots:1:19: import cycle detected: `./c.rots` -> `./a.rots` -> `./b.rots` -> `./c.rots`"],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_default_import_test() {
+        let errors = check_files(&[
+            (
+                "./main.rots",
+                r#"import times from "./math";
+import none from "./util";
+import P from "./lib";
+export const s: string = times(2, 3);
+export const p: P = P { x: "1" };"#,
+            ),
+            (
+                "./math.rots",
+                "export default const mul = (a: number, b: number) => { a * b };",
+            ),
+            ("./util.rots", "export const u = 1;"),
+            (
+                "./lib.rots",
+                "export default struct P { x: number }\nexport default const two = 2;",
+            ),
+        ]);
+        assert_eq!(
+            vec![
+                "./lib.rots:2:22: a module cannot have multiple default exports",
+                "./main.rots:2:8: module `./util` has no default export",
+                "./main.rots:4:26: mismatched types: expected `string`, found `number`",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn check_re_export_test() {
+        let errors = check_files(&[
+            (
+                "./main.rots",
+                r#"import { plus, Point as P, times, missing } from "./lib/index";
+import * as lib from "./lib/index";
+export const a: string = plus(1, 2);
+export const b: P = P { x: "1" };
+export const c: string = times(1, 2);
+export const d: string = lib.plus(1, 2);
+export const e = lib.nothing;
+export const f = (lib: number) => { lib.nothing };"#,
+            ),
+            (
+                "./lib/index.rots",
+                r#"export { add as plus } from "./math";
+export * from "./math";
+export * from "./types";
+export { mul as times } from "./ops.js";"#,
+            ),
+            (
+                "./lib/math.rots",
+                r#"export * from "./index";
+export const add = (a: number, b: number) => { a + b };"#,
+            ),
+            ("./lib/types.rots", "export struct Point { x: number }"),
+        ]);
+        assert_eq!(
+            vec![
+                "./lib/math.rots:1:15: import cycle detected: `./lib/math.rots` -> `./lib/index.rots` -> `./lib/math.rots`",
+                "./main.rots:1:35: module `./lib/index` has no exported member `missing`",
+                "./main.rots:3:26: mismatched types: expected `string`, found `number`",
+                "./main.rots:4:28: mismatched types: expected `number`, found `string`",
+                "./main.rots:6:26: mismatched types: expected `string`, found `number`",
+                "./main.rots:7:22: module `./lib/index` has no exported member `nothing`",
+            ],
+            errors
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use serde::Deserialize;
+
+/// Associates a position in the generated code with a position in the source.
+#[derive(Debug, Clone)]
+pub struct Mapping {
+    /// byte offset in the generated code
+    pub generated: usize,
+    /// 0-based line in the source
+    pub source_line: u32,
+    /// 0-based column in the source, counted in UTF-16 code units
+    pub source_column: u32,
+}
+
+/// How the source map of the generated code is provided.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceMapKind {
+    #[default]
+    None,
+    /// `//# sourceMappingURL=file.js.map` (the map is written next to the code)
+    File,
+    /// `//# sourceMappingURL=data:application/json;base64,...`
+    Inline,
+}
+
+/// Source map revision 3 of a generated file which has a single source.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    pub file: String,
+    pub sources: Vec<String>,
+    pub sources_content: Option<Vec<String>>,
+    pub mappings: String,
+}
+
+impl SourceMap {
+    pub fn new(
+        file: String,
+        source: String,
+        source_content: Option<String>,
+        code: &str,
+        mappings: &[Mapping],
+    ) -> Self {
+        Self {
+            file,
+            sources: vec![source],
+            sources_content: source_content.map(|x| vec![x]),
+            mappings: Self::encode_mappings(code, mappings),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let sources = self
+            .sources
+            .iter()
+            .map(|x| to_json_string(x))
+            .collect::<Vec<_>>();
+        let mut json = format!(
+            r#"{{"version":3,"file":{},"sources":[{}],"#,
+            to_json_string(&self.file),
+            sources.join(",")
+        );
+        if let Some(contents) = &self.sources_content {
+            let contents = contents
+                .iter()
+                .map(|x| to_json_string(x))
+                .collect::<Vec<_>>();
+            json.push_str(&format!(r#""sourcesContent":[{}],"#, contents.join(",")));
+        }
+        json.push_str(&format!(
+            r#""names":[],"mappings":{}}}"#,
+            to_json_string(&self.mappings)
+        ));
+        json
+    }
+
+    /// Returns the comment which refers to the map written at `map_file_name`.
+    pub fn to_url_comment(map_file_name: &str) -> String {
+        format!("//# sourceMappingURL={}\n", map_file_name)
+    }
+
+    /// Returns the comment which embeds the map.
+    pub fn to_inline_comment(&self) -> String {
+        format!(
+            "//# sourceMappingURL=data:application/json;base64,{}\n",
+            encode_base64(self.to_json().as_bytes())
+        )
+    }
+
+    /// Encodes the mappings to `;`-separated lines of `,`-separated Base64 VLQ segments.
+    /// Each segment is [generated column, source index, source line, source column],
+    /// relative to the previous segment.
+    fn encode_mappings(code: &str, mappings: &[Mapping]) -> String {
+        let mut mappings = mappings.iter().collect::<Vec<_>>();
+        mappings.sort_by_key(|x| x.generated);
+        let mut mappings = mappings.into_iter().peekable();
+
+        let mut result = String::new();
+        let mut column = 0;
+        let mut previous_column = 0;
+        let mut previous_source = (0, 0);
+        let mut is_first_segment = true;
+        let positions = code.char_indices().map(Some).chain(std::iter::once(None));
+        for position in positions {
+            let offset = position.map_or(code.len(), |(ind, _)| ind);
+            while let Some(mapping) = mappings.next_if(|x| x.generated <= offset) {
+                if !is_first_segment {
+                    result.push(',');
+                }
+                is_first_segment = false;
+                let source = (mapping.source_line as i64, mapping.source_column as i64);
+                encode_vlq(column - previous_column, &mut result);
+                encode_vlq(0, &mut result);
+                encode_vlq(source.0 - previous_source.0, &mut result);
+                encode_vlq(source.1 - previous_source.1, &mut result);
+                previous_column = column;
+                previous_source = source;
+            }
+            match position {
+                Some((_, '\n')) => {
+                    result.push(';');
+                    column = 0;
+                    previous_column = 0;
+                    is_first_segment = true;
+                }
+                // columns are counted in UTF-16 code units
+                Some((_, c)) => column += c.len_utf16() as i64,
+                None => {}
+            }
+        }
+        result
+    }
+}
+
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Appends the value as Base64 VLQ (the sign is the least significant bit).
+fn encode_vlq(value: i64, result: &mut String) {
+    let mut vlq = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+    loop {
+        let mut digit = vlq & 0b11111;
+        vlq >>= 5;
+        if vlq > 0 {
+            // continuation bit
+            digit |= 0b100000;
+        }
+        result.push(BASE64_CHARS[digit as usize] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (ind, x)| n | (*x as u32) << (16 - ind * 8));
+        for ind in 0..4 {
+            if ind <= chunk.len() {
+                let digit = (n >> (18 - ind * 6)) & 0b111111;
+                result.push(BASE64_CHARS[digit as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+pub(crate) fn to_json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_base64, encode_vlq, Mapping, SourceMap};
+
+    #[test]
+    fn encode_vlq_test() {
+        let cases = [
+            (0, "A"),
+            (1, "C"),
+            (-1, "D"),
+            (15, "e"),
+            (16, "gB"),
+            (-17, "jB"),
+        ];
+        for (value, expected) in cases.iter() {
+            let mut result = String::new();
+            encode_vlq(*value, &mut result);
+            assert_eq!(*expected, result);
+        }
+    }
+
+    #[test]
+    fn encode_base64_test() {
+        assert_eq!("TWFu", encode_base64(b"Man"));
+        assert_eq!("TWE=", encode_base64(b"Ma"));
+        assert_eq!("TQ==", encode_base64(b"M"));
+    }
+
+    #[test]
+    fn source_map_test() {
+        let code = "const a = 1;\nconst b = a;\n";
+        let mapping = |generated, source_line, source_column| Mapping {
+            generated,
+            source_line,
+            source_column,
+        };
+        let mappings = [
+            mapping(6, 0, 6),
+            mapping(10, 0, 10),
+            mapping(19, 2, 6),
+            mapping(23, 2, 14),
+        ];
+        let map = SourceMap::new(
+            "a.js".to_string(),
+            "a.rots".to_string(),
+            Some("const a = 1;\n\nconst b = a;".to_string()),
+            code,
+            &mappings,
+        );
+        assert_eq!("MAAM,IAAI;MAEJ,IAAQ;", map.mappings);
+        assert_eq!(
+            r#"{"version":3,"file":"a.js","sources":["a.rots"],"sourcesContent":["const a = 1;\n\nconst b = a;"],"names":[],"mappings":"MAAM,IAAI;MAEJ,IAAQ;"}"#,
+            map.to_json()
+        );
+    }
+}
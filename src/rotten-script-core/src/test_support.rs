@@ -0,0 +1,37 @@
+//! Helpers shared by the unit tests.
+
+use crate::{
+    compile::parse_source,
+    diagnostic::Diagnostics,
+    lexer::{token::Token, Lexer},
+    parser::ast::Ast,
+    semantic_analyzer::{self, project::Project},
+};
+
+/// Lexes the source for the tests which drive the parser directly. Panics on a lex error.
+pub fn lex(source: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(source, "");
+    lexer.lex().unwrap();
+    lexer.tokens
+}
+
+/// Parses the files of (path, source). Panics on a lex or parse error.
+pub fn parse(files: &[(&str, &str)]) -> Vec<(String, Ast)> {
+    let mut diagnostics = Diagnostics::new();
+    let asts = files
+        .iter()
+        .filter_map(|(path, source)| {
+            Some((
+                path.to_string(),
+                parse_source(path, source, &mut diagnostics)?,
+            ))
+        })
+        .collect();
+    assert!(diagnostics.is_empty(), "{}", diagnostics.render(|_| None));
+    asts
+}
+
+/// Analyzes the parsed files of (path, AST).
+pub fn analyze(asts: &[(String, Ast)]) -> Project<'_> {
+    semantic_analyzer::analyze(asts.iter().map(|(path, ast)| (path.clone(), ast)).collect())
+}
@@ -1,70 +1,581 @@
-use std::{env, fs, io};
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    io::{self, IsTerminal},
+    path::{Component, Path, PathBuf},
+    process,
+};
 
 use rotten_script_core::{
-    lexer::Lexer,
-    parser::{token_stack::TokenStack, Parser},
-    semantic_analyzer::analyze,
+    builder::BuildOutput,
+    compile::{check as check_project, compile, parse_source},
+    diagnostic::Diagnostics,
+    manifest::{Manifest, ModuleFormat, Target, MANIFEST_FILE_NAME},
+    parser::ast::Ast,
+    semantic_analyzer::{analyze_project, get_output_file_name},
+    source_map::{SourceMap, SourceMapKind},
 };
 
-const HELP_TEXT: &str = "Usage: rotc [PROJECT-PATH]";
+mod watch;
+
+const HELP_TEXT: &str = "Usage:
+    rotc [PROJECT-PATH]
+        Print the AST and the transpiled files.
+    rotc build [PROJECT-PATH] [--out-dir <DIR>]
+        Write the transpiled files to DIR (default: `out-dir` in rotten.toml), which
+        must not contain the source roots. Only the files written by the previous
+        build are removed when they become stale.
+    rotc check [PROJECT-PATH] [--message-format <FORMAT>]
+        Report the errors without writing files. FORMAT is `human` (default) or
+        `json`, which prints a JSON object per line for editors and CI.
+    rotc watch [PROJECT-PATH] [--out-dir <DIR>] [--interval <MILLISECONDS>]
+        Build the project and rebuild the changed files and their importers
+        whenever the sources change (polls every 500ms by default).
+
+Options:
+    --color <WHEN>
+        Colorize the diagnostics: auto (default), always or never.
+
+The files to compile, the output directory, the target and the module format
+are read from PROJECT-PATH/rotten.toml if it exists.";
+
+/// The file in the output directory which lists the files written by the last build, so that
+/// only they are removed when they become stale.
+const OUTPUT_MANIFEST_FILE_NAME: &str = ".rotc-outputs";
 
 fn main() {
-    let args = env::args().collect::<Vec<_>>();
-    let path = if args.len() >= 2 {
-        args[1].clone()
-    } else {
-        println!("{}", HELP_TEXT);
-        return;
-    };
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    if let Err(e) = set_color(&mut args) {
+        eprintln!("error: {}\n\n{}", e, HELP_TEXT);
+        process::exit(2);
+    }
+    match args.first().map(|x| x.as_str()) {
+        Some("build") => process::exit(build(&args[1..])),
+        Some("check") => process::exit(check(&args[1..])),
+        Some("watch") => process::exit(watch::watch(&args[1..])),
+        Some("-h") | Some("--help") | None => println!("{}", HELP_TEXT),
+        Some(path) => print_project(path),
+    }
+}
 
-    let files = get_directory_files_recursive(&path).unwrap();
+fn print_project(path: &str) {
+    let manifest = match load_manifest(path) {
+        Some(manifest) => manifest,
+        None => return,
+    };
+    let files = get_source_files(path, &manifest).unwrap();
 
     let content_file_pair = files
         .iter()
         .map(|x| (x.to_string(), fs::read_to_string(x).unwrap()))
         .collect::<Vec<_>>();
 
-    let mut has_error = false;
-    // TODO: Replace for stmt
-    let ast_pairs = content_file_pair
-        .iter()
-        .map(|x| {
-            let mut lexer = Lexer::new(&x.1, &x.0);
-            let lexer_result = lexer.lex();
-            match lexer_result {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("{}", e);
-                    has_error = true;
-                }
+    let ast_pairs = match parse_files(&content_file_pair) {
+        Some(ast_pairs) => ast_pairs,
+        None => return,
+    };
+    for (_, ast) in &ast_pairs {
+        println!("{}", ast.unparse());
+    }
+
+    let project = analyze_project(
+        &manifest,
+        ast_pairs.iter().map(|x| (x.0.clone(), &x.1)).collect(),
+    );
+    let mut diagnostics = Diagnostics::new();
+    project.report_errors(&mut diagnostics);
+    if diagnostics.has_errors() {
+        print_diagnostics(&diagnostics, &content_file_pair);
+        return;
+    }
+    let mut options = manifest.build_options();
+    options.is_debug = true;
+    let result = project.build(&options);
+
+    for item in result {
+        println!("// {}", item.0);
+        println!("{}", item.1.code);
+        println!("// {}.d.ts", item.0.trim_end_matches(".rots"));
+        println!("{}", item.1.declaration);
+    }
+}
+
+/// Removes `--color <WHEN>` from the arguments and applies it to the diagnostics.
+/// `auto` colorizes them only if stdout is a terminal.
+fn set_color(args: &mut Vec<String>) -> Result<(), String> {
+    let mut value = "auto".to_string();
+    if let Some(index) = args.iter().position(|x| x.starts_with("--color=")) {
+        value = args.remove(index)["--color=".len()..].to_string();
+    } else if let Some(index) = args.iter().position(|x| x == "--color") {
+        args.remove(index);
+        if index == args.len() {
+            return Err("`--color` requires a value".to_string());
+        }
+        value = args.remove(index);
+    }
+    match value.as_str() {
+        "auto" => {
+            if !io::stdout().is_terminal() {
+                colored::control::set_override(false);
             }
-            // println!("{:?}", lexer.tokens);
-            let token_stack = &mut TokenStack::new(&lexer.tokens);
-            let mut parser = Parser::new(token_stack);
-            match parser.parse() {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("{}", e);
-                    has_error = true;
+        }
+        "always" => colored::control::set_override(true),
+        "never" => colored::control::set_override(false),
+        _ => {
+            return Err(format!(
+                "invalid value `{}` for `--color` (expected `auto`, `always` or `never`)",
+                value
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Runs `rotc check` and returns the exit code.
+fn check(args: &[String]) -> i32 {
+    let (project_path, options) = match parse_args(args, &["--message-format"]) {
+        Some(args) => args,
+        None => return 2,
+    };
+    let is_json = match options.get("--message-format").map(|x| x.as_str()) {
+        None | Some("human") => false,
+        Some("json") => true,
+        Some(format) => {
+            eprintln!(
+                "error: invalid value `{}` for `--message-format` (expected `human` or `json`)",
+                format
+            );
+            return 2;
+        }
+    };
+    let manifest = match load_manifest(&project_path) {
+        Some(manifest) => manifest,
+        None => return 1,
+    };
+    let content_file_pair = match read_source_files(&project_path, &manifest) {
+        Some(content_file_pair) => content_file_pair,
+        None => return 1,
+    };
+
+    let diagnostics = check_project(&manifest, &content_file_pair);
+    if is_json {
+        for diagnostic in &diagnostics {
+            println!("{}", diagnostic.to_json());
+        }
+    } else if diagnostics.is_empty() {
+        println!(
+            "{}: checked {} file(s)",
+            manifest.name,
+            content_file_pair.len()
+        );
+    } else {
+        print_diagnostics(&diagnostics, &content_file_pair);
+    }
+    if diagnostics.has_errors() {
+        1
+    } else {
+        0
+    }
+}
+
+/// Runs `rotc build` and returns the exit code.
+fn build(args: &[String]) -> i32 {
+    let (project_path, mut options) = match parse_args(args, &["--out-dir"]) {
+        Some(args) => args,
+        None => return 2,
+    };
+    let out_dir = options.remove("--out-dir");
+    let manifest = match load_manifest(&project_path) {
+        Some(manifest) => manifest,
+        None => return 1,
+    };
+    let out_dir = get_out_dir(&project_path, out_dir.as_deref(), &manifest);
+    if let Err(e) = check_out_dir(&project_path, &out_dir, &manifest) {
+        eprintln!("error: {}", e);
+        return 1;
+    }
+    let content_file_pair = match read_source_files(&project_path, &manifest) {
+        Some(content_file_pair) => content_file_pair,
+        None => return 1,
+    };
+
+    let mut options = manifest.build_options();
+    options.source_map = get_source_map_kind(&manifest);
+    options.sources_content = content_file_pair.iter().cloned().collect();
+    let output = compile(&manifest, &content_file_pair, &options);
+    if output.diagnostics.has_errors() {
+        print_diagnostics(&output.diagnostics, &content_file_pair);
+        return 1;
+    }
+    match write_outputs(&project_path, &out_dir, &manifest, output.outputs) {
+        Ok(count) => {
+            println!(
+                "{}: wrote {} file(s) to {}",
+                manifest.name,
+                count,
+                out_dir.display()
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("error: cannot write to `{}`: {}", out_dir.display(), e);
+            1
+        }
+    }
+}
+
+/// Parses `[PROJECT-PATH] [--option <VALUE>]...` and returns the project path and the values of
+/// `options`. Prints the error and returns `None` if the arguments are invalid.
+fn parse_args(
+    args: &[String],
+    options: &[&'static str],
+) -> Option<(String, HashMap<&'static str, String>)> {
+    let mut project_path = None;
+    let mut values = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        match options.iter().find(|x| **x == name) {
+            Some(option) => match value.or_else(|| args.next().cloned()) {
+                Some(value) => {
+                    values.insert(*option, value);
+                }
+                None => {
+                    eprintln!("error: `{}` requires a value\n\n{}", option, HELP_TEXT);
+                    return None;
                 }
+            },
+            None if arg.starts_with('-') || project_path.is_some() => {
+                eprintln!("error: unexpected argument `{}`\n\n{}", arg, HELP_TEXT);
+                return None;
             }
-            println!("{}", parser.ast.unparse());
-            (x.0.clone(), parser.ast)
+            None => project_path = Some(arg.clone()),
+        }
+    }
+    Some((project_path.unwrap_or_else(|| ".".to_string()), values))
+}
+
+/// Returns `--out-dir` if it is given, otherwise `out-dir` of the manifest in the project.
+fn get_out_dir(project_path: &str, out_dir: Option<&str>, manifest: &Manifest) -> PathBuf {
+    match out_dir {
+        Some(out_dir) => PathBuf::from(out_dir),
+        None => Path::new(project_path).join(&manifest.out_dir),
+    }
+}
+
+/// Returns an error if `out_dir` is a source root or contains one, where writing the outputs
+/// would mix them with the sources.
+fn check_out_dir(project_path: &str, out_dir: &Path, manifest: &Manifest) -> Result<(), String> {
+    let absolute_out_dir = absolute_path(out_dir).map_err(|e| e.to_string())?;
+    for root in &manifest.source_roots {
+        let root = match Path::new(project_path).join(root).canonicalize() {
+            Ok(root) => root,
+            // the source root does not exist and has no source
+            Err(_) => continue,
+        };
+        if root.starts_with(&absolute_out_dir) {
+            return Err(format!(
+                "the output directory `{}` must not contain the source root `{}`",
+                out_dir.display(),
+                root.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the absolute path of `path`, which may not exist yet.
+fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    if let Ok(path) = path.canonicalize() {
+        return Ok(path);
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if parent.as_os_str().is_empty() => {
+            Ok(env::current_dir()?.join(name))
+        }
+        (Some(parent), Some(name)) => Ok(absolute_path(parent)?.join(name)),
+        _ => Ok(env::current_dir()?.join(path)),
+    }
+}
+
+/// Writes the outputs to `out_dir` keeping the relative paths from the source roots,
+/// removes the stale outputs and returns the number of the written files.
+fn write_outputs(
+    project_path: &str,
+    out_dir: &Path,
+    manifest: &Manifest,
+    outputs: HashMap<String, BuildOutput>,
+) -> io::Result<usize> {
+    let previous = read_output_manifest(out_dir);
+    let mut written = HashSet::new();
+    written.extend(write_package_json(out_dir, manifest, &previous)?);
+    for (path, output) in outputs {
+        written.extend(write_output(
+            project_path,
+            out_dir,
+            manifest,
+            &path,
+            output,
+        )?);
+    }
+    remove_stale_outputs(out_dir, &previous, &written)?;
+    write_output_manifest(out_dir, &written)?;
+    Ok(written.len())
+}
+
+/// Writes `package.json` for Node.js, which decides the module system of `.js` files by it.
+/// The existing file is kept unless it was written by the previous build (`previous`).
+fn write_package_json(
+    out_dir: &Path,
+    manifest: &Manifest,
+    previous: &HashSet<PathBuf>,
+) -> io::Result<Option<PathBuf>> {
+    if manifest.target != Target::Node {
+        return Ok(None);
+    }
+    let file = out_dir.join("package.json");
+    if let Ok(existing) = file.canonicalize() {
+        if !previous.contains(&existing) {
+            return Ok(None);
+        }
+    }
+    let module_type = match manifest.module_format {
+        ModuleFormat::Esm => "module",
+        ModuleFormat::CommonJs => "commonjs",
+    };
+    fs::create_dir_all(out_dir)?;
+    fs::write(&file, format!("{{ \"type\": \"{}\" }}\n", module_type))?;
+    Ok(Some(file.canonicalize()?))
+}
+
+/// Returns the kind of the source maps built for `write_output`. The inline maps are built as
+/// files too so that `write_output` makes their sources relative to the output before embedding them.
+fn get_source_map_kind(manifest: &Manifest) -> SourceMapKind {
+    match manifest.source_map {
+        SourceMapKind::None => SourceMapKind::None,
+        SourceMapKind::File | SourceMapKind::Inline => SourceMapKind::File,
+    }
+}
+
+/// Writes the outputs of the source file and returns the written files.
+fn write_output(
+    project_path: &str,
+    out_dir: &Path,
+    manifest: &Manifest,
+    path: &str,
+    output: BuildOutput,
+) -> io::Result<Vec<PathBuf>> {
+    let source = Path::new(path);
+    let relative = source.strip_prefix(project_path).unwrap_or(source);
+    let relative = relative.to_string_lossy();
+    let relative = Path::new(manifest.strip_source_root(&relative));
+    let dir = out_dir.join(relative.parent().unwrap_or_else(|| Path::new("")));
+    fs::create_dir_all(&dir)?;
+
+    let js_name = get_output_file_name(path);
+    let stem = js_name.trim_end_matches(".js");
+    let mut code = output.code;
+    let mut files = Vec::new();
+    if let Some(mut source_map) = output.source_map {
+        let source = relative_path(&dir.canonicalize()?, &source.canonicalize()?);
+        source_map.sources = vec![source.to_string_lossy().replace('\\', "/")];
+        let map_name = format!("{}.map", js_name);
+        if manifest.source_map == SourceMapKind::Inline {
+            let url_comment = SourceMap::to_url_comment(&map_name);
+            let code_without_url = code.strip_suffix(url_comment.as_str()).unwrap_or(&code);
+            code = format!("{}{}", code_without_url, source_map.to_inline_comment());
+        } else {
+            files.push((dir.join(map_name), source_map.to_json()));
+        }
+    }
+    files.insert(0, (dir.join(&js_name), code));
+    files.push((dir.join(format!("{}.d.ts", stem)), output.declaration));
+
+    let mut written = Vec::new();
+    for (file, content) in files {
+        fs::write(&file, content)?;
+        written.push(file.canonicalize()?);
+    }
+    Ok(written)
+}
+
+/// Returns the files (absolute paths) written by the last build to `out_dir`, which are listed in
+/// the output manifest. Returns an empty set if it has never been built.
+fn read_output_manifest(out_dir: &Path) -> HashSet<PathBuf> {
+    let (out_dir, content) = match (
+        out_dir.canonicalize(),
+        fs::read_to_string(out_dir.join(OUTPUT_MANIFEST_FILE_NAME)),
+    ) {
+        (Ok(out_dir), Ok(content)) => (out_dir, content),
+        _ => return HashSet::new(),
+    };
+    content
+        .lines()
+        .map(Path::new)
+        // ignore the paths pointing outside of `out_dir` (e.g. the file is edited by hand)
+        .filter(|x| {
+            x.components().next().is_some()
+                && x.components().all(|x| matches!(x, Component::Normal(_)))
         })
+        .map(|x| out_dir.join(x))
+        .collect()
+}
+
+/// Lists the written files (absolute paths) in the output manifest of `out_dir`.
+fn write_output_manifest(out_dir: &Path, written: &HashSet<PathBuf>) -> io::Result<()> {
+    fs::create_dir_all(out_dir)?;
+    let canonical_out_dir = out_dir.canonicalize()?;
+    let mut files = written
+        .iter()
+        .filter_map(|x| x.strip_prefix(&canonical_out_dir).ok())
+        .map(|x| x.to_string_lossy().replace('\\', "/"))
         .collect::<Vec<_>>();
-    if has_error {
-        return;
+    files.sort();
+    let mut content = files.join("\n");
+    content.push('\n');
+    fs::write(out_dir.join(OUTPUT_MANIFEST_FILE_NAME), content)
+}
+
+/// Removes the outputs of the previous build which are not written this time (e.g. the source
+/// file was deleted) and the directories which become empty by it. The other files in `out_dir`
+/// are kept even if they look like outputs.
+fn remove_stale_outputs(
+    out_dir: &Path,
+    previous: &HashSet<PathBuf>,
+    written: &HashSet<PathBuf>,
+) -> io::Result<()> {
+    let out_dir = out_dir.canonicalize()?;
+    for path in previous.difference(written) {
+        match fs::remove_file(path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        }
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|x| *x != out_dir && x.starts_with(&out_dir)) {
+            if fs::remove_dir(parent).is_err() {
+                // not empty
+                break;
+            }
+            dir = parent.parent();
+        }
     }
+    Ok(())
+}
 
-    let project = analyze(ast_pairs.iter().map(|x| (x.0.clone(), &x.1)).collect());
-    let result = project.call_builder(true);
+/// Returns the path of `target` relative to the directory `base` (both must be absolute).
+fn relative_path(base: &Path, target: &Path) -> PathBuf {
+    let base = base.components().collect::<Vec<_>>();
+    let target = target.components().collect::<Vec<_>>();
+    let common = base.iter().zip(&target).take_while(|(x, y)| x == y).count();
+    let mut result = PathBuf::new();
+    for _ in common..base.len() {
+        result.push("..");
+    }
+    for component in &target[common..] {
+        result.push(component);
+    }
+    result
+}
 
-    for item in result {
-        println!("// {}", item.0);
-        println!("{}", item.1);
+/// Reads the source files of the project and returns the pairs of (path, content).
+/// Prints the error and returns `None` if any file cannot be read.
+fn read_source_files(project_path: &str, manifest: &Manifest) -> Option<Vec<(String, String)>> {
+    let files = match get_source_files(project_path, manifest) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("error: cannot read `{}`: {}", project_path, e);
+            return None;
+        }
+    };
+    let mut content_file_pair = Vec::new();
+    for file in files {
+        match fs::read_to_string(&file) {
+            Ok(content) => content_file_pair.push((file, content)),
+            Err(e) => {
+                eprintln!("error: cannot read `{}`: {}", file, e);
+                return None;
+            }
+        }
+    }
+    Some(content_file_pair)
+}
+
+/// Lexes and parses the files and prints the errors.
+/// Returns `None` if any file has an error.
+fn parse_files(content_file_pair: &[(String, String)]) -> Option<Vec<(String, Ast)>> {
+    let mut diagnostics = Diagnostics::new();
+    let ast_pairs = content_file_pair
+        .iter()
+        .filter_map(|(path, content)| {
+            Some((path.clone(), parse_source(path, content, &mut diagnostics)?))
+        })
+        .collect();
+    if diagnostics.has_errors() {
+        print_diagnostics(&diagnostics, content_file_pair);
+        None
+    } else {
+        Some(ast_pairs)
+    }
+}
+
+/// Prints the diagnostics with the lines of the files (path, content).
+fn print_diagnostics(diagnostics: &Diagnostics, files: &[(String, String)]) {
+    print!("{}", diagnostics.render(|path| get_content(files, path)));
+}
+
+fn get_content<'a>(files: &'a [(String, String)], path: &str) -> Option<&'a str> {
+    files
+        .iter()
+        .find(|(x, _)| x == path)
+        .map(|(_, content)| content.as_str())
+}
+
+/// Reads `rotten.toml` of the project and prints the error if it is invalid.
+/// The default settings are used if the project has no manifest.
+fn load_manifest(project_path: &str) -> Option<Manifest> {
+    let path = Path::new(project_path).join(MANIFEST_FILE_NAME);
+    if !path.is_file() {
+        let name = fs::canonicalize(project_path)
+            .ok()
+            .and_then(|x| Some(x.file_name()?.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        return Some(Manifest::new(name));
+    }
+    let result = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|x| Manifest::parse(&x).map_err(|e| e.to_string()));
+    match result {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            eprintln!("error: {}\n\t --> {}", e, path.display());
+            None
+        }
     }
 }
 
+/// Returns the files in the source roots which the manifest selects.
+fn get_source_files(project_path: &str, manifest: &Manifest) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    for root in &manifest.source_roots {
+        let root = if root == "." {
+            project_path.to_string()
+        } else {
+            format!("{}/{}", project_path, root)
+        };
+        for file in get_directory_files_recursive(&root)? {
+            let relative = Path::new(&file).strip_prefix(project_path).unwrap();
+            if manifest.is_source_file(&relative.to_string_lossy()) && !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
 fn get_directory_files_recursive(path: &str) -> io::Result<Vec<String>> {
     let mut files = Vec::new();
     let dir = fs::read_dir(path)?;
@@ -78,12 +589,177 @@ fn get_directory_files_recursive(path: &str) -> io::Result<Vec<String>> {
                 entry.file_name().to_str().unwrap()
             ))?);
         } else if file_type.is_file() && entry.file_name().to_str().unwrap().ends_with(".rots") {
-            files.push(format!(
-                "{}/{}",
-                path,
-                entry.file_name().to_str().as_ref().unwrap().to_string()
-            ));
+            files.push(format!("{}/{}", path, entry.file_name().to_str().unwrap()));
         }
     }
     Ok(files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory for the test in the temporary directory.
+    fn create_test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rotc-test-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn run_build(args: &[&str]) -> i32 {
+        build(&args.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn build_test() {
+        let dir = create_test_dir("build");
+        let project = dir.to_str().unwrap();
+        write_file(
+            &dir.join("main.rots"),
+            "import { a } from \"./lib/a\";\n[EntryPoint]\nconst main = () => { console.log(a) };",
+        );
+        write_file(&dir.join("lib/a.rots"), "export const a = 1;");
+        write_file(&dir.join("lib/b.rots"), "export const b = 2;");
+        // the files which are not written by `rotc build`
+        write_file(&dir.join("dist/web/app.js"), "app");
+        write_file(&dir.join("dist/web/types.d.ts"), "types");
+        write_file(&dir.join("dist/lib/old.js"), "old");
+
+        assert_eq!(0, run_build(&[project]));
+        let out_dir = dir.join("dist");
+        for file in [
+            "main.js",
+            "main.js.map",
+            "main.d.ts",
+            "lib/a.js",
+            "lib/b.d.ts",
+        ]
+        .iter()
+        {
+            assert!(out_dir.join(file).is_file(), "{}", file);
+        }
+        assert!(fs::read_to_string(out_dir.join("lib/a.js"))
+            .unwrap()
+            .contains("export const a = 1;"));
+
+        // the outputs of the removed source are removed, but the other files are kept
+        fs::remove_file(dir.join("lib/b.rots")).unwrap();
+        fs::remove_file(dir.join("lib/a.rots")).unwrap();
+        write_file(
+            &dir.join("main.rots"),
+            "[EntryPoint]\nconst main = () => { };",
+        );
+        assert_eq!(0, run_build(&[project]));
+        assert!(out_dir.join("main.js").is_file());
+        assert!(!out_dir.join("lib/a.js").exists());
+        assert!(!out_dir.join("lib/b.d.ts").exists());
+        assert!(out_dir.join("lib/old.js").is_file());
+        assert_eq!(
+            "app",
+            fs::read_to_string(out_dir.join("web/app.js")).unwrap()
+        );
+        assert!(out_dir.join("web/types.d.ts").is_file());
+
+        // the directory which has only the outputs is removed
+        fs::remove_file(out_dir.join("lib/old.js")).unwrap();
+        write_file(&dir.join("lib/c.rots"), "export const c = 3;");
+        assert_eq!(0, run_build(&[project]));
+        fs::remove_file(dir.join("lib/c.rots")).unwrap();
+        assert_eq!(0, run_build(&[project]));
+        assert!(!out_dir.join("lib").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn build_error_test() {
+        let dir = create_test_dir("build-error");
+        let project = dir.to_str().unwrap();
+        write_file(&dir.join("main.rots"), "const a: number = \"a\";");
+        assert_eq!(1, run_build(&[project]));
+        assert!(!dir.join("dist").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn build_package_json_test() {
+        let dir = create_test_dir("build-package-json");
+        let project = dir.to_str().unwrap();
+        write_file(&dir.join("main.rots"), "export const a = 1;");
+        let package_json = dir.join("dist/package.json");
+        assert_eq!(0, run_build(&[project]));
+        assert_eq!(
+            "{ \"type\": \"module\" }\n",
+            fs::read_to_string(&package_json).unwrap()
+        );
+        write_file(
+            &dir.join(MANIFEST_FILE_NAME),
+            "[project]\nname = \"a\"\n[build]\nmodule = \"commonjs\"",
+        );
+        assert_eq!(0, run_build(&[project]));
+        assert_eq!(
+            "{ \"type\": \"commonjs\" }\n",
+            fs::read_to_string(&package_json).unwrap()
+        );
+
+        // the file written by the user is kept
+        let content = "{ \"name\": \"app\", \"type\": \"commonjs\" }\n";
+        write_file(&package_json, content);
+        fs::remove_file(dir.join("dist").join(OUTPUT_MANIFEST_FILE_NAME)).unwrap();
+        assert_eq!(0, run_build(&[project]));
+        assert_eq!(content, fs::read_to_string(&package_json).unwrap());
+        assert_eq!(0, run_build(&[project]));
+        assert_eq!(content, fs::read_to_string(&package_json).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn build_inline_source_map_test() {
+        let dir = create_test_dir("build-inline-source-map");
+        let project = dir.to_str().unwrap();
+        write_file(&dir.join("main.rots"), "export const a = 1;");
+        write_file(
+            &dir.join(MANIFEST_FILE_NAME),
+            "[project]\nname = \"a\"\n[build]\nsource-map = \"inline\"",
+        );
+        assert_eq!(0, run_build(&[project]));
+        let code = fs::read_to_string(dir.join("dist/main.js")).unwrap();
+        assert!(code.contains("//# sourceMappingURL=data:application/json;base64,"));
+        assert!(!code.contains("main.js.map"));
+        assert!(!dir.join("dist/main.js.map").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn build_into_source_root_test() {
+        let dir = create_test_dir("build-source-root");
+        let project = dir.to_str().unwrap();
+        write_file(&dir.join("main.rots"), "export const a = 1;");
+        write_file(&dir.join("web/app.js"), "app");
+        write_file(&dir.join("web/types.d.ts"), "types");
+
+        // the output directory is the source root or contains it
+        assert_eq!(1, run_build(&[project, "--out-dir", project]));
+        let parent = dir.join("..");
+        assert_eq!(
+            1,
+            run_build(&[project, "--out-dir", parent.to_str().unwrap()])
+        );
+        assert!(!dir.join("main.js").exists());
+        assert!(dir.join("web/app.js").is_file());
+        assert!(dir.join("web/types.d.ts").is_file());
+
+        let out_dir = dir.join("dist");
+        assert_eq!(
+            0,
+            run_build(&[project, "--out-dir", out_dir.to_str().unwrap()])
+        );
+        assert!(out_dir.join("main.js").is_file());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use rotten_script_core::{
+    builder::BuildOutput,
+    compile::parse_source,
+    diagnostic::{Diagnostic, Diagnostics},
+    manifest::{Manifest, MANIFEST_FILE_NAME},
+    parser::ast::Ast,
+    semantic_analyzer::{analyze_project_files, FileMapCache},
+};
+
+use super::{
+    check_out_dir, get_out_dir, get_source_files, get_source_map_kind, load_manifest, parse_args,
+    read_output_manifest, remove_stale_outputs, write_output, write_output_manifest,
+    write_package_json,
+};
+
+const DEFAULT_INTERVAL: u64 = 500;
+
+/// A source file and the results of the last build of it.
+struct CachedFile {
+    modified: Option<SystemTime>,
+    content: String,
+    /// `None` if the file has lex or parse errors
+    ast: Option<Ast>,
+    /// The lex and parse errors, or the semantic errors found by the last check
+    diagnostics: Vec<Diagnostic>,
+    /// The files written from the file
+    outputs: Vec<PathBuf>,
+}
+
+struct Watcher {
+    project_path: String,
+    out_dir_arg: Option<String>,
+    out_dir: PathBuf,
+    manifest: Manifest,
+    manifest_modified: Option<SystemTime>,
+    files: HashMap<String, CachedFile>,
+    /// The changed files which are not built yet (e.g. another file has an error)
+    pending: HashSet<String>,
+    /// The file maps of the files analyzed before, which are reused while the files are unchanged
+    file_map_cache: FileMapCache,
+    /// true until the first successful build, which removes the stale outputs
+    is_first_build: bool,
+}
+
+/// The result of `Watcher::analyze`.
+struct Analysis {
+    checked_files: HashSet<String>,
+    /// The semantic errors in the checked files
+    errors: Vec<Diagnostic>,
+    /// `None` if the project has an error
+    outputs: Option<HashMap<String, BuildOutput>>,
+}
+
+/// Runs `rotc watch` and returns the exit code.
+/// Polls the modification times instead of using file system events so that it works in containers.
+pub fn watch(args: &[String]) -> i32 {
+    let (project_path, mut options) = match parse_args(args, &["--out-dir", "--interval"]) {
+        Some(args) => args,
+        None => return 2,
+    };
+    let interval = match options.remove("--interval").map(|x| x.parse::<u64>()) {
+        Some(Ok(interval)) => interval,
+        Some(Err(_)) => {
+            eprintln!("error: `--interval` requires milliseconds");
+            return 2;
+        }
+        None => DEFAULT_INTERVAL,
+    };
+    let manifest = match load_manifest(&project_path) {
+        Some(manifest) => manifest,
+        None => return 1,
+    };
+    let out_dir_arg = options.remove("--out-dir");
+    let out_dir = get_out_dir(&project_path, out_dir_arg.as_deref(), &manifest);
+    if let Err(e) = check_out_dir(&project_path, &out_dir, &manifest) {
+        eprintln!("error: {}", e);
+        return 1;
+    }
+    let mut watcher = Watcher {
+        out_dir,
+        manifest_modified: get_modified(&Path::new(&project_path).join(MANIFEST_FILE_NAME)),
+        project_path,
+        out_dir_arg,
+        manifest,
+        files: HashMap::new(),
+        pending: HashSet::new(),
+        file_map_cache: FileMapCache::default(),
+        is_first_build: true,
+    };
+
+    loop {
+        watcher.reload_manifest();
+        let changed = watcher.poll();
+        if !changed.is_empty() {
+            watcher.rebuild(changed);
+            println!("watching for changes...");
+        }
+        thread::sleep(Duration::from_millis(interval));
+    }
+}
+
+impl Watcher {
+    /// Reloads `rotten.toml` if it is changed and rebuilds all files with it.
+    /// The previous manifest is kept if the new one is invalid.
+    fn reload_manifest(&mut self) {
+        let path = Path::new(&self.project_path).join(MANIFEST_FILE_NAME);
+        let modified = get_modified(&path);
+        if modified == self.manifest_modified {
+            return;
+        }
+        self.manifest_modified = modified;
+        let manifest = match load_manifest(&self.project_path) {
+            Some(manifest) => manifest,
+            None => return,
+        };
+        println!("changed {}", path.display());
+        let out_dir = get_out_dir(&self.project_path, self.out_dir_arg.as_deref(), &manifest);
+        if let Err(e) = check_out_dir(&self.project_path, &out_dir, &manifest) {
+            eprintln!("error: {}", e);
+            return;
+        }
+        self.out_dir = out_dir;
+        self.manifest = manifest;
+        self.files.clear();
+        self.is_first_build = true;
+    }
+
+    /// Re-parses the added or modified files, forgets the removed files and
+    /// returns the paths of them.
+    fn poll(&mut self) -> HashSet<String> {
+        let mut changed = HashSet::new();
+        let paths = match get_source_files(&self.project_path, &self.manifest) {
+            Ok(paths) => paths,
+            Err(e) => {
+                eprintln!("error: cannot read `{}`: {}", self.project_path, e);
+                return changed;
+            }
+        };
+
+        for path in &paths {
+            let modified = get_modified(Path::new(path));
+            if let Some(file) = self.files.get(path) {
+                if file.modified == modified {
+                    continue;
+                }
+            }
+            let content = match fs::read_to_string(path) {
+                Ok(content) => content,
+                // the file is being written or removed; retry at the next poll
+                Err(_) => continue,
+            };
+            if let Some(file) = self.files.get_mut(path) {
+                file.modified = modified;
+                if file.content == content {
+                    continue;
+                }
+            }
+            let mut diagnostics = Diagnostics::new();
+            let ast = parse_source(path, &content, &mut diagnostics);
+            let diagnostics = diagnostics.into_vec();
+            let outputs = self.files.remove(path).map(|x| x.outputs);
+            self.files.insert(
+                path.clone(),
+                CachedFile {
+                    modified,
+                    content,
+                    ast,
+                    diagnostics,
+                    outputs: outputs.unwrap_or_default(),
+                },
+            );
+            changed.insert(path.clone());
+        }
+
+        let removed = self
+            .files
+            .keys()
+            .filter(|x| !paths.contains(x))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in removed {
+            for output in self.files.remove(&path).unwrap().outputs {
+                let _ = fs::remove_file(output);
+            }
+            changed.insert(path);
+        }
+        changed
+    }
+
+    /// Re-analyzes the changed files and their importers, prints the diagnostics and
+    /// rewrites the outputs of them if the project has no error.
+    fn rebuild(&mut self, changed: HashSet<String>) {
+        let mut changed_files = changed.iter().cloned().collect::<Vec<_>>();
+        changed_files.sort();
+        // every file is changed at the first build
+        for path in changed_files.iter().filter(|_| !self.is_first_build) {
+            let state = if self.files.contains_key(path) {
+                "changed"
+            } else {
+                "removed"
+            };
+            println!("{} {}", state, path);
+        }
+        self.pending.extend(changed);
+
+        if self.files.values().any(|x| x.ast.is_none()) {
+            self.print_diagnostics();
+            return;
+        }
+        let Analysis {
+            checked_files,
+            errors,
+            outputs,
+        } = self.analyze();
+        for path in &checked_files {
+            if let Some(file) = self.files.get_mut(path) {
+                file.diagnostics.clear();
+            }
+        }
+        for error in errors {
+            let path = error.span.as_ref().map(|x| x.path.as_str());
+            if let Some(file) = path.and_then(|x| self.files.get_mut(x)) {
+                file.diagnostics.push(error);
+            }
+        }
+        let outputs = match outputs {
+            Some(outputs) => outputs,
+            None => {
+                self.print_diagnostics();
+                return;
+            }
+        };
+        match self.write_outputs(outputs) {
+            Ok(count) => {
+                println!("wrote {} file(s) to {}", count, self.out_dir.display());
+                self.pending.clear();
+            }
+            Err(e) => eprintln!("error: cannot write to `{}`: {}", self.out_dir.display(), e),
+        }
+    }
+
+    /// Analyzes the pending files and their importers and builds them if the project has no error.
+    fn analyze(&mut self) -> Analysis {
+        let ast_list = self
+            .files
+            .iter()
+            .map(|(path, file)| (path.clone(), file.ast.as_ref().unwrap()))
+            .collect();
+        let project = analyze_project_files(
+            &self.manifest,
+            ast_list,
+            &self.pending,
+            &mut self.file_map_cache,
+        );
+        let checked_files = project.get_checked_files().clone();
+        let mut errors = Diagnostics::new();
+        project.report_errors(&mut errors);
+        let errors = errors.into_vec();
+        // the errors of the other files are kept until they are checked again
+        let has_other_error = self
+            .files
+            .iter()
+            .any(|(path, file)| !checked_files.contains(path) && !file.diagnostics.is_empty());
+        if !errors.is_empty() || has_other_error {
+            return Analysis {
+                checked_files,
+                errors,
+                outputs: None,
+            };
+        }
+
+        let mut options = self.manifest.build_options();
+        options.source_map = get_source_map_kind(&self.manifest);
+        options.sources_content = checked_files
+            .iter()
+            .filter_map(|x| Some((x.clone(), self.files.get(x)?.content.clone())))
+            .collect();
+        let outputs = project.build_files(&options, &checked_files);
+        Analysis {
+            checked_files,
+            errors,
+            outputs: Some(outputs),
+        }
+    }
+
+    /// Writes the outputs and returns the number of the written files.
+    /// The first build also removes the stale outputs of the previous runs.
+    fn write_outputs(&mut self, outputs: HashMap<String, BuildOutput>) -> io::Result<usize> {
+        let mut count = 0;
+        let previous = read_output_manifest(&self.out_dir);
+        let package_json = write_package_json(&self.out_dir, &self.manifest, &previous)?;
+        for (path, output) in outputs {
+            let written = write_output(
+                &self.project_path,
+                &self.out_dir,
+                &self.manifest,
+                &path,
+                output,
+            )?;
+            count += written.len();
+            self.files.get_mut(&path).unwrap().outputs = written;
+        }
+        let mut written = self
+            .files
+            .values()
+            .flat_map(|x| x.outputs.iter().cloned())
+            .collect::<HashSet<_>>();
+        written.extend(package_json);
+        if self.is_first_build {
+            remove_stale_outputs(&self.out_dir, &previous, &written)?;
+            self.is_first_build = false;
+        }
+        // the outputs of the removed files are already removed by `poll`
+        write_output_manifest(&self.out_dir, &written)?;
+        Ok(count)
+    }
+
+    fn print_diagnostics(&self) {
+        let mut paths = self
+            .files
+            .iter()
+            .filter(|(_, file)| !file.diagnostics.is_empty())
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        let mut count = 0;
+        for path in paths {
+            for diagnostic in &self.files[path].diagnostics {
+                let get_content = |path: &str| Some(self.files.get(path)?.content.as_str());
+                println!("{}\n", diagnostic.render(get_content));
+                count += 1;
+            }
+        }
+        println!("found {} error(s)", count);
+    }
+}
+
+fn get_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|x| x.modified()).ok()
+}
@@ -3,8 +3,14 @@ import {
   add_file,
   execute_processing,
   eject_sourcecode,
+  eject_declaration,
+  set_manifest,
+  get_out_dir,
+  get_source_roots,
+  get_output_files,
 } from "../../pkg/rotten_script_wasm";
 import fs from "fs";
+import path from "path";
 import { exit } from "process";
 
 const helpText = "Usage: npm start -- [filename|dirName] [-d]";
@@ -25,10 +31,41 @@ const getDirectoryRecursive = (path: string): string[] => {
   return paths;
 };
 
+// Returns an error if `outDir` is a source root or contains one, where removing it
+// would remove the sources.
+const checkOutDir = (projectPath: string, outDir: string): string | undefined => {
+  const absoluteOutDir = fs.existsSync(outDir)
+    ? fs.realpathSync(outDir)
+    : path.resolve(outDir);
+  for (const item of get_source_roots() as string[]) {
+    const root = path.resolve(projectPath, item);
+    if (!fs.existsSync(root)) {
+      continue;
+    }
+    const relative = path.relative(absoluteOutDir, fs.realpathSync(root));
+    if (relative.split(path.sep)[0] !== ".." && !path.isAbsolute(relative)) {
+      return `the output directory \`${outDir}\` must not contain the source root \`${root}\``;
+    }
+  }
+  return undefined;
+};
+
 if (args.length < 3) {
   console.log(helpText);
 } else {
   if (args.length > 3 && args[3] === "-d") {
+    const manifestPath = `${args[2]}/rotten.toml`;
+    if (fs.existsSync(manifestPath)) {
+      if (!set_manifest(args[2], fs.readFileSync(manifestPath, "utf-8"))) {
+        exit(-1);
+      }
+    }
+    const outDir = `${args[2]}/${get_out_dir()}`;
+    const error = checkOutDir(args[2], outDir);
+    if (error) {
+      console.error(error);
+      exit(-1);
+    }
     const files = getDirectoryRecursive(args[2]);
     for (const item of files) {
       const text = fs.readFileSync(item, "utf-8");
@@ -38,24 +75,26 @@ if (args.length < 3) {
     if (!result) {
       exit(-1);
     }
-    if (fs.existsSync(`${args[2]}/dist`)) {
-      fs.rmSync(`${args[2]}/dist`, { recursive: true });
+    if (fs.existsSync(outDir)) {
+      fs.rmSync(outDir, { recursive: true });
     }
 
-    fs.mkdirSync(`${args[2]}/dist`);
-    for (const item of files) {
+    fs.mkdirSync(outDir);
+    // the sources excluded by the manifest have no outputs
+    for (const item of get_output_files() as string[]) {
       const last_slash = item.lastIndexOf("/");
       const dir_name = item.slice(0, last_slash);
-      const target = `${args[2]}/dist/${dir_name}`;
-      if (!fs.existsSync(target)) {
-        fs.mkdirSync(target);
-      }
+      fs.mkdirSync(`${outDir}/${dir_name}`, { recursive: true });
       const deletedRots = item.match(/^(.*)\.rots$/)?.[1];
       if (deletedRots) {
         fs.writeFileSync(
-          `${args[2]}/dist/${deletedRots}.js`,
+          `${outDir}/${deletedRots}.js`,
           eject_sourcecode(item)
         );
+        fs.writeFileSync(
+          `${outDir}/${deletedRots}.d.ts`,
+          eject_declaration(item)
+        );
       }
     }
   } else {
@@ -4,8 +4,11 @@ use once_cell::sync::Lazy;
 // use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::{collections::HashMap, sync::Mutex};
 
+use js_sys::{Array, JSON};
 use rotten_script_core::{
-    lexer::Lexer,
+    builder::BuildOutput,
+    compile::{check as check_project, compile},
+    manifest::Manifest,
     parser::{token_stack::TokenStack, Parser},
     semantic_analyzer::analyze,
 };
@@ -46,62 +49,101 @@ pub fn process(file_str: &str) {
     }
     let ast = parser.ast;
     let tree = analyze(vec![("sample1.rots".to_string(), &ast)]);
+    for error in tree.get_semantic_errors() {
+        log_string(format!("{}", error));
+    }
     let result = tree.call_builder(false);
 
     for item in result {
         log(&format!("// {}\n", item.0));
-        log(&format!("{}\n", item.1));
+        log(&format!("{}\n", item.1.code));
     }
 }
 
 #[wasm_bindgen]
 pub fn execute_processing() -> bool {
     rotten_script_core::LOGGER.lock().unwrap().logger = Some(Box::new(log_string));
-    let files = &SOURCES.lock().unwrap().file_pairs;
-    let mut has_error = false;
-    let asts = files
+    let (manifest, files) = get_project_files();
+    let output = compile(&manifest, &files, &manifest.build_options());
+    if !output.diagnostics.is_empty() {
+        log_string(output.diagnostics.render(|path| {
+            let (_, content) = files.iter().find(|(x, _)| x == path)?;
+            Some(content.as_str())
+        }));
+    }
+    if output.diagnostics.has_errors() {
+        return false;
+    }
+    RESULTS.lock().unwrap().file_pairs = Some(output.outputs);
+    true
+}
+
+/// Checks the files added by `add_file` without building them and returns the diagnostics
+/// as an array of the objects printed by `rotc check --message-format=json`.
+#[wasm_bindgen]
+pub fn check() -> Array {
+    let (manifest, files) = get_project_files();
+    let json = check_project(&manifest, &files).to_json();
+    JSON::parse(&json)
+        .map(|x| x.unchecked_into())
+        .unwrap_or_else(|_| Array::new())
+}
+
+/// Returns the manifest and the files added by `add_file` which the manifest selects.
+fn get_project_files() -> (Manifest, Vec<(String, String)>) {
+    let sources = SOURCES.lock().unwrap();
+    let (project_path, manifest) = sources
+        .manifest
+        .clone()
+        .unwrap_or_else(|| (String::new(), Manifest::new(String::new())));
+    let files = sources
+        .file_pairs
         .iter()
-        .map(|x| {
-            let mut lexer = Lexer::new(&x.1, &x.0);
-            match lexer.lex() {
-                Ok(_) => {}
-                Err(e) => {
-                    log_string(format!("{}", e));
-                    has_error = true;
-                }
-            }
-            let token_stack = &mut TokenStack::new(&lexer.tokens);
-            let mut parser = Parser::new(token_stack);
-            match parser.parse() {
-                Ok(_) => {}
-                Err(e) => {
-                    log_string(format!("{}", e));
-                    has_error = true;
-                }
-            }
-            (x.0.clone(), parser.ast)
+        .filter(|(path, _)| {
+            let relative = path.strip_prefix(&project_path).unwrap_or(path);
+            manifest.is_source_file(relative.trim_start_matches('/'))
         })
-        .collect::<Vec<_>>();
-    if has_error {
-        false
+        .cloned()
+        .collect();
+    (manifest, files)
+}
+
+#[wasm_bindgen]
+pub fn eject_sourcecode(file_path: &str) -> String {
+    let ts = RESULTS.lock().unwrap();
+    let map = ts.file_pairs.as_ref().unwrap();
+    if let Some(t) = map.get(file_path) {
+        t.code.clone()
     } else {
-        let tree = analyze(asts.iter().map(|x| (x.0.clone(), &x.1)).collect());
-        RESULTS.lock().unwrap().file_pairs = Some(tree.call_builder(false));
-        true
+        "".to_string()
     }
 }
 
 #[wasm_bindgen]
-pub fn eject_sourcecode(file_path: &str) -> String {
+pub fn eject_declaration(file_path: &str) -> String {
     let ts = RESULTS.lock().unwrap();
     let map = ts.file_pairs.as_ref().unwrap();
     if let Some(t) = map.get(file_path) {
-        t.clone()
+        t.declaration.clone()
     } else {
         "".to_string()
     }
 }
 
+/// Returns the paths of the files built by `execute_processing`, which excludes the files
+/// the manifest does not select.
+#[wasm_bindgen]
+pub fn get_output_files() -> Array {
+    let ts = RESULTS.lock().unwrap();
+    let mut paths = ts
+        .file_pairs
+        .as_ref()
+        .map(|x| x.keys().collect::<Vec<_>>())
+        .unwrap_or_default();
+    paths.sort();
+    paths.into_iter().map(|x| JsValue::from_str(x)).collect()
+}
+
 #[wasm_bindgen]
 pub fn add_file(full_path: &str, file_strs: &str) {
     SOURCES
@@ -111,19 +153,59 @@ pub fn add_file(full_path: &str, file_strs: &str) {
         .push((String::from(full_path), String::from(file_strs)));
 }
 
+/// Sets `rotten.toml` of the project placed at `project_path`.
+/// The files added by `add_file` are filtered by the manifest.
+#[wasm_bindgen]
+pub fn set_manifest(project_path: &str, manifest: &str) -> bool {
+    match Manifest::parse(manifest) {
+        Ok(manifest) => {
+            SOURCES.lock().unwrap().manifest = Some((project_path.to_string(), manifest));
+            true
+        }
+        Err(e) => {
+            log_string(format!("{}", e));
+            false
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_out_dir() -> String {
+    match &SOURCES.lock().unwrap().manifest {
+        Some((_, manifest)) => manifest.out_dir.clone(),
+        None => "dist".to_string(),
+    }
+}
+
+/// Returns the source roots of the manifest, which are relative to the project path.
+#[wasm_bindgen]
+pub fn get_source_roots() -> Array {
+    match &SOURCES.lock().unwrap().manifest {
+        Some((_, manifest)) => manifest
+            .source_roots
+            .iter()
+            .map(|x| JsValue::from_str(x))
+            .collect(),
+        None => Array::of1(&JsValue::from_str(".")),
+    }
+}
+
 #[derive(Debug)]
 pub struct SourceFiles {
     pub file_pairs: Vec<(String, String)>,
+    // (project path, manifest)
+    pub manifest: Option<(String, Manifest)>,
 }
 
 #[derive(Debug)]
 pub struct TranspiledSources {
-    pub file_pairs: Option<HashMap<String, String>>,
+    pub file_pairs: Option<HashMap<String, BuildOutput>>,
 }
 
 static SOURCES: Lazy<Mutex<SourceFiles>> = Lazy::new(|| {
     Mutex::new(SourceFiles {
         file_pairs: Vec::new(),
+        manifest: None,
     })
 });
 